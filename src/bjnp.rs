@@ -0,0 +1,61 @@
+use crate::discovery::Discovered;
+use crate::probes::{BJNP_DISCOVER, BJNP_PORT, MFNP_PORT, bjnp_packet, get_bjnp_info, is_bjnp_reply};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::{Instant, timeout_at};
+
+/// 各网卡的定向广播地址，加上 255.255.255.255 (多网卡时系统只会从默认网卡发出)
+fn broadcast_addrs() -> Vec<Ipv4Addr> {
+    let mut addrs: Vec<Ipv4Addr> = if_addrs::get_if_addrs()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|i| !i.is_loopback())
+        .filter_map(|i| match i.addr {
+            if_addrs::IfAddr::V4(v4) => v4.broadcast,
+            _ => None,
+        })
+        .collect();
+    addrs.push(Ipv4Addr::BROADCAST);
+    addrs.sort();
+    addrs.dedup();
+    addrs
+}
+
+/// 在本地网段广播 BJNP / MFNP 发现报文，收集在 `wait` 时间内响应的 Canon 设备，
+/// 再逐台查询 IEEE 1284 设备 ID 取得型号
+pub async fn discover(wait: Duration) -> Vec<Discovered> {
+    let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await {
+        Ok(s) => s,
+        Err(e) => { eprintln!("{}", tr!(BjnpFailed, e)); return Vec::new(); }
+    };
+    if let Err(e) = socket.set_broadcast(true) {
+        eprintln!("{}", tr!(BjnpFailed, e));
+        return Vec::new();
+    }
+    for addr in broadcast_addrs() {
+        for port in [BJNP_PORT, MFNP_PORT] {
+            // 个别网卡不允许广播，换下一个地址即可
+            let _ = socket.send_to(&bjnp_packet(port, BJNP_DISCOVER, 1), SocketAddr::new(IpAddr::V4(addr), port)).await;
+        }
+    }
+
+    let deadline = Instant::now() + wait;
+    // 同时开了打印和扫描服务的设备会响应两次，优先用打印端口查询型号
+    let mut devices: BTreeMap<IpAddr, u16> = BTreeMap::new();
+    let mut buf = [0u8; 2048];
+    while let Ok(Ok((n, from))) = timeout_at(deadline, socket.recv_from(&mut buf)).await {
+        if is_bjnp_reply(&buf[..n], BJNP_DISCOVER) {
+            let port = devices.entry(from.ip()).or_insert(from.port());
+            *port = (*port).min(from.port());
+        }
+    }
+
+    let timeout_ms = wait.as_millis().max(1000) as u64;
+    let lookups = devices.into_iter().map(|(ip, port)| async move {
+        let model = get_bjnp_info(ip, port, timeout_ms).await;
+        Discovered { ip, port, source: "BJNP", name: None, model, txt: BTreeMap::new() }
+    });
+    futures::future::join_all(lookups).await
+}
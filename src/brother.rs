@@ -0,0 +1,63 @@
+use crate::probes::{self, Context, Source};
+use crate::{http, snmp};
+use std::net::{IpAddr, SocketAddr};
+
+/// Brother 私有 MIB (企业号 2435)：brInfoDeviceID 是 IEEE 1284 设备 ID，brInfoSerialNumber 是序列号
+const OID_DEVICE_ID: &[u64] = &[1, 3, 6, 1, 4, 1, 2435, 2, 3, 9, 1, 1, 7, 0];
+const OID_SERIAL: &[u64] = &[1, 3, 6, 1, 4, 1, 2435, 2, 3, 9, 4, 2, 1, 5, 5, 1, 0];
+/// 管理网页的“设备信息”页，不登录也能打开
+const INFO_PATH: &str = "/general/information.html";
+
+/// Brother 设备的确切型号和序列号
+pub struct Details {
+    pub model: Option<String>,
+    pub serial: Option<String>,
+}
+
+/// 型号或 MAC 厂商里出现 Brother 时返回 true
+pub fn is_brother(text: &str) -> bool {
+    text.to_lowercase().contains("brother")
+}
+
+/// 网页里 `<dt>Model Name</dt><dd>HL-L2350DW series</dd>` (或表格 `<th>` / `<td>`) 形式的字段：
+/// 标签后面第一段非空文本就是值
+fn page_field(body: &str, labels: &[&str]) -> Option<String> {
+    // 只做 ASCII 小写，字节位置与原文一致
+    let lower = body.to_ascii_lowercase();
+    let start = labels.iter().find_map(|l| lower.find(&l.to_ascii_lowercase()).map(|i| i + l.len()))?;
+    body[start..].split('<').skip(1).find_map(|chunk| {
+        let text = chunk.split_once('>')?.1.replace("&#32;", " ").replace("&nbsp;", " ");
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    })
+}
+
+async fn info_page(source: Source, ip: IpAddr, timeout_ms: u64) -> Option<Details> {
+    let client = http::client(source, ip, timeout_ms).build().ok()?;
+    let url = format!("http://{}{}", SocketAddr::new(ip, http::HTTP_PORT), INFO_PATH);
+    let resp = client.get(url).send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    let body = resp.text().await.ok()?;
+    let model = page_field(&body, &["Model Name", "Model&#32;Name"]);
+    let serial = page_field(&body, &["Serial no.", "Serial&#32;no.", "Serial Number"]);
+    (model.is_some() || serial.is_some()).then_some(Details { model, serial })
+}
+
+/// 先查私有 MIB，缺的字段再从管理网页补。两边都没有结果时返回 None
+pub async fn details(context: &Context, ip: IpAddr, timeout_ms: u64) -> Option<Details> {
+    let values = snmp::get(&context.snmp, ip, &[OID_DEVICE_ID, OID_SERIAL]).await.unwrap_or_default();
+    let text = |i: usize| values.get(i).cloned().flatten().and_then(|v| v.text());
+    let mut details = Details {
+        model: text(0).and_then(|id| probes::parse_bjnp(id.as_bytes())),
+        serial: text(1),
+    };
+    if (details.model.is_none() || details.serial.is_none())
+        && let Some(page) = info_page(context.source, ip, timeout_ms).await
+    {
+        details.model = details.model.or(page.model);
+        details.serial = details.serial.or(page.serial);
+    }
+    (details.model.is_some() || details.serial.is_some()).then_some(details)
+}
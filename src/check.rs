@@ -0,0 +1,92 @@
+use crate::cli::CheckArgs;
+use crate::probes::Context;
+use crate::scan_network;
+use crate::targets::Targets;
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::process::ExitCode;
+
+/// Nagios 插件约定的退出码
+const OK: u8 = 0;
+const WARNING: u8 = 1;
+const CRITICAL: u8 = 2;
+const UNKNOWN: u8 = 3;
+
+fn unknown(msg: String) -> ExitCode {
+    println!("PRINTERS UNKNOWN - {}", msg);
+    ExitCode::from(UNKNOWN)
+}
+
+/// 期望清单文件：每行一个 IP，支持空行和 `#` 注释
+fn load_expect_file(path: &std::path::Path) -> Result<Vec<IpAddr>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| tr!(ReadFailed, path.display(), e))?;
+    text.lines()
+        .map(|l| l.split('#').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty())
+        .map(|l| l.parse().map_err(|_| tr!(InvalidExpectIp, path.display(), l)))
+        .collect()
+}
+
+fn join(ips: &BTreeSet<IpAddr>) -> String {
+    ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// 扫描后与期望清单比较，输出一行 Nagios 格式的状态并返回对应退出码：
+/// 缺失期望的打印机为 CRITICAL，出现清单外的打印机为 WARNING
+pub async fn run_check(args: CheckArgs) -> ExitCode {
+    let context = match Context::from_args(&args.target) {
+        Ok(c) => c,
+        Err(e) => return unknown(e.to_string()),
+    };
+    let targets = match Targets::from_args(&args.target) {
+        Ok(t) => t,
+        Err(e) => return unknown(tr!(InvalidNetwork, e)),
+    };
+
+    let mut expected: BTreeSet<IpAddr> = args.expect.iter().copied().collect();
+    if let Some(path) = &args.expect_file {
+        match load_expect_file(path) {
+            Ok(ips) => expected.extend(ips),
+            Err(e) => return unknown(e),
+        }
+    }
+    if expected.is_empty() {
+        return unknown(tr!(NoExpected));
+    }
+
+    let found: BTreeSet<IpAddr> = scan_network(&targets, &args.target, &context, false, None, |_| {})
+        .await
+        .0
+        .into_iter()
+        .map(|p| p.ip)
+        .collect();
+
+    let missing: BTreeSet<IpAddr> = expected.difference(&found).copied().collect();
+    let unexpected: BTreeSet<IpAddr> = found.difference(&expected).copied().collect();
+
+    let (code, status) = if !missing.is_empty() {
+        (CRITICAL, "CRITICAL")
+    } else if !unexpected.is_empty() {
+        (WARNING, "WARNING")
+    } else {
+        (OK, "OK")
+    };
+
+    let mut detail = format!("{}/{} expected printers online", expected.len() - missing.len(), expected.len());
+    if !missing.is_empty() {
+        detail.push_str(&format!(", missing: {}", join(&missing)));
+    }
+    if !unexpected.is_empty() {
+        detail.push_str(&format!(", unexpected: {}", join(&unexpected)));
+    }
+    // `|` 之后是 perfdata，供 Nagios/Icinga 画图
+    println!(
+        "PRINTERS {} - {} | found={} missing={} unexpected={}",
+        status,
+        detail,
+        found.len(),
+        missing.len(),
+        unexpected.len()
+    );
+    ExitCode::from(code)
+}
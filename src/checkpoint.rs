@@ -0,0 +1,93 @@
+use crate::PrinterInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// 两次写入检查点文件的最短间隔
+const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 检查点文件的内容
+#[derive(Serialize, Deserialize, Default)]
+pub struct State {
+    /// 扫描目标，恢复时必须与本次的目标一致
+    pub target: String,
+    /// 已经探测完的主机 (不管端口是否开放)
+    pub done: BTreeSet<IpAddr>,
+    pub ports_open: usize,
+    pub total_response_ms: u64,
+    pub printers: Vec<PrinterInfo>,
+}
+
+/// `--checkpoint`：定期把扫描进度写入文件，中断后用 `--resume` 跳过已经扫过的主机
+pub struct Checkpoint {
+    path: PathBuf,
+    state: State,
+    last_saved: Instant,
+}
+
+impl Checkpoint {
+    /// `resume` 为 true 时读取已有的检查点；文件不存在时从头开始
+    pub fn open(path: &Path, target: &str, resume: bool) -> Result<Self, String> {
+        let fresh = State { target: target.to_string(), ..Default::default() };
+        let state = match resume {
+            false => fresh,
+            true => match std::fs::read_to_string(path) {
+                Ok(text) => {
+                    let state: State = serde_json::from_str(&text).map_err(|e| tr!(CheckpointInvalid, path.display(), e))?;
+                    if state.target != target {
+                        return Err(tr!(CheckpointMismatch, path.display(), state.target));
+                    }
+                    status!("{}", tr!(CheckpointResumed, state.done.len(), state.printers.len()));
+                    state
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    status!("{}", tr!(CheckpointMissing, path.display()));
+                    fresh
+                }
+                Err(e) => return Err(tr!(ReadFailed, path.display(), e)),
+            },
+        };
+        Ok(Checkpoint { path: path.to_path_buf(), state, last_saved: Instant::now() })
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// 记录一台探测完的主机，距上次写入超过 `SAVE_INTERVAL` 时写一次文件
+    pub fn record(&mut self, ip: IpAddr, response_ms: Option<u64>, printers: &[PrinterInfo]) {
+        self.state.done.insert(ip);
+        if let Some(ms) = response_ms {
+            self.state.ports_open += 1;
+            self.state.total_response_ms += ms;
+        }
+        self.state.printers.extend_from_slice(printers);
+        if self.last_saved.elapsed() >= SAVE_INTERVAL {
+            self.save();
+        }
+    }
+
+    /// 先写临时文件再改名，中途被打断也不会留下写了一半的检查点
+    fn save(&mut self) {
+        let tmp = self.path.with_extension("tmp");
+        let result = serde_json::to_vec(&self.state)
+            .map_err(std::io::Error::other)
+            .and_then(|data| std::fs::write(&tmp, data))
+            .and_then(|_| std::fs::rename(&tmp, &self.path));
+        if let Err(e) = result {
+            eprintln!("{}", tr!(StateWriteFailed, self.path.display(), e));
+        }
+        self.last_saved = Instant::now();
+    }
+
+    /// 全部主机扫完，检查点不再需要
+    pub fn finish(self) {
+        if let Err(e) = std::fs::remove_file(&self.path)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            eprintln!("{}", tr!(StateWriteFailed, self.path.display(), e));
+        }
+    }
+}
@@ -0,0 +1,700 @@
+use crate::i18n::{self, Lang};
+use crate::output::{self, ColorChoice, OutputFormat, ProbeReport, ScanReport};
+use crate::probes::{Context, PRINTER_PORT, is_port_open, run_all_probes};
+use crate::snmp::{self, AuthProtocol, PrivProtocol, SNMP_PORT, SNMP_TIMEOUT_MS, SnmpVersion};
+use crate::targets::Targets;
+use crate::{check, checkpoint, diff, monitor, mqtt, notify, pjl, pjl_monitor, printer_mib, report, scan_network, serve, snmp_walk, syslog_sink, traps, webhook};
+use clap::{Args, Parser, Subcommand};
+use colored::*;
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// 网络打印机发现工具
+///
+/// 不带子命令时等同于 `scan`，兼容旧的 `printer_scanner -n <NETWORK>` 用法。
+#[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// 只输出结果本身，不显示横幅、进度和提示信息
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// 提示和错误信息的语言，默认按系统语言环境 (LANG) 选择
+    #[arg(long, global = true, value_enum)]
+    pub lang: Option<Lang>,
+
+    /// 是否输出 ANSI 颜色
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    #[command(flatten)]
+    pub scan: ScanArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// 扫描整个网段，识别其中的打印机
+    Scan(ScanArgs),
+    /// 对单台主机执行全部探测，显示每个协议的原始响应和解析结果
+    Probe(ProbeArgs),
+    /// 按固定间隔持续扫描，报告设备上线、下线和型号变化
+    Monitor(MonitorArgs),
+    /// 比较两次保存的扫描结果 (JSON / YAML)
+    Diff(DiffArgs),
+    /// 启动 HTTP API 服务，按需扫描并提供最近一次结果
+    Serve(ServeArgs),
+    /// Nagios/Icinga 检查插件：与期望清单比较并返回 OK/WARNING/CRITICAL 退出码
+    Check(CheckArgs),
+    /// 把文件原样发送到打印机的 9100 端口打印
+    Print(PrintArgs),
+    /// 打开 PJL USTATUS，持续输出打印机主动上报的状态 (卡纸、缺纸、作业完成等)
+    MonitorPjl(PjlMonitorArgs),
+    /// 遍历打印机的 Printer MIB / Host Resources MIB，按名称列出各变量
+    Snmpwalk(SnmpWalkArgs),
+    /// 接收 SNMP trap，解码并输出打印机的告警 (卡纸、缺纸、缺粉等)
+    Traps(TrapArgs),
+}
+
+/// 各子命令共用的结果输出参数
+#[derive(Args, Debug, Clone)]
+pub struct OutputArgs {
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// 把结果写入文件而不是 stdout，状态信息仍输出到 stderr
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// 扫描范围与节奏，`scan` 和 `monitor` 共用
+#[derive(Args, Debug, Clone)]
+pub struct TargetArgs {
+    /// 扫描目标，可重复或用逗号分隔。支持 CIDR (192.168.1.0/24)、地址范围 (192.168.1.10-50)、
+    /// 单个 IP 或主机名 (printer01.corp)，后面可跟端口列表 (192.168.1.0/24:9100,631)
+    /// 未指定 -n 和 --targets-file 时扫描本机各网卡所在的 IPv4 网段
+    #[arg(short, long)]
+    pub network: Vec<String>,
+
+    /// 从文件读取扫描目标，每行一个 (写法同 -n)，`-` 表示 stdin；支持空行和 `#` 注释
+    #[arg(long)]
+    pub targets_file: Option<PathBuf>,
+
+    /// 从 nmap XML 结果 (`nmap -oX`) 导入 9100 / 631 / 515 开放的主机，
+    /// 这些主机跳过端口探测，直接识别型号
+    #[arg(long, value_name = "PATH")]
+    pub import_nmap: Option<PathBuf>,
+
+    /// 目标没有写端口时探测的端口，按给出的顺序尝试，第一个开放的用于识别。
+    /// 打印语言探测 (PJL、SGD 等) 用于 515 / 80 / 443 / 631 以外的所有端口，打印端口改过也能识别
+    #[arg(long, value_delimiter = ',', default_value = "9100", value_name = "PORTS")]
+    pub ports: Vec<u16>,
+
+    /// 跳过的地址 (网关、服务器等)，写法同 -n 但不带端口，可重复或用逗号分隔
+    #[arg(long, value_delimiter = ',')]
+    pub exclude: Vec<String>,
+
+    /// 从文件读取要跳过的地址，每行一个，`-` 表示 stdin
+    #[arg(long)]
+    pub exclude_file: Option<PathBuf>,
+
+    /// 从指定网卡发出探测；未指定 -n 和 --targets-file 时只扫描该网卡所在的网段
+    #[arg(short, long)]
+    pub interface: Option<String>,
+
+    #[arg(short, long, default_value_t = 2000)]
+    pub timeout_ms: u64,
+
+    #[arg(short, long, default_value_t = 50)]
+    pub concurrency: usize,
+
+    /// 先用 ICMP ping 过滤掉不在线的地址，只对响应的主机做 TCP 探测。
+    /// 适合大部分地址空闲的大网段；禁 ping 的打印机会被漏掉
+    #[arg(long)]
+    pub ping_first: bool,
+
+    /// 以随机顺序扫描主机，避免按地址顺序扫过网段触发基于速率的入侵检测告警
+    #[arg(long)]
+    pub randomize: bool,
+
+    /// 用原始套接字 SYN 半开扫描代替逐个 TCP 连接来发现开放端口 (需要 root / CAP_NET_RAW，仅 IPv4)
+    #[arg(long)]
+    pub syn: bool,
+
+    /// 不在第一个回应的协议处停下，运行全部识别协议，按来源可信度和相互印证选出型号，
+    /// 结果带上置信度和各协议给出的型号。每台主机的探测时间会变长
+    #[arg(long)]
+    pub all_probes: bool,
+
+    /// 不对发现的打印机做反向 DNS (PTR) 解析
+    #[arg(long)]
+    pub no_rdns: bool,
+
+    /// 不向发现的打印机发送 NetBIOS 名称查询 (UDP 137)
+    #[arg(long)]
+    pub no_netbios: bool,
+
+    /// 查询发现的打印机的耗材余量 (HP 内嵌网页服务器的 LEDM 耗材接口，其他设备用 Printer MIB 的耗材表)
+    #[arg(long)]
+    pub with_supplies: bool,
+
+    /// 查询各进纸盒的纸张和余量 (Printer MIB 的 prtInputTable)，需要设备回应 SNMP
+    #[arg(long)]
+    pub with_trays: bool,
+
+    /// 连接识别为 HP 的设备的 telnet (23) 端口，读取 JetDirect 的固件版本、MAC 和 TCP/IP 设置
+    #[arg(long)]
+    pub with_telnet: bool,
+
+    /// 对斑马打印机发送 `~HQES`，读取碳带用完、切刀卡纸等错误和警告
+    #[arg(long)]
+    pub with_status: bool,
+
+    /// 从斑马打印机取回配置标签 (`^HH`)，记录浓度、速度、介质类型等设置
+    #[arg(long)]
+    pub with_config: bool,
+
+    /// 对斑马打印机读取的 SGD 变量，逗号分隔；`allcv` 表示全部变量
+    #[arg(long, value_delimiter = ',', value_name = "VARS")]
+    pub sgd_vars: Vec<String>,
+
+    /// 对 PJL 打印机查询 INFO CONFIG / STATUS / PAGECOUNT / MEMORY，记录状态码、页数和内存
+    #[arg(long)]
+    pub with_pjl_info: bool,
+
+    /// 安全审计：对 PJL 打印机发送 `@PJL FSDIRLIST`，检查文件系统能否被任意浏览
+    #[arg(long)]
+    pub with_pjl_fs: bool,
+
+    /// 安全审计：用常见 community 和 `--community` 的候选把 sysContact 原样写回，检查哪些 community 允许 SNMP SET
+    #[arg(long)]
+    pub audit_snmp_write: bool,
+
+    /// 完整的 MAC 厂商库 (IEEE oui.txt、Wireshark manuf 或 nmap-mac-prefixes)，
+    /// 不指定时只识别内置的打印机厂商
+    #[arg(long, value_name = "PATH")]
+    pub oui_file: Option<PathBuf>,
+
+    /// 同时通过 mDNS/Bonjour 发现打印机 (_pdl-datastream、_ipp、_printer 服务)
+    #[arg(long)]
+    pub mdns: bool,
+
+    /// 同时通过 WS-Discovery (UDP 3702) 发现打印机，适合关闭了 9100 的新款 HP / Canon
+    #[arg(long)]
+    pub wsd: bool,
+
+    /// 同时通过 SSDP/UPnP (UDP 1900) 查找打印机，部分 Epson / Samsung 设备只有这种方式容易识别
+    #[arg(long)]
+    pub ssdp: bool,
+
+    /// 同时在本地网段广播 Canon BJNP 发现报文 (UDP 8611/8612)，找出没开 9100 的 Canon 设备
+    #[arg(long)]
+    pub bjnp: bool,
+
+    /// 组播发现等待响应的时间
+    #[arg(long, default_value = "3s")]
+    pub discover_wait: humantime::Duration,
+
+    #[command(flatten)]
+    pub snmp: SnmpArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ScanArgs {
+    #[command(flatten)]
+    pub target: TargetArgs,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+
+    /// 额外生成一份独立的 HTML 报告
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub mqtt: MqttArgs,
+
+    #[command(flatten)]
+    pub webhook: WebhookArgs,
+
+    /// 每发现一台打印机弹出一条桌面通知
+    #[arg(long)]
+    pub notify: bool,
+
+    /// 把每台发现的设备和扫描汇总写入本机 syslog / journald
+    #[arg(long)]
+    pub syslog: bool,
+
+    /// 没有发现任何打印机时以退出码 1 结束，方便脚本判断；不指定时没有发现也返回 0
+    #[arg(long)]
+    pub fail_if_none: bool,
+
+    /// 扫描过程中定期把已扫过的主机和结果写入该文件，扫描完成后删除
+    #[arg(long, value_name = "PATH")]
+    pub checkpoint: Option<PathBuf>,
+
+    /// 从 `--checkpoint` 文件恢复中断的扫描，跳过已经扫过的主机
+    #[arg(long, requires = "checkpoint")]
+    pub resume: bool,
+}
+
+/// 发现新打印机时回调 Webhook，`scan` 和 `monitor` 共用
+#[derive(Args, Debug, Clone)]
+pub struct WebhookArgs {
+    /// 发现上一轮没有的打印机时，向该地址 POST 一条 JSON
+    #[arg(long)]
+    pub webhook_url: Option<String>,
+
+    /// 保存上一轮设备列表的文件。`scan` 需要它才能跨次运行比较；
+    /// 首次运行 (文件不存在) 只记录基线，不发通知
+    #[arg(long)]
+    pub webhook_state: Option<PathBuf>,
+}
+
+/// 把结果以保留消息发布到 MQTT，`scan` 和 `monitor` 共用
+#[derive(Args, Debug, Clone)]
+pub struct MqttArgs {
+    /// MQTT broker 地址，格式 host[:port]，默认端口 1883
+    #[arg(long)]
+    pub mqtt_broker: Option<String>,
+
+    /// 设备 topic 前缀，每台打印机发布到 `<prefix>/<ip>`
+    #[arg(long, default_value = "printers")]
+    pub mqtt_topic_prefix: String,
+
+    #[arg(long)]
+    pub mqtt_client_id: Option<String>,
+
+    #[arg(long)]
+    pub mqtt_username: Option<String>,
+
+    #[arg(long)]
+    pub mqtt_password: Option<String>,
+}
+
+/// SNMP 版本和 v3 凭据，`scan`、`monitor` 和 `probe` 共用
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct SnmpArgs {
+    /// SNMP 版本。关闭了 v2c / public 的网络用 v3，并提供 `--snmp-user`
+    #[arg(long = "snmp", value_enum, default_value_t = SnmpVersion::V2c, value_name = "VERSION")]
+    pub version: SnmpVersion,
+
+    /// v1 / v2c 依次尝试的 community，逗号分隔
+    #[arg(long, value_delimiter = ',', default_value = "public")]
+    pub community: Vec<String>,
+
+    /// 从文件读取候选 community，每行一个，排在 `--community` 之后；支持空行和 `#` 开头的注释
+    #[arg(long, value_name = "PATH")]
+    pub community_file: Option<PathBuf>,
+
+    /// SNMP 的 UDP 端口，部分打印服务器把 SNMP 代理在其他端口上
+    #[arg(long, default_value_t = SNMP_PORT)]
+    pub snmp_port: u16,
+
+    /// 每个 SNMP 请求等待响应的时间，与打印端口的 `--timeout-ms` 分开设置
+    #[arg(long, default_value_t = SNMP_TIMEOUT_MS)]
+    pub snmp_timeout_ms: u64,
+
+    /// 额外读取的 OID，写成 `名称=OID`，可重复；结果按名称写入 `oids` 字段
+    #[arg(long, value_name = "NAME=OID")]
+    pub oid: Vec<String>,
+
+    /// 从文件读取额外的 OID，每行一个 `名称=OID`；支持空行和 `#` 注释
+    #[arg(long, value_name = "PATH")]
+    pub oid_file: Option<PathBuf>,
+
+    /// SNMPv3 用户名
+    #[arg(long)]
+    pub snmp_user: Option<String>,
+
+    /// SNMPv3 认证密码，不指定时为 noAuthNoPriv
+    #[arg(long, value_name = "PASSWORD")]
+    pub snmp_auth: Option<String>,
+
+    #[arg(long, value_enum, default_value_t = AuthProtocol::Sha1)]
+    pub snmp_auth_protocol: AuthProtocol,
+
+    /// SNMPv3 加密密码，需要同时指定 `--snmp-auth` (authPriv)
+    #[arg(long, value_name = "PASSWORD", requires = "snmp_auth")]
+    pub snmp_priv: Option<String>,
+
+    #[arg(long, value_enum, default_value_t = PrivProtocol::Aes128)]
+    pub snmp_priv_protocol: PrivProtocol,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ProbeArgs {
+    /// 目标主机 IP
+    pub ip: IpAddr,
+
+    #[arg(short, long, default_value_t = 2000)]
+    pub timeout_ms: u64,
+
+    /// 额外发送 PJL INFO CONFIG / STATUS / PAGECOUNT / MEMORY，列出各自的响应
+    #[arg(long)]
+    pub full: bool,
+
+    #[command(flatten)]
+    pub snmp: SnmpArgs,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct MonitorArgs {
+    #[command(flatten)]
+    pub target: TargetArgs,
+
+    /// 两轮扫描之间的间隔，例如 30s、5m、1h
+    #[arg(long, default_value = "5m")]
+    pub every: humantime::Duration,
+
+    /// 以 NDJSON 输出变化事件，每个事件一行
+    #[arg(long)]
+    pub json: bool,
+
+    /// 把事件写入文件而不是 stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub mqtt: MqttArgs,
+
+    #[command(flatten)]
+    pub webhook: WebhookArgs,
+
+    /// 有打印机上线时弹出桌面通知
+    #[arg(long)]
+    pub notify: bool,
+
+    /// 把设备变化和每轮扫描汇总写入本机 syslog / journald
+    #[arg(long)]
+    pub syslog: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DiffArgs {
+    /// 旧的扫描结果文件
+    pub old: PathBuf,
+
+    /// 新的扫描结果文件
+    pub new: PathBuf,
+
+    /// 以 JSON 数组输出变化列表
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ServeArgs {
+    #[command(flatten)]
+    pub target: TargetArgs,
+
+    /// HTTP 服务监听地址
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    pub listen: SocketAddr,
+
+    /// 按固定间隔自动重新扫描，例如 5m；不指定时只在启动和 POST /scan 时扫描
+    #[arg(long)]
+    pub every: Option<humantime::Duration>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CheckArgs {
+    #[command(flatten)]
+    pub target: TargetArgs,
+
+    /// 期望在线的打印机 IP，可重复或用逗号分隔
+    #[arg(short, long, value_delimiter = ',')]
+    pub expect: Vec<IpAddr>,
+
+    /// 期望清单文件，每行一个 IP，支持 `#` 注释
+    #[arg(long)]
+    pub expect_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct PrintArgs {
+    /// 目标打印机 IP
+    pub ip: IpAddr,
+
+    /// 要发送的文件 (ZPL、PCL、PostScript、纯文本等打印机能直接识别的数据)
+    pub file: PathBuf,
+
+    #[arg(short, long, default_value_t = PRINTER_PORT)]
+    pub port: u16,
+
+    #[arg(short, long, default_value_t = 2000)]
+    pub timeout_ms: u64,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct PjlMonitorArgs {
+    /// 目标打印机 IP
+    pub ip: IpAddr,
+
+    #[arg(short, long, default_value_t = PRINTER_PORT)]
+    pub port: u16,
+
+    #[arg(short, long, default_value_t = 2000)]
+    pub timeout_ms: u64,
+
+    /// 以 NDJSON 输出状态事件，每个事件一行
+    #[arg(long)]
+    pub json: bool,
+
+    /// 把事件写入文件而不是 stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SnmpWalkArgs {
+    /// 目标打印机 IP
+    pub ip: IpAddr,
+
+    /// 遍历的起点：名称 (如 prtMarkerSuppliesTable、hrPrinterStatus) 或点分 OID；
+    /// 不指定时遍历整个 Printer MIB 和 Host Resources MIB
+    #[arg(value_name = "BASE_OID")]
+    pub base: Option<String>,
+
+    #[command(flatten)]
+    pub snmp: SnmpArgs,
+
+    /// 以 JSON 数组输出，每项包含 oid、name、value
+    #[arg(long)]
+    pub json: bool,
+
+    /// 把结果写入文件而不是 stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct TrapArgs {
+    /// 监听地址；162 端口通常需要 root 权限
+    #[arg(short, long, default_value = "0.0.0.0:162")]
+    pub listen: SocketAddr,
+
+    /// 之前保存的扫描结果 (JSON / YAML)，只接收其中打印机发来的 trap；不指定时接收所有来源
+    #[arg(long, value_name = "FILE")]
+    pub scan: Option<PathBuf>,
+
+    /// 把收到的 trap 原样转发到这些地址 (如网管平台的 trap 接收端)，可重复或用逗号分隔
+    #[arg(long, value_delimiter = ',', value_name = "ADDR")]
+    pub forward: Vec<SocketAddr>,
+
+    /// 把 trap 写入本机 syslog / journald
+    #[arg(long)]
+    pub syslog: bool,
+
+    /// 以 NDJSON 输出 trap，每个一行
+    #[arg(long)]
+    pub json: bool,
+
+    /// 把 trap 写入文件而不是 stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// 以错误退出：参数、网络或文件错误，与 clap 解析失败时的退出码一致
+const EXIT_ERROR: u8 = 2;
+/// 没有发现任何打印机，只在指定了 `--fail-if-none` 时使用，不指定时返回 0
+const EXIT_NONE_FOUND: u8 = 1;
+
+pub fn exit_error() -> ExitCode {
+    ExitCode::from(EXIT_ERROR)
+}
+
+async fn run_scan(args: ScanArgs) -> ExitCode {
+    let target = &args.target;
+    let context = match Context::from_args(target) {
+        Ok(c) => c,
+        Err(e) => { eprintln!("{}", e); return exit_error(); }
+    };
+    let targets = match Targets::from_args(target) {
+        Ok(t) => t,
+        Err(e) => { eprintln!("{}", tr!(InvalidNetwork, e)); return exit_error(); }
+    };
+
+    // 结果写入 --output 指定的文件或 stdout，横幅等状态信息一律走 stderr，
+    // 这样重定向 stdout 时拿到的只有结果
+    let mut out = match output::open_output(args.output.output.as_deref()) {
+        Ok(out) => out,
+        Err(e) => { eprintln!("{}", tr!(CreateOutputFailed, e)); return exit_error(); }
+    };
+    let format = args.output.format;
+
+    status!("{}", tr!(Scanning, "🚀".green(), targets.label));
+
+    let timestamp = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    let started = Instant::now();
+
+    let mut syslog = if args.syslog { syslog_sink::SyslogSink::connect() } else { None };
+    let mut notifications = Vec::new();
+    let mut write_failed = false;
+    let checkpoint = match &args.checkpoint {
+        Some(path) => match checkpoint::Checkpoint::open(path, &targets.label, args.resume) {
+            Ok(cp) => Some(cp),
+            Err(e) => { eprintln!("{}", e); return exit_error(); }
+        },
+        None => None,
+    };
+    let (results, summary) = scan_network(&targets, target, &context, true, checkpoint, |printer| {
+        if args.notify {
+            notifications.push(notify::printer_found(printer));
+        }
+        if let Some(sink) = syslog.as_mut() {
+            sink.discovered(printer);
+        }
+        // NDJSON 模式下发现一台就立刻输出一行，不等整个网段扫完
+        if format == OutputFormat::Ndjson
+            && let Err(e) = output::write_ndjson_line(&mut out, printer)
+        {
+            eprintln!("{}", tr!(WriteFailed, e));
+            write_failed = true;
+        }
+    })
+    .await;
+    futures::future::join_all(notifications).await;
+
+    let report = ScanReport {
+        timestamp,
+        network: targets.label.clone(),
+        timeout_ms: target.timeout_ms,
+        concurrency: target.concurrency,
+        duration_ms: started.elapsed().as_millis() as u64,
+        printers: results,
+        summary,
+    };
+    if let Err(e) = output::write_report(&mut out, format, &report).and_then(|_| out.flush()) {
+        eprintln!("{}", tr!(WriteFailed, e));
+        write_failed = true;
+    }
+    if let Some(sink) = syslog.as_mut() {
+        sink.summary(&report.network, report.printers.len(), report.duration_ms);
+    }
+
+    if let Some(path) = &args.report {
+        match report::write_html_report(path, &report) {
+            Ok(()) => status!("{}", tr!(HtmlReportWritten, "📄".green(), path.display())),
+            Err(e) => {
+                eprintln!("{}", tr!(HtmlReportFailed, e));
+                write_failed = true;
+            }
+        }
+    }
+
+    if let Some(mut sink) = mqtt::MqttSink::connect(&args.mqtt) {
+        sink.sync(&report.printers).await;
+        sink.close().await;
+    }
+
+    if let Some(mut hook) = webhook::Webhook::new(&args.webhook) {
+        if args.webhook.webhook_state.is_none() {
+            status!("{}", tr!(WebhookNeedsState));
+        } else {
+            hook.notify_new(&report.printers).await;
+        }
+    }
+
+    if write_failed {
+        exit_error()
+    } else if args.fail_if_none && report.printers.is_empty() {
+        ExitCode::from(EXIT_NONE_FOUND)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// 对单台主机执行全部探测，输出每个协议的原始响应和解析结果，
+/// 用于排查型号识别错误
+async fn run_probe(args: ProbeArgs) -> ExitCode {
+    let mut out = match output::open_output(args.output.output.as_deref()) {
+        Ok(out) => out,
+        Err(e) => { eprintln!("{}", tr!(CreateOutputFailed, e)); return exit_error(); }
+    };
+    let context = match snmp::Client::from_args(&args.snmp) {
+        Ok(snmp) => Context { snmp, ..Default::default() },
+        Err(e) => { eprintln!("{}", e); return exit_error(); }
+    };
+
+    status!("{}", tr!(Probing, "🔍".green(), args.ip));
+
+    let timestamp = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    let started = Instant::now();
+    let port_open = is_port_open(context.source, args.ip, PRINTER_PORT, args.timeout_ms).await;
+    let response_ms = port_open.then(|| started.elapsed().as_millis() as u64);
+
+    // 9100 不通时仍然跑一遍：SNMP 走 UDP，不受影响
+    let addr = SocketAddr::new(args.ip, PRINTER_PORT);
+    let mut probes = run_all_probes(&context, addr, args.timeout_ms).await;
+    if args.full {
+        probes.extend(pjl::probe_outcomes(context.source, addr, args.timeout_ms).await);
+    }
+    if context.snmp.answered(args.ip).is_some()
+        && let Some(values) = snmp::scalars(&context.snmp, args.ip).await
+    {
+        probes.push(printer_mib::status_outcome(&context.snmp, &values));
+        probes.push(printer_mib::alert_outcome(&context.snmp, args.ip).await);
+    }
+    let report = ProbeReport { timestamp, ip: args.ip, port_open, response_ms, probes };
+
+    if let Err(e) = output::write_probe_report(&mut out, args.output.format, &report).and_then(|_| out.flush()) {
+        eprintln!("{}", tr!(WriteFailed, e));
+        return exit_error();
+    }
+    ExitCode::SUCCESS
+}
+
+/// 把文件原样写到打印机的 RAW 端口 (JetDirect / AppSocket)
+async fn run_print(args: PrintArgs) -> ExitCode {
+    let data = match tokio::fs::read(&args.file).await {
+        Ok(d) => d,
+        Err(e) => { eprintln!("{}", tr!(ReadFailed, args.file.display(), e)); return exit_error(); }
+    };
+
+    let addr = SocketAddr::new(args.ip, args.port);
+    let mut stream = match timeout(Duration::from_millis(args.timeout_ms), TcpStream::connect(addr)).await {
+        Ok(Ok(s)) => s,
+        Ok(Err(e)) => { eprintln!("{}", tr!(ConnectFailed, addr, e)); return exit_error(); }
+        Err(_) => { eprintln!("{}", tr!(ConnectTimeout, addr)); return exit_error(); }
+    };
+
+    if let Err(e) = stream.write_all(&data).await.and(stream.shutdown().await) {
+        eprintln!("{}", tr!(SendFailed, e));
+        return exit_error();
+    }
+    status!("{}", tr!(Sent, "🖨️".green(), data.len(), addr));
+    ExitCode::SUCCESS
+}
+
+/// 命令行入口：解析参数并执行子命令，`main.rs` 只是调用它
+pub async fn run() -> ExitCode {
+    let cli = Cli::parse();
+    output::set_quiet(cli.quiet);
+    output::set_color(cli.color);
+    i18n::set_lang(cli.lang.unwrap_or_else(i18n::detect));
+    match cli.command {
+        Some(Command::Scan(args)) => run_scan(args).await,
+        Some(Command::Probe(args)) => run_probe(args).await,
+        Some(Command::Monitor(args)) => monitor::run_monitor(args).await,
+        Some(Command::Diff(args)) => diff::run_diff(args),
+        Some(Command::Serve(args)) => serve::run_serve(args).await,
+        Some(Command::Check(args)) => check::run_check(args).await,
+        Some(Command::Print(args)) => run_print(args).await,
+        Some(Command::MonitorPjl(args)) => pjl_monitor::run_monitor_pjl(args).await,
+        Some(Command::Snmpwalk(args)) => snmp_walk::run_snmpwalk(args).await,
+        Some(Command::Traps(args)) => traps::run_traps(args).await,
+        None => run_scan(cli.scan).await,
+    }
+}
@@ -0,0 +1,150 @@
+use crate::http;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// 各识别来源的基础可信度。SNMP sysDescr、IPP 和 PJL 报的是设备自己声明的完整型号，
+/// 标签机 / 小票机的指令回应次之，网页、证书和欢迎信息只是线索
+const SOURCE_WEIGHTS: &[(&str, f64)] = &[
+    ("SNMP", 0.9),
+    ("IPP", 0.9),
+    ("PJL", 0.8),
+    ("BJNP", 0.8),
+    ("ENPC", 0.8),
+    ("SGD (Zebra)", 0.7),
+    ("ZPL", 0.7),
+    ("Star", 0.7),
+    ("ESC/POS", 0.6),
+    ("Dymo/Seiko", 0.6),
+    ("HTTP", 0.5),
+    ("HTTPS", 0.4),
+    ("FTP", 0.4),
+    ("LPD", 0.2),
+    ("Raw Banner", 0.1),
+];
+const DEFAULT_WEIGHT: f64 = 0.3;
+/// 型号看起来像乱码时 (控制字符多、没有字母) 可信度打的折扣，
+/// Zebra SGD 偶尔回一段二进制，不能让它压过正常的 sysDescr
+const GARBAGE_FACTOR: f64 = 0.2;
+/// 其他来源印证时给候选加的分数比例
+const AGREEMENT_BONUS: f64 = 0.5;
+
+/// `--all-probes` 时每个回应的协议给出的型号
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Evidence {
+    pub source: String,
+    pub model: String,
+    /// 这条证据的可信度：来源的基础可信度，型号像乱码时打折
+    pub weight: f64,
+    /// 是否与采用的型号一致
+    pub agrees: bool,
+}
+
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// 型号是否像样：绝大部分是可打印字符，且去掉厂商名后还剩至少一个带字母的词。
+/// 探测函数会在回应前面加上厂商名 (`Zebra ...`)，只剩 `"?"`、`0` 这类内容的仍算乱码
+fn plausible(model: &str) -> bool {
+    let total = model.chars().count();
+    let printable = model.chars().filter(|c| c.is_alphanumeric() || " -_./()+,:#".contains(*c)).count();
+    let named = model
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| http::vendor_of(word).is_none())
+        .any(|word| word.chars().count() >= 2 && word.chars().any(char::is_alphabetic));
+    named && !model.contains('\u{FFFD}') && printable * 10 >= total * 8
+}
+
+fn weigh(source: &str, model: &str) -> f64 {
+    let base = SOURCE_WEIGHTS.iter().find(|(s, _)| *s == source).map(|(_, w)| *w).unwrap_or(DEFAULT_WEIGHT);
+    if plausible(model) { base } else { base * GARBAGE_FACTOR }
+}
+
+/// 型号中带数字的词 (`m404n`、`gx430t`)，厂商名和系列名相同不算印证
+fn model_numbers(model: &str) -> BTreeSet<String> {
+    model
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|t| t.len() >= 3 && t.chars().any(|c| c.is_ascii_digit()) && t.chars().any(|c| c.is_ascii_alphabetic()))
+        .map(str::to_string)
+        .collect()
+}
+
+fn agree(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b) || !model_numbers(a).is_disjoint(&model_numbers(b))
+}
+
+/// 在各协议给出的 (来源, 型号) 中选出采用的一条：得分为自身可信度加上印证它的其他来源可信度的一半，
+/// 同分时取探测顺序靠前的。置信度按采用的型号及其印证来源合并 (1 - Π(1 - 可信度))。
+/// 返回采用的下标、置信度和证据列表，没有候选时返回 None
+pub fn merge(candidates: &[(&str, &str)]) -> Option<(usize, f64, Vec<Evidence>)> {
+    let weights: Vec<f64> = candidates.iter().map(|(source, model)| weigh(source, model)).collect();
+    let score = |i: usize| {
+        let support: f64 = (0..candidates.len()).filter(|&j| j != i && agree(candidates[i].1, candidates[j].1)).map(|j| weights[j]).sum();
+        weights[i] + support * AGREEMENT_BONUS
+    };
+    let best = (0..candidates.len()).fold(None, |best: Option<(usize, f64)>, i| match best {
+        Some((_, top)) if top >= score(i) => best,
+        _ => Some((i, score(i))),
+    })?;
+    let chosen = candidates[best.0].1;
+    let evidence: Vec<Evidence> = candidates
+        .iter()
+        .zip(&weights)
+        .map(|((source, model), &weight)| Evidence { source: source.to_string(), model: model.to_string(), weight: round(weight), agrees: agree(chosen, model) })
+        .collect();
+    let doubt: f64 = evidence.iter().zip(&weights).filter(|(e, _)| e.agrees).map(|(_, w)| 1.0 - w).product();
+    Some((best.0, round(1.0 - doubt), evidence))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn garbage_loses_to_sys_descr() {
+        let candidates = [("SGD (Zebra)", "Zebra \u{1}\u{2}?"), ("SNMP", "ZTC GX430t-300dpi ZPL"), ("ZPL", "GX430t")];
+        let (chosen, confidence, evidence) = merge(&candidates).unwrap();
+        assert_eq!(chosen, 1);
+        assert_eq!(evidence[0].weight, 0.14);
+        assert_eq!(evidence.iter().map(|e| e.agrees).collect::<Vec<_>>(), [false, true, true]);
+        // SNMP 和 ZPL 互相印证：1 - 0.1 × 0.3
+        assert_eq!(confidence, 0.97);
+    }
+
+    #[test]
+    fn agreeing_weak_sources_outvote_one_strong_source() {
+        let candidates =
+            [("PJL", "Brother HL-L2350DW series"), ("HTTP", "HP LaserJet M404n"), ("HTTPS", "HP LaserJet Pro M404n"), ("FTP", "M404n")];
+        let (chosen, confidence, evidence) = merge(&candidates).unwrap();
+        assert_eq!(chosen, 1);
+        assert!(!evidence[0].agrees);
+        assert_eq!(confidence, 0.82);
+    }
+
+    #[test]
+    fn ties_keep_probe_order() {
+        assert_eq!(merge(&[("SNMP", "Lexmark MX622ade"), ("IPP", "Xerox C235")]).unwrap().0, 0);
+        assert_eq!(merge(&[("IPP", "Xerox C235"), ("SNMP", "Lexmark MX622ade")]).unwrap().0, 0);
+        assert!(merge(&[]).is_none());
+    }
+
+    #[test]
+    fn vendor_names_alone_do_not_agree() {
+        assert!(agree("HP LaserJet 400", "hp laserjet 400"));
+        assert!(!agree("HP LaserJet", "HP OfficeJet"));
+        // 300dpi 这样的数字词也算型号的一部分
+        assert!(agree("ZTC ZD420-300dpi", "Zebra GX430t 300dpi"));
+        assert!(!agree("TM-T88V", "T88"));
+    }
+
+    #[test]
+    fn plausible_models_need_a_word_beyond_the_vendor() {
+        assert!(plausible("EPSON TM-T88V"));
+        assert!(!plausible("Zebra ?"));
+        assert!(!plausible("0"));
+        assert!(!plausible("Brother \u{FFFD}\u{FFFD}L2350"));
+        assert!(!plausible("A4\u{7}\u{7}\u{7}\u{7}"));
+        assert_eq!(weigh("Unknown", "Canon MF743C"), DEFAULT_WEIGHT);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::PrinterInfo;
+use crate::cli::{DiffArgs, exit_error};
+use crate::output;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// 两次扫描之间单台设备的变化
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Change {
+    Added { printer: Box<PrinterInfo> },
+    Removed { printer: Box<PrinterInfo> },
+    /// 同一 IP 上报的型号发生了变化 (换机、固件升级或识别来源不同)
+    Changed { before: Box<PrinterInfo>, after: Box<PrinterInfo> },
+    /// 型号不变，Printer MIB 的活动告警有新增或解除，用作设备健康检查
+    Alerts { printer: Box<PrinterInfo>, raised: Vec<String>, cleared: Vec<String> },
+}
+
+impl Change {
+    pub fn ip(&self) -> IpAddr {
+        match self {
+            Change::Added { printer } | Change::Removed { printer } | Change::Alerts { printer, .. } => printer.ip,
+            Change::Changed { after, .. } => after.ip,
+        }
+    }
+}
+
+/// 以 IP 和端口为键比较两次扫描结果 (多口打印服务器的每个端口是一台设备)，按 IP 顺序返回变化列表
+pub fn diff(old: &[PrinterInfo], new: &[PrinterInfo]) -> Vec<Change> {
+    let old: BTreeMap<(IpAddr, u16), &PrinterInfo> = old.iter().map(|p| ((p.ip, p.port), p)).collect();
+    let new: BTreeMap<(IpAddr, u16), &PrinterInfo> = new.iter().map(|p| ((p.ip, p.port), p)).collect();
+
+    let mut changes = Vec::new();
+    for (key, before) in &old {
+        match new.get(key) {
+            None => changes.push(Change::Removed { printer: Box::new((*before).clone()) }),
+            Some(after) if after.model != before.model => changes.push(Change::Changed {
+                before: Box::new((*before).clone()),
+                after: Box::new((*after).clone()),
+            }),
+            Some(after) => {
+                let descriptions = |p: &PrinterInfo| p.alerts.iter().map(|a| a.description.clone()).collect::<BTreeSet<_>>();
+                let (old_alerts, new_alerts) = (descriptions(before), descriptions(after));
+                if old_alerts != new_alerts {
+                    changes.push(Change::Alerts {
+                        printer: Box::new((*after).clone()),
+                        raised: new_alerts.difference(&old_alerts).cloned().collect(),
+                        cleared: old_alerts.difference(&new_alerts).cloned().collect(),
+                    });
+                }
+            }
+        }
+    }
+    for (key, printer) in &new {
+        if !old.contains_key(key) {
+            changes.push(Change::Added { printer: Box::new((*printer).clone()) });
+        }
+    }
+
+    changes.sort_by_key(|c| c.ip());
+    changes
+}
+
+/// 读取 `scan --format json|yaml` 保存的结果，只关心其中的设备列表
+#[derive(Deserialize)]
+struct SavedScan {
+    printers: Vec<PrinterInfo>,
+}
+
+pub fn load_scan(path: &Path) -> Result<Vec<PrinterInfo>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| tr!(ReadFailed, path.display(), e))?;
+    let is_yaml = matches!(path.extension().and_then(|e| e.to_str()), Some("yaml" | "yml"));
+    let saved: SavedScan = if is_yaml {
+        serde_yaml::from_str(&text).map_err(|e| tr!(InvalidScanFile, path.display(), e))?
+    } else {
+        serde_json::from_str(&text).map_err(|e| tr!(InvalidScanFile, path.display(), e))?
+    };
+    Ok(saved.printers)
+}
+
+pub fn run_diff(args: DiffArgs) -> ExitCode {
+    let (old, new) = match (load_scan(&args.old), load_scan(&args.new)) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(e), _) | (_, Err(e)) => { eprintln!("{}", e); return exit_error(); }
+    };
+
+    let changes = diff(&old, &new);
+    let mut out = io::stdout();
+    let written = if args.json {
+        serde_json::to_writer_pretty(&mut out, &changes).map_err(io::Error::from).and_then(|_| writeln!(out))
+    } else {
+        changes.iter().try_for_each(|c| writeln!(out, "{}", output::format_change(c)))
+    };
+    if let Err(e) = written {
+        eprintln!("{}", tr!(WriteFailed, e));
+        return exit_error();
+    }
+
+    let count = |f: fn(&Change) -> bool| changes.iter().filter(|c| f(c)).count();
+    status!(
+        "{}",
+        tr!(
+            DiffSummary,
+            count(|c| matches!(c, Change::Added { .. })),
+            count(|c| matches!(c, Change::Removed { .. })),
+            count(|c| matches!(c, Change::Changed { .. } | Change::Alerts { .. })),
+        )
+    );
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `scan --format json` 保存的两次结果：.20 换了机器，.31 下线，.7 新上线，.5 没有变化
+    const BEFORE: &str = r#"{
+        "timestamp": "2024-05-06T09:00:00Z", "network": "10.0.0.0/24", "timeout_ms": 2000, "concurrency": 100, "duration_ms": 5123,
+        "printers": [
+            {"ip": "10.0.0.31", "model": "Brother HL-L2350DW series", "source": "PJL", "response_ms": 3},
+            {"ip": "10.0.0.5", "model": "HP LaserJet Pro M404n", "source": "PJL", "response_ms": 2},
+            {"ip": "10.0.0.20", "model": "ZTC GX430t-300dpi ZPL", "source": "SGD (Zebra)", "response_ms": 4}
+        ],
+        "summary": {"hosts_scanned": 254, "ports_open": 3, "identified": 3, "by_source": {"PJL": 2, "SGD (Zebra)": 1}}
+    }"#;
+    const AFTER: &str = r#"{
+        "printers": [
+            {"ip": "10.0.0.5", "model": "HP LaserJet Pro M404n", "source": "SNMP", "response_ms": 9},
+            {"ip": "10.0.0.7", "model": "EPSON TM-T88V", "source": "ESC/POS", "response_ms": 1},
+            {"ip": "10.0.0.20", "model": "ZTC ZD420-203dpi ZPL", "source": "SGD (Zebra)", "response_ms": 4}
+        ]
+    }"#;
+
+    fn printers(json: &str) -> Vec<PrinterInfo> {
+        serde_json::from_str::<SavedScan>(json).unwrap().printers
+    }
+
+    #[test]
+    fn changes_sorted_by_ip() {
+        let changes = diff(&printers(BEFORE), &printers(AFTER));
+        let kinds: Vec<(String, &str)> = changes
+            .iter()
+            .map(|c| {
+                let kind = match c {
+                    Change::Added { .. } => "added",
+                    Change::Removed { .. } => "removed",
+                    Change::Changed { .. } => "changed",
+                    Change::Alerts { .. } => "alerts",
+                };
+                (c.ip().to_string(), kind)
+            })
+            .collect();
+        // 按地址而不是字符串排序：.7 在 .20 之前
+        assert_eq!(kinds, [("10.0.0.7".into(), "added"), ("10.0.0.20".into(), "changed"), ("10.0.0.31".into(), "removed")]);
+    }
+
+    #[test]
+    fn changed_keeps_both_sides() {
+        let changes = diff(&printers(BEFORE), &printers(AFTER));
+        let Some(Change::Changed { before, after }) = changes.iter().find(|c| matches!(c, Change::Changed { .. })) else { panic!() };
+        assert_eq!(before.model, "ZTC GX430t-300dpi ZPL");
+        assert_eq!(after.model, "ZTC ZD420-203dpi ZPL");
+    }
+
+    #[test]
+    fn source_or_timing_alone_is_not_a_change() {
+        // .5 的识别来源和响应时间变了，型号没变
+        let changes = diff(&printers(BEFORE), &printers(AFTER));
+        assert!(changes.iter().all(|c| c.ip().to_string() != "10.0.0.5"));
+        assert!(diff(&printers(BEFORE), &printers(BEFORE)).is_empty());
+    }
+
+    #[test]
+    fn alert_changes_on_same_model() {
+        let scan = |alerts: &str| {
+            format!(
+                r#"{{"timestamp": "2024-05-06T09:00:00Z", "network": "10.0.0.5", "timeout_ms": 2000, "concurrency": 1, "duration_ms": 10,
+                "printers": [{{"ip": "10.0.0.5", "model": "HP LaserJet Pro M404n", "source": "SNMP", "response_ms": 2, "alerts": [{}]}}]}}"#,
+                alerts
+            )
+        };
+        let toner = r#"{"severity": "warning", "code": 1104, "description": "toner low"}"#;
+        let tray = r#"{"severity": "critical", "code": 13, "description": "tray 2 empty"}"#;
+        let changes = diff(&printers(&scan(toner)), &printers(&scan(&format!("{},{}", tray, toner))));
+        let [Change::Alerts { raised, cleared, .. }] = changes.as_slice() else { panic!("{}", changes.len()) };
+        assert_eq!((raised.as_slice(), cleared.as_slice()), (&["tray 2 empty".to_string()][..], &[][..]));
+        // 告警顺序不同不算变化
+        assert!(diff(&printers(&scan(&format!("{},{}", tray, toner))), &printers(&scan(&format!("{},{}", toner, tray)))).is_empty());
+    }
+}
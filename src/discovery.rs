@@ -0,0 +1,159 @@
+use crate::cli::TargetArgs;
+use crate::{PrinterInfo, bjnp, mdns, ssdp, wsd};
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+
+/// 组播 / 广播发现协议 (mDNS、WSD、SSDP、BJNP) 找到的设备
+#[derive(Debug, Clone)]
+pub struct Discovered {
+    pub ip: IpAddr,
+    pub port: u16,
+    pub source: &'static str,
+    /// 设备通告的名称，例如 mDNS 实例名
+    pub name: Option<String>,
+    pub model: Option<String>,
+    /// 通告中附带的键值对，例如 mDNS TXT 记录
+    pub txt: BTreeMap<String, String>,
+}
+
+/// 把发现结果并入主动扫描结果：已扫到的设备补充名称和 TXT，
+/// 返回只被组播发现找到的设备 (如关闭了 9100 的 AirPrint 打印机)，由调用方加入结果
+pub fn merge(printers: &mut [PrinterInfo], found: Vec<Discovered>) -> Vec<PrinterInfo> {
+    let mut added: BTreeMap<IpAddr, PrinterInfo> = BTreeMap::new();
+    for d in found {
+        if let Some(printer) = printers.iter_mut().find(|p| p.ip == d.ip) {
+            enrich(printer, d);
+            continue;
+        }
+        match added.get_mut(&d.ip) {
+            Some(printer) => enrich(printer, d),
+            None => {
+                let mut printer = PrinterInfo {
+                    ip: d.ip,
+                    model: d.model.clone().or_else(|| d.name.clone()).unwrap_or_else(|| "Unknown".to_string()),
+                    source: d.source.to_string(),
+                    port: d.port,
+                    ..Default::default()
+                };
+                enrich(&mut printer, d);
+                added.insert(printer.ip, printer);
+            }
+        }
+    }
+    added.into_values().collect()
+}
+
+fn enrich(printer: &mut PrinterInfo, d: Discovered) {
+    if printer.advertised_name.is_none() {
+        printer.advertised_name = d.name;
+    }
+    for (key, value) in d.txt {
+        printer.txt.entry(key).or_insert(value);
+    }
+    printer.driverless |= crate::ipp::txt_driverless(&printer.txt);
+    printer.color |= crate::ipp::txt_flag(&printer.txt, "Color");
+    printer.duplex |= crate::ipp::txt_flag(&printer.txt, "Duplex");
+}
+
+/// 按参数启动各组播发现协议，与主动扫描并行进行
+pub fn start(args: &TargetArgs) -> Option<JoinHandle<Vec<Discovered>>> {
+    let wait: Duration = args.discover_wait.into();
+    let mut tasks: Vec<BoxFuture<'static, Vec<Discovered>>> = Vec::new();
+    if args.mdns {
+        tasks.push(mdns::browse(wait).boxed());
+    }
+    if args.wsd {
+        tasks.push(wsd::probe(wait).boxed());
+    }
+    if args.ssdp {
+        tasks.push(ssdp::search(wait).boxed());
+    }
+    if args.bjnp {
+        tasks.push(bjnp::discover(wait).boxed());
+    }
+    if tasks.is_empty() {
+        return None;
+    }
+    Some(tokio::spawn(async move { futures::future::join_all(tasks).await.into_iter().flatten().collect() }))
+}
+
+/// 取第一个本地名为 `name` 的元素的文本，忽略命名空间前缀。
+/// 只用于解析打印机返回的简单 XML (WSD 元数据、UPnP 设备描述、eSCL 能力)
+pub fn xml_text(doc: &str, name: &str) -> Option<String> {
+    xml_texts(doc, name).into_iter().next()
+}
+
+/// 所有本地名为 `name` 的元素的文本
+pub fn xml_texts(doc: &str, name: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut rest = doc;
+    while let Some(i) = rest.find('<') {
+        rest = &rest[i + 1..];
+        let Some(tag_end) = rest.find(['>', ' ', '\t', '\r', '\n', '/']) else { break };
+        let tag = &rest[..tag_end];
+        if tag.rsplit(':').next() != Some(name) {
+            continue;
+        }
+        let Some(open_end) = rest.find('>') else { break };
+        if rest[..open_end].ends_with('/') {
+            continue;
+        }
+        let body = &rest[open_end + 1..];
+        let Some(close) = body.find("</") else { break };
+        found.push(
+            body[..close]
+                .trim()
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&"),
+        );
+    }
+    found
+}
+
+/// 组播报文里的 MessageID 只需要不重复，不必是严格的随机 UUID
+pub fn message_uuid() -> String {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+    let n = nanos ^ ((std::process::id() as u128) << 64);
+    let hex = format!("{:032x}", n);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn namespace_prefixes_are_ignored() {
+        let doc = r#"<wsdp:ThisModel><wsdp:Manufacturer xml:lang="en">Brother</wsdp:Manufacturer><ModelName>HL-L2350DW</ModelName></wsdp:ThisModel>"#;
+        assert_eq!(xml_text(doc, "Manufacturer").as_deref(), Some("Brother"));
+        assert_eq!(xml_text(doc, "ModelName").as_deref(), Some("HL-L2350DW"));
+        // 只比较本地名，不做前缀匹配
+        assert_eq!(xml_text(doc, "Model"), None);
+    }
+
+    #[test]
+    fn self_closing_elements_are_skipped() {
+        let doc = "<a:ColorMode/>\n<a:ColorMode>RGB24</a:ColorMode><a:ColorMode />\n<a:ColorMode>\n  Grayscale8\n</a:ColorMode>";
+        assert_eq!(xml_texts(doc, "ColorMode"), ["RGB24", "Grayscale8"]);
+    }
+
+    #[test]
+    fn entities_are_decoded_once() {
+        let doc = "<friendlyName>Sales &amp; Marketing &lt;2F&gt; &quot;A&quot; &apos;B&apos; &amp;lt;</friendlyName>";
+        assert_eq!(xml_text(doc, "friendlyName").as_deref(), Some(r#"Sales & Marketing <2F> "A" 'B' &lt;"#));
+    }
+
+    #[test]
+    fn unterminated_documents_stop_quietly() {
+        assert_eq!(xml_texts("<a>1</a><a>2", "a"), ["1"]);
+        assert_eq!(xml_texts("<a>1</a><a", "a"), ["1"]);
+        assert!(xml_texts("", "a").is_empty());
+    }
+}
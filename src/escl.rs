@@ -0,0 +1,91 @@
+use crate::discovery::{xml_text, xml_texts};
+use crate::http::{self, HTTP_PORT, HTTPS_PORT};
+use crate::probes::Source;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::net::{IpAddr, SocketAddr};
+
+/// eSCL (AirScan) 扫描能力
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Scanner {
+    /// 支持的输入来源：`Platen` (平板)、`Adf` (自动进纸器)
+    pub sources: Vec<String>,
+    /// 支持的分辨率 (dpi)，从小到大
+    pub resolutions: Vec<u32>,
+    /// 支持的颜色模式，如 `RGB24`、`Grayscale8`、`BlackAndWhite1`
+    pub color_modes: Vec<String>,
+}
+
+fn parse(doc: &str) -> Option<Scanner> {
+    if !doc.contains("ScannerCapabilities") {
+        return None;
+    }
+    let sources = ["Platen", "Adf"].into_iter().filter(|s| xml_text(doc, s).is_some()).map(str::to_string).collect();
+    let resolutions: BTreeSet<u32> = xml_texts(doc, "XResolution").iter().filter_map(|r| r.parse().ok()).collect();
+    let color_modes: BTreeSet<String> = xml_texts(doc, "ColorMode").into_iter().filter(|m| !m.is_empty()).collect();
+    Some(Scanner {
+        sources,
+        resolutions: resolutions.into_iter().collect(),
+        color_modes: color_modes.into_iter().collect(),
+    })
+}
+
+/// 查询 `/eSCL/ScannerCapabilities`，先试 HTTP 再试 HTTPS，只问 `open` 中开放的端口。
+/// 不支持网络扫描的设备 (包括单功能打印机) 返回 None
+pub async fn capabilities(source: Source, ip: IpAddr, open: &[u16], timeout_ms: u64) -> Option<Scanner> {
+    let schemes: Vec<(&str, u16)> = [("http", HTTP_PORT), ("https", HTTPS_PORT)].into_iter().filter(|(_, port)| open.contains(port)).collect();
+    if schemes.is_empty() {
+        return None;
+    }
+    let client = http::client(source, ip, timeout_ms).tls_danger_accept_invalid_certs(true).build().ok()?;
+    for (scheme, port) in schemes {
+        let url = format!("{}://{}/eSCL/ScannerCapabilities", scheme, SocketAddr::new(ip, port));
+        let Ok(resp) = client.get(url).send().await else { continue };
+        if !resp.status().is_success() {
+            continue;
+        }
+        if let Some(scanner) = resp.text().await.ok().as_deref().and_then(parse) {
+            return Some(scanner);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAPS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<scan:ScannerCapabilities xmlns:pwg="http://www.pwg.org/schemas/2010/12/sm" xmlns:scan="http://schemas.hp.com/imaging/escl/2011/05/03">
+<pwg:Version>2.63</pwg:Version>
+<scan:Platen><scan:PlatenInputCaps>
+<scan:SettingProfiles><scan:SettingProfile>
+<scan:ColorModes><scan:ColorMode>BlackAndWhite1</scan:ColorMode><scan:ColorMode>Grayscale8</scan:ColorMode><scan:ColorMode>RGB24</scan:ColorMode></scan:ColorModes>
+<scan:SupportedResolutions><scan:DiscreteResolutions>
+<scan:DiscreteResolution><scan:XResolution>300</scan:XResolution><scan:YResolution>300</scan:YResolution></scan:DiscreteResolution>
+<scan:DiscreteResolution><scan:XResolution>75</scan:XResolution><scan:YResolution>75</scan:YResolution></scan:DiscreteResolution>
+<scan:DiscreteResolution><scan:XResolution>1200</scan:XResolution><scan:YResolution>1200</scan:YResolution></scan:DiscreteResolution>
+</scan:DiscreteResolutions></scan:SupportedResolutions>
+</scan:SettingProfile></scan:SettingProfiles>
+</scan:PlatenInputCaps></scan:Platen>
+<scan:Adf><scan:AdfSimplexInputCaps><scan:SettingProfiles><scan:SettingProfile>
+<scan:ColorModes><scan:ColorMode>RGB24</scan:ColorMode></scan:ColorModes>
+<scan:SupportedResolutions><scan:DiscreteResolutions><scan:DiscreteResolution><scan:XResolution>300</scan:XResolution></scan:DiscreteResolution></scan:DiscreteResolutions></scan:SupportedResolutions>
+</scan:SettingProfile></scan:SettingProfiles></scan:AdfSimplexInputCaps></scan:Adf>
+</scan:ScannerCapabilities>"#;
+
+    #[test]
+    fn merges_platen_and_adf_profiles() {
+        let scanner = parse(CAPS).unwrap();
+        assert_eq!(scanner.sources, ["Platen", "Adf"]);
+        assert_eq!(scanner.resolutions, [75, 300, 1200]);
+        assert_eq!(scanner.color_modes, ["BlackAndWhite1", "Grayscale8", "RGB24"]);
+    }
+
+    #[test]
+    fn other_documents_are_not_scanners() {
+        assert!(parse("<html><body>404 Not Found</body></html>").is_none());
+        let scanner = parse("<scan:ScannerCapabilities></scan:ScannerCapabilities>").unwrap();
+        assert!(scanner.sources.is_empty() && scanner.resolutions.is_empty());
+    }
+}
@@ -0,0 +1,238 @@
+use crate::discovery::xml_text;
+use crate::probes::Source;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+pub const HTTP_PORT: u16 = 80;
+pub const HTTPS_PORT: u16 = 443;
+
+/// 标题或 Server 头中出现这些词 (按单词比较，不区分大小写) 即认为是对应厂商
+const VENDOR_WORDS: &[(&str, &str)] = &[
+    ("hp", "HP"),
+    ("laserjet", "HP"),
+    ("officejet", "HP"),
+    ("deskjet", "HP"),
+    ("jetdirect", "HP"),
+    ("brother", "Brother"),
+    ("epson", "Epson"),
+    ("canon", "Canon"),
+    ("imagerunner", "Canon"),
+    ("lexmark", "Lexmark"),
+    ("xerox", "Xerox"),
+    ("ricoh", "Ricoh"),
+    ("kyocera", "Kyocera"),
+    ("ecosys", "Kyocera"),
+    ("taskalfa", "Kyocera"),
+    ("zebra", "Zebra"),
+    ("dymo", "Dymo"),
+    ("labelwriter", "Dymo"),
+    ("seiko", "Seiko"),
+    ("konica", "Konica Minolta"),
+    ("bizhub", "Konica Minolta"),
+    ("sharp", "Sharp"),
+    ("oki", "Oki"),
+    ("samsung", "Samsung"),
+    ("toshiba", "Toshiba"),
+    ("dell", "Dell"),
+    ("pantum", "Pantum"),
+];
+
+/// 打印机内嵌网页的固定路径：首页认不出厂商时逐个尝试，能打开即说明是该厂商的设备
+const VENDOR_PATHS: &[(&str, &str)] = &[
+    ("/DevMgmt/ProductConfigDyn.xml", "HP"),
+    ("/hp/device/DeviceInformation/View", "HP"),
+    ("/general/information.html", "Brother"),
+    ("/PRESENTATION/HTML/TOP/PRTINFO.HTML", "Epson"),
+    ("/cgi-bin/dynamic/printer/config/reports/deviceinfo.html", "Lexmark"),
+    ("/web/guest/en/websys/webArch/mainFrame.cgi", "Ricoh"),
+];
+
+/// 打印机出厂时的默认主机名前缀，常被直接用作自签名证书的 CN (后面跟 MAC 或序列号)
+const HOSTNAME_PREFIXES: &[(&str, &str)] = &[
+    ("NPI", "HP"),
+    ("BRN", "Brother"),
+    ("BRW", "Brother"),
+    ("RNP", "Ricoh"),
+    ("KMBT", "Konica Minolta"),
+    ("KM", "Kyocera"),
+];
+
+/// HTTPS 端口证书的主题和颁发者 (RFC 4514 格式)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Certificate {
+    pub subject: String,
+    pub issuer: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub common_name: Option<String>,
+}
+
+/// 一次 GET 的结果
+struct Page {
+    ok: bool,
+    server: Option<String>,
+    body: String,
+}
+
+/// 文本中出现的厂商关键词 (按单词比较)
+pub fn vendor_of(text: &str) -> Option<&'static str> {
+    let lower = text.to_lowercase();
+    lower
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .find_map(|word| VENDOR_WORDS.iter().find(|(w, _)| *w == word).map(|(_, vendor)| *vendor))
+}
+
+fn title(body: &str) -> Option<String> {
+    let title = xml_text(body, "title").or_else(|| xml_text(body, "TITLE"))?;
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+async fn get(client: &reqwest::Client, base: &str, path: &str) -> Option<Page> {
+    let resp = client.get(format!("{}{}", base, path)).send().await.ok()?;
+    let ok = resp.status().is_success();
+    let server = resp.headers().get(reqwest::header::SERVER).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let body = resp.text().await.unwrap_or_default();
+    Some(Page { ok, server, body })
+}
+
+fn hostname_vendor(name: &str) -> Option<&'static str> {
+    HOSTNAME_PREFIXES.iter().find_map(|(prefix, vendor)| {
+        let rest = name.strip_prefix(prefix)?;
+        (rest.len() >= 6 && rest.chars().all(|c| c.is_ascii_hexdigit())).then_some(*vendor)
+    })
+}
+
+fn parse_certificate(der: &[u8]) -> Option<Certificate> {
+    let (_, cert) = X509Certificate::from_der(der).ok()?;
+    let common_name = cert.subject().iter_common_name().next().and_then(|cn| cn.as_str().ok()).map(str::to_string);
+    Some(Certificate { subject: cert.subject().to_string(), issuer: cert.issuer().to_string(), common_name })
+}
+
+/// 从证书认出厂商：CN 是型号 (`HP Color LaserJet MFP M477fdw`) 或出厂主机名 (`NPI3B2C1D`, `BRN30055C...`)，
+/// 否则看主题里的组织名
+pub fn certificate_model(cert: &Certificate) -> Option<String> {
+    if let Some(cn) = &cert.common_name {
+        if vendor_of(cn).is_some() {
+            return Some(cn.clone());
+        }
+        if let Some(vendor) = hostname_vendor(cn) {
+            return Some(format!("{} ({})", vendor, cn));
+        }
+    }
+    let vendor = vendor_of(&cert.subject)?;
+    Some(format!("{} ({})", vendor, cert.common_name.as_deref().unwrap_or(&cert.subject)))
+}
+
+/// 由首页 (或厂商路径) 的标题和 Server 头拼出型号，没有任何厂商特征时返回 None
+fn describe(page: &Page, path_vendor: Option<&str>) -> Option<String> {
+    // HP 内嵌服务器的 Server 头自带型号: `HP HTTP Server; HP Officejet Pro 8600 - CM749A; Serial Number: ...`
+    if let Some(model) = page.server.as_deref().and_then(|s| s.strip_prefix("HP HTTP Server;"))
+        && let Some(model) = model.split(';').next().map(str::trim).filter(|m| !m.is_empty())
+    {
+        return Some(model.to_string());
+    }
+    // HP LEDM 接口直接给出型号
+    if let Some(model) = xml_text(&page.body, "MakeAndModel") {
+        return Some(model);
+    }
+    let title = title(&page.body);
+    let vendor = title
+        .as_deref()
+        .and_then(vendor_of)
+        .or_else(|| page.server.as_deref().and_then(vendor_of))
+        .or(path_vendor)?;
+    Some(match title.or_else(|| page.server.clone()) {
+        Some(detail) if vendor_of(&detail) == Some(vendor) => detail,
+        Some(detail) => format!("{} ({})", vendor, detail),
+        None => format!("{} (web)", vendor),
+    })
+}
+
+/// HTTP 内嵌网页指纹：取首页的标题和 Server 头，认不出时再试各厂商的固定路径。
+/// 用于 JetDirect 端口被锁定 (不响应 PJL 等指令) 但网页管理界面仍然开放的打印机
+pub async fn fingerprint(source: Source, ip: IpAddr, port: u16, timeout_ms: u64) -> Option<String> {
+    let client = client(source, ip, timeout_ms).build().ok()?;
+    let base = format!("http://{}", SocketAddr::new(ip, port));
+    // 连不上就不是网页服务器；首页要求登录 (401) 时 Server 头仍可能带厂商信息
+    let home = get(&client, &base, "/").await?;
+    identify_site(&client, &base, &home).await
+}
+
+/// 端口上有 HTTP 服务 (返回任何状态码都算)
+pub async fn responds(source: Source, ip: IpAddr, port: u16, timeout_ms: u64) -> bool {
+    let Ok(client) = client(source, ip, timeout_ms).build() else { return false };
+    client.get(format!("http://{}/", SocketAddr::new(ip, port))).send().await.is_ok()
+}
+
+/// 探测用的 HTTP 客户端，指定了 `--interface` 时从该网卡的地址发出
+pub fn client(source: Source, ip: IpAddr, timeout_ms: u64) -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder()
+        .timeout(Duration::from_millis(timeout_ms.max(1000)))
+        .redirect(reqwest::redirect::Policy::limited(3));
+    match source.addr(ip) {
+        Some(addr) => builder.local_address(addr),
+        None => builder,
+    }
+}
+
+async fn identify_site(client: &reqwest::Client, base: &str, home: &Page) -> Option<String> {
+    if let Some(model) = describe(home, None) {
+        return Some(model);
+    }
+    for (path, vendor) in VENDOR_PATHS {
+        // 对任何路径都返回首页的服务器 (单页应用) 不算命中
+        if let Some(page) = get(client, base, path).await
+            && page.ok
+            && page.body != home.body
+        {
+            return describe(&page, Some(vendor));
+        }
+    }
+    None
+}
+
+/// HTTPS 探测：证书和网页两方面的线索
+pub struct TlsProbe {
+    pub certificate: Option<Certificate>,
+    /// 证书或网页认出的型号
+    pub model: Option<String>,
+}
+
+/// 连接 443，接受自签名、过期和主机名不符的证书 (打印机几乎都是这样)，
+/// 记录证书主题和颁发者；证书认不出厂商时再按网页指纹识别。
+/// 握手失败 (端口不通，或只支持 rustls 不接受的 TLS 1.0 / 1.1) 时返回 None
+pub async fn https_probe(source: Source, ip: IpAddr, port: u16, timeout_ms: u64) -> Option<TlsProbe> {
+    let (client, base) = tls_client(source, ip, port, timeout_ms)?;
+    let (certificate, home) = fetch_tls(&client, &base).await?;
+    let model = match certificate.as_ref().and_then(certificate_model) {
+        Some(model) => Some(model),
+        None => identify_site(&client, &base, &home).await,
+    };
+    Some(TlsProbe { certificate, model })
+}
+
+/// 只取证书，用于已经由其他协议识别出的打印机
+pub async fn certificate(source: Source, ip: IpAddr, port: u16, timeout_ms: u64) -> Option<Certificate> {
+    let (client, base) = tls_client(source, ip, port, timeout_ms)?;
+    fetch_tls(&client, &base).await?.0
+}
+
+fn tls_client(source: Source, ip: IpAddr, port: u16, timeout_ms: u64) -> Option<(reqwest::Client, String)> {
+    let client = client(source, ip, timeout_ms).tls_danger_accept_invalid_certs(true).tls_info(true).build().ok()?;
+    Some((client, format!("https://{}", SocketAddr::new(ip, port))))
+}
+
+/// 请求首页，返回握手时拿到的证书和页面
+async fn fetch_tls(client: &reqwest::Client, base: &str) -> Option<(Option<Certificate>, Page)> {
+    let resp = client.get(format!("{}/", base)).send().await.ok()?;
+    let certificate = resp
+        .extensions()
+        .get::<reqwest::tls::TlsInfo>()
+        .and_then(|info| info.peer_certificate())
+        .and_then(parse_certificate);
+    let ok = resp.status().is_success();
+    let server = resp.headers().get(reqwest::header::SERVER).and_then(|v| v.to_str().ok()).map(str::to_string);
+    Some((certificate, Page { ok, server, body: resp.text().await.unwrap_or_default() }))
+}
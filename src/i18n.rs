@@ -0,0 +1,245 @@
+use clap::ValueEnum;
+use std::fmt::{Display, Write as _};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 状态、错误和提示信息使用的语言
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang {
+    /// 中文
+    Zh,
+    /// English
+    En,
+}
+
+static LANG: AtomicU8 = AtomicU8::new(Lang::Zh as u8);
+
+/// 未指定 `--lang` 时按 LC_ALL / LC_MESSAGES / LANG 判断：
+/// zh_* 或未设置 (C / POSIX) 用中文，其他语言环境用英文
+pub fn detect() -> Lang {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find(|v| !v.is_empty())
+        .unwrap_or_default();
+    let neutral = locale.is_empty() || locale == "C" || locale.starts_with("C.") || locale == "POSIX";
+    if neutral || locale.starts_with("zh") { Lang::Zh } else { Lang::En }
+}
+
+pub fn set_lang(lang: Lang) {
+    LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn lang() -> Lang {
+    if LANG.load(Ordering::Relaxed) == Lang::En as u8 { Lang::En } else { Lang::Zh }
+}
+
+/// 消息目录。模板中的 `{}` 按顺序替换为参数，见 `tr!`
+#[derive(Clone, Copy, Debug)]
+pub enum Msg {
+    InvalidNetwork,
+    InvalidAddress,
+    InvalidRange,
+    TooManyHosts,
+    InvalidPort,
+    ResolveFailed,
+    MdnsFailed,
+    WsdFailed,
+    SsdpFailed,
+    BjnpFailed,
+    NoAddress,
+    InterfacesFailed,
+    NoLocalNetworks,
+    NoSuchInterface,
+    SnmpUserRequired,
+    InvalidOidSpec,
+    AutoNetworks,
+    PingUnavailable,
+    PingSweep,
+    SynUnavailable,
+    Ipv6Neighbors,
+    Ipv6NeighborsFailed,
+    CreateOutputFailed,
+    WriteFailed,
+    ReadFailed,
+    Scanning,
+    ProgressFound,
+    HtmlReportWritten,
+    HtmlReportFailed,
+    WebhookNeedsState,
+    Probing,
+    ConnectFailed,
+    ConnectTimeout,
+    SendFailed,
+    Sent,
+    ResultsHeader,
+    NoPrinters,
+    NoPrintersHint,
+    SummaryHeader,
+    SummaryTotals,
+    SummarySources,
+    SummaryTarget,
+    ProbeHeader,
+    NoResponse,
+    InvalidScanFile,
+    InvalidNmapXml,
+    DiffSummary,
+    MonitorStarted,
+    MonitorRound,
+    EventWriteFailed,
+    PjlMonitorStarted,
+    SnmpWalking,
+    SnmpNoAnswer,
+    SnmpWalkOutOfScope,
+    InvalidOid,
+    TrapsStarted,
+    TrapsStartedFiltered,
+    TrapReceiveFailed,
+    TrapForwardFailed,
+    PjlMonitorReconnect,
+    ScanFinished,
+    ListenFailed,
+    ServeStarted,
+    ServeFailed,
+    InvalidExpectIp,
+    NoExpected,
+    MqttConnectFailed,
+    JsonFailed,
+    MqttPublishFailed,
+    NotifySummary,
+    NotifyFailed,
+    SyslogConnectFailed,
+    SyslogWriteFailed,
+    StateInvalid,
+    CheckpointInvalid,
+    CheckpointMismatch,
+    CheckpointResumed,
+    CheckpointMissing,
+    StateWriteFailed,
+    WebhookBaseline,
+    WebhookStatus,
+    WebhookFailed,
+}
+
+impl Msg {
+    /// (中文, English)
+    fn texts(self) -> (&'static str, &'static str) {
+        use Msg::*;
+        match self {
+            InvalidNetwork => ("扫描目标错误: {}", "Invalid target: {}"),
+            InvalidAddress => ("无效地址: {}", "Invalid address: {}"),
+            InvalidRange => ("无效范围: {}", "Invalid range: {}"),
+            TooManyHosts => (
+                "目标共 {} 个地址，--randomize / --ping-first / --syn 需要先列出全部主机，最多 {} 个",
+                "Targets contain {} addresses; --randomize / --ping-first / --syn list every host up front and allow at most {}",
+            ),
+            InvalidPort => ("无效端口: {}", "Invalid port: {}"),
+            MdnsFailed => ("mDNS 启动失败: {}", "Failed to start mDNS: {}"),
+            WsdFailed => ("WS-Discovery 发送失败: {}", "WS-Discovery probe failed: {}"),
+            SsdpFailed => ("SSDP 发送失败: {}", "SSDP search failed: {}"),
+            BjnpFailed => ("BJNP 广播失败: {}", "BJNP broadcast failed: {}"),
+            ResolveFailed => ("无法解析主机名 {}: {}", "Cannot resolve {}: {}"),
+            InterfacesFailed => ("无法获取网络接口: {}", "Cannot list network interfaces: {}"),
+            NoLocalNetworks => ("未找到可扫描的本机网段，请用 -n 指定扫描目标", "No local network found, specify targets with -n"),
+            NoSuchInterface => ("找不到网卡 {}", "No such interface: {}"),
+            SnmpUserRequired => ("--snmp v3 需要同时指定 --snmp-user", "--snmp v3 requires --snmp-user"),
+            InvalidOidSpec => ("无效的 OID 定义 {} (应写成 名称=1.3.6.1...)", "Invalid OID definition {} (expected name=1.3.6.1...)"),
+            AutoNetworks => ("未指定扫描目标，自动扫描本机网段: {}", "No targets given, scanning local networks: {}"),
+            PingUnavailable => ("无法创建 ICMP 套接字，不做 ping 过滤: {}", "Cannot open an ICMP socket, skipping the ping filter: {}"),
+            PingSweep => ("Ping 预扫描: {} / {} 台主机在线", "Ping sweep: {} of {} hosts up"),
+            SynUnavailable => ("无法创建原始套接字，改用普通 TCP 连接扫描: {}", "Cannot open a raw socket, falling back to TCP connect scan: {}"),
+            NoAddress => ("{} 没有可用的地址", "{} has no usable address"),
+            Ipv6Neighbors => ("{} 前缀过大，从邻居表中找到 {} 台主机", "{} is too large to sweep, {} hosts found in the neighbor table"),
+            Ipv6NeighborsFailed => ("无法读取 IPv6 邻居表 ({}): {}", "Cannot read the IPv6 neighbor table ({}): {}"),
+            CreateOutputFailed => ("无法创建输出文件: {}", "Cannot create output file: {}"),
+            WriteFailed => ("结果写入失败: {}", "Failed to write results: {}"),
+            ReadFailed => ("无法读取 {}: {}", "Cannot read {}: {}"),
+            Scanning => ("{} 正在扫描: {} (包含 Zebra SGD 深度检测)", "{} Scanning: {} (with Zebra SGD deep detection)"),
+            ProgressFound => ("已发现 {} 台", "{} found"),
+            HtmlReportWritten => ("{} HTML 报告已生成: {}", "{} HTML report written: {}"),
+            HtmlReportFailed => ("HTML 报告写入失败: {}", "Failed to write HTML report: {}"),
+            WebhookNeedsState => (
+                "提示: 未指定 --webhook-state，单次扫描无法与上次结果比较，不会发送 Webhook",
+                "Hint: without --webhook-state a single scan cannot be compared with the previous one, no webhook will be sent",
+            ),
+            Probing => ("{} 正在探测: {}", "{} Probing: {}"),
+            ConnectFailed => ("连接 {} 失败: {}", "Connection to {} failed: {}"),
+            ConnectTimeout => ("连接 {} 超时", "Connection to {} timed out"),
+            SendFailed => ("发送失败: {}", "Send failed: {}"),
+            Sent => ("{} 已发送 {} 字节到 {}", "{} Sent {} bytes to {}"),
+            ResultsHeader => ("--- 扫描结果 ---", "--- Scan results ---"),
+            NoPrinters => ("未发现有效设备。", "No printers found."),
+            NoPrintersHint => (
+                "建议: 检查打印机是否跨网段，或防火墙是否拦截了非标准协议。",
+                "Hint: check whether the printers are on another subnet, or a firewall blocks non-standard protocols.",
+            ),
+            SummaryHeader => ("--- 统计 ---", "--- Summary ---"),
+            SummaryTotals => (
+                "扫描主机 {} / 端口开放 {} / 识别打印机 {} / 耗时 {} / 平均响应 {}",
+                "hosts scanned {} / ports open {} / printers {} / elapsed {} / avg response {}",
+            ),
+            SummarySources => ("识别来源: {}", "by source: {}"),
+            SummaryTarget => ("  {}: 扫描主机 {} / 识别打印机 {}", "  {}: {} hosts scanned / {} printers identified"),
+            ProbeHeader => ("--- 探测结果 ---", "--- Probe results ---"),
+            NoResponse => ("(无响应)", "(no response)"),
+            InvalidScanFile => ("{} 不是有效的扫描结果: {}", "{} is not a valid scan result: {}"),
+            InvalidNmapXml => ("{} 不是 nmap XML 结果 (nmap -oX)", "{} is not an nmap XML file (nmap -oX)"),
+            DiffSummary => ("新增 {} / 移除 {} / 变化 {}", "added {} / removed {} / changed {}"),
+            MonitorStarted => ("{} 开始监控: {} (每 {} 扫描一次)", "{} Monitoring: {} (scanning every {})"),
+            MonitorRound => ("{} 第 {} 轮扫描完成: {} 台在线", "{} Round {} finished: {} online"),
+            EventWriteFailed => ("事件写入失败: {}", "Failed to write event: {}"),
+            PjlMonitorStarted => ("{} 开始监听 {} 的 PJL 状态，按 Ctrl+C 结束", "{} Listening for PJL status from {}, press Ctrl+C to stop"),
+            SnmpWalking => ("{} 正在遍历 {} 的 SNMP 变量", "{} Walking SNMP variables on {}"),
+            SnmpNoAnswer => ("{} 没有回应 SNMP 请求", "{} did not answer SNMP requests"),
+            SnmpWalkOutOfScope => ("{} 不在 Printer MIB (1.3.6.1.2.1.43) 或 Host Resources MIB (1.3.6.1.2.1.25) 中", "{} is outside the Printer MIB (1.3.6.1.2.1.43) and Host Resources MIB (1.3.6.1.2.1.25)"),
+            InvalidOid => ("无法识别的 OID 或名称: {}", "Unknown OID or name: {}"),
+            TrapsStarted => ("{} 正在 {} 上接收 SNMP trap，按 Ctrl+C 结束", "{} Receiving SNMP traps on {}, press Ctrl+C to stop"),
+            TrapsStartedFiltered => ("{} 正在 {} 上接收 {} 台打印机的 SNMP trap，按 Ctrl+C 结束", "{} Receiving SNMP traps on {} from {} printers, press Ctrl+C to stop"),
+            TrapReceiveFailed => ("trap 接收失败: {}", "Failed to receive trap: {}"),
+            TrapForwardFailed => ("trap 转发到 {} 失败: {}", "Failed to forward trap to {}: {}"),
+            PjlMonitorReconnect => ("{} 打印机断开了连接，{} 秒后重连", "{} Printer closed the connection, reconnecting in {} s"),
+            ScanFinished => ("{} 扫描完成: {} 台设备", "{} Scan finished: {} printers"),
+            ListenFailed => ("无法监听 {}: {}", "Cannot listen on {}: {}"),
+            ServeStarted => ("{} HTTP 服务已启动: http://{} (扫描网段 {})", "{} HTTP server listening on http://{} (network {})"),
+            ServeFailed => ("HTTP 服务异常退出: {}", "HTTP server exited: {}"),
+            InvalidExpectIp => ("{} 中的无效 IP: {}", "Invalid IP in {}: {}"),
+            NoExpected => ("未指定期望的打印机 (--expect / --expect-file)", "No expected printers given (--expect / --expect-file)"),
+            MqttConnectFailed => ("MQTT 连接失败: {}", "MQTT connection failed: {}"),
+            JsonFailed => ("JSON 序列化失败: {}", "JSON serialization failed: {}"),
+            MqttPublishFailed => ("MQTT 发布失败 {}: {}", "MQTT publish to {} failed: {}"),
+            NotifySummary => ("发现打印机 {}", "Printer found: {}"),
+            NotifyFailed => ("桌面通知发送失败: {}", "Desktop notification failed: {}"),
+            SyslogConnectFailed => ("无法连接 syslog: {}", "Cannot connect to syslog: {}"),
+            SyslogWriteFailed => ("syslog 写入失败: {}", "Failed to write to syslog: {}"),
+            CheckpointInvalid => ("检查点文件 {} 无效: {}", "Checkpoint file {} is invalid: {}"),
+            CheckpointMismatch => ("检查点文件 {} 属于另一次扫描 (目标: {})，请去掉 --resume 重新开始", "Checkpoint file {} belongs to a different scan (targets: {}); drop --resume to start over"),
+            CheckpointResumed => ("从检查点恢复: 跳过 {} 台已扫描的主机，已发现 {} 台打印机", "Resuming from checkpoint: skipping {} scanned hosts, {} printers already found"),
+            CheckpointMissing => ("检查点文件 {} 不存在，从头开始扫描", "Checkpoint file {} not found, starting from scratch"),
+            StateInvalid => ("状态文件 {} 无效，将重新建立基线: {}", "State file {} is invalid, starting a new baseline: {}"),
+            StateWriteFailed => ("状态文件 {} 写入失败: {}", "Failed to write state file {}: {}"),
+            WebhookBaseline => ("Webhook: 首次运行，记录 {} 台设备作为基线", "Webhook: first run, recording {} printers as baseline"),
+            WebhookStatus => ("Webhook 返回 {} ({})", "Webhook returned {} ({})"),
+            WebhookFailed => ("Webhook 发送失败 ({}): {}", "Webhook delivery failed ({}): {}"),
+        }
+    }
+
+    pub fn text(self) -> &'static str {
+        let (zh, en) = self.texts();
+        match lang() {
+            Lang::Zh => zh,
+            Lang::En => en,
+        }
+    }
+
+    pub fn render(self, args: &[&dyn Display]) -> String {
+        let mut parts = self.text().split("{}");
+        let mut out = parts.next().unwrap_or_default().to_string();
+        let mut args = args.iter();
+        for part in parts {
+            if let Some(arg) = args.next() {
+                let _ = write!(out, "{}", arg);
+            }
+            out.push_str(part);
+        }
+        out
+    }
+}
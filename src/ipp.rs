@@ -0,0 +1,256 @@
+use crate::http;
+use crate::probes::Source;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+
+pub const IPP_PORT: u16 = 631;
+
+/// Get-Printer-Attributes 操作码
+const GET_PRINTER_ATTRIBUTES: u16 = 0x000b;
+/// 常见的 IPP 打印机路径，AirPrint / IPP Everywhere 设备都支持 `/ipp/print`
+const PRINTER_PATHS: &[&str] = &["/ipp/print", "/ipp", "/"];
+/// 判断免驱打印需要的属性，以及只开了 631 的打印机用来识别的型号
+const REQUESTED: &[&str] = &[
+    "ipp-versions-supported",
+    "urf-supported",
+    "document-format-supported",
+    "printer-make-and-model",
+    "color-supported",
+    "sides-supported",
+];
+
+const TAG_OPERATION: u8 = 0x01;
+const TAG_BOOLEAN: u8 = 0x22;
+const TAG_END: u8 = 0x03;
+const TAG_URI: u8 = 0x45;
+const TAG_KEYWORD: u8 = 0x44;
+const TAG_CHARSET: u8 = 0x47;
+const TAG_LANGUAGE: u8 = 0x48;
+
+fn attribute(buf: &mut Vec<u8>, tag: u8, name: &str, value: &str) {
+    buf.push(tag);
+    buf.extend((name.len() as u16).to_be_bytes());
+    buf.extend(name.as_bytes());
+    buf.extend((value.len() as u16).to_be_bytes());
+    buf.extend(value.as_bytes());
+}
+
+/// IPP 2.0 Get-Printer-Attributes 请求 (RFC 8010)
+fn request(uri: &str) -> Vec<u8> {
+    let mut buf = vec![0x02, 0x00];
+    buf.extend(GET_PRINTER_ATTRIBUTES.to_be_bytes());
+    buf.extend(1u32.to_be_bytes());
+    buf.push(TAG_OPERATION);
+    attribute(&mut buf, TAG_CHARSET, "attributes-charset", "utf-8");
+    attribute(&mut buf, TAG_LANGUAGE, "attributes-natural-language", "en");
+    attribute(&mut buf, TAG_URI, "printer-uri", uri);
+    for (i, name) in REQUESTED.iter().enumerate() {
+        // 同一属性的后续值名称长度为 0
+        attribute(&mut buf, TAG_KEYWORD, if i == 0 { "requested-attributes" } else { "" }, name);
+    }
+    buf.push(TAG_END);
+    buf
+}
+
+/// 解析响应中的属性，值一律按文本处理 (需要的属性都是 keyword / mimeMediaType)，布尔值写成 `true` / `false`
+fn parse(raw: &[u8]) -> Option<BTreeMap<String, Vec<String>>> {
+    // 状态码不是 successful-ok (0x0000-0x00ff) 时没有打印机属性
+    if raw.len() < 8 || raw[2] != 0 {
+        return None;
+    }
+    let mut attrs: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut pos = 8;
+    let mut current = String::new();
+    while let Some(&tag) = raw.get(pos) {
+        pos += 1;
+        if tag == TAG_END {
+            break;
+        }
+        // 0x00-0x0f 是分组标签，后面直接是下一个标签
+        if tag < 0x10 {
+            continue;
+        }
+        let name_len = u16::from_be_bytes([*raw.get(pos)?, *raw.get(pos + 1)?]) as usize;
+        let name = raw.get(pos + 2..pos + 2 + name_len)?;
+        pos += 2 + name_len;
+        let value_len = u16::from_be_bytes([*raw.get(pos)?, *raw.get(pos + 1)?]) as usize;
+        let value = raw.get(pos + 2..pos + 2 + value_len)?;
+        pos += 2 + value_len;
+        if !name.is_empty() {
+            current = String::from_utf8_lossy(name).to_string();
+        }
+        let value = match tag {
+            TAG_BOOLEAN => (value.first() == Some(&1)).to_string(),
+            _ => String::from_utf8_lossy(value).to_string(),
+        };
+        attrs.entry(current.clone()).or_default().push(value);
+    }
+    Some(attrs)
+}
+
+async fn printer_attributes(source: Source, ip: IpAddr, timeout_ms: u64) -> Option<BTreeMap<String, Vec<String>>> {
+    let client = http::client(source, ip, timeout_ms).build().ok()?;
+    let addr = SocketAddr::new(ip, IPP_PORT);
+    for path in PRINTER_PATHS {
+        let uri = format!("ipp://{}{}", addr, path);
+        let sent = client
+            .post(format!("http://{}{}", addr, path))
+            .header(reqwest::header::CONTENT_TYPE, "application/ipp")
+            .body(request(&uri))
+            .send()
+            .await;
+        // 连不上 631 就不用再试别的路径
+        let Ok(resp) = sent else { return None };
+        if !resp.status().is_success() {
+            continue;
+        }
+        if let Some(attrs) = resp.bytes().await.ok().as_deref().and_then(parse) {
+            return Some(attrs);
+        }
+    }
+    None
+}
+
+/// AirPrint 设备通告 `urf-supported`；IPP Everywhere 要求 IPP 2.0 以上并支持 PWG Raster
+fn attrs_driverless(attrs: &BTreeMap<String, Vec<String>>) -> bool {
+    let has = |name: &str, pred: &dyn Fn(&str) -> bool| attrs.get(name).is_some_and(|v| v.iter().any(|s| pred(s)));
+    let airprint = has("urf-supported", &|v| !v.is_empty() && v != "none");
+    let everywhere = has("ipp-versions-supported", &|v| v.starts_with("2.")) && has("document-format-supported", &|v| v == "image/pwg-raster");
+    airprint || everywhere
+}
+
+/// IPP 属性中的型号和打印能力
+#[derive(Clone, Debug, Default)]
+pub struct Capabilities {
+    /// `printer-make-and-model`，用于识别只开放 631 端口的打印机
+    pub make_and_model: Option<String>,
+    pub driverless: bool,
+    /// `color-supported`
+    pub color: bool,
+    /// `sides-supported` 中有 `two-sided-long-edge` 或 `two-sided-short-edge`
+    pub duplex: bool,
+}
+
+/// 用一个 Get-Printer-Attributes 请求查询 631 端口的 IPP 属性，取出型号，判断是否支持免驱打印、彩色和双面；
+/// 没有 IPP 服务时返回 None
+pub async fn capabilities(source: Source, ip: IpAddr, timeout_ms: u64) -> Option<Capabilities> {
+    let attrs = printer_attributes(source, ip, timeout_ms).await?;
+    let has = |name: &str, pred: fn(&str) -> bool| attrs.get(name).is_some_and(|v| v.iter().any(|s| pred(s)));
+    Some(Capabilities {
+        make_and_model: attrs.get("printer-make-and-model").and_then(|v| v.first()).map(|m| m.trim().to_string()).filter(|m| !m.is_empty()),
+        driverless: attrs_driverless(&attrs),
+        color: has("color-supported", |v| v == "true"),
+        duplex: has("sides-supported", |v| v.starts_with("two-sided")),
+    })
+}
+
+/// mDNS TXT 记录里的免驱特征：AirPrint 的 `URF`，或 `pdl` 中包含 PWG Raster / URF
+pub fn txt_driverless(txt: &BTreeMap<String, String>) -> bool {
+    let urf = txt.get("URF").is_some_and(|v| !v.is_empty() && !v.eq_ignore_ascii_case("none"));
+    let pdl = txt.get("pdl").is_some_and(|v| v.split(',').any(|f| matches!(f.trim(), "image/pwg-raster" | "image/urf")));
+    urf || pdl
+}
+
+/// mDNS TXT 记录中的 `Color=T` / `Duplex=T` (Bonjour 打印规范)
+pub fn txt_flag(txt: &BTreeMap<String, String>, key: &str) -> bool {
+    txt.get(key).is_some_and(|v| v.eq_ignore_ascii_case("T"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAG_PRINTER: u8 = 0x04;
+    const TAG_MIME: u8 = 0x49;
+    const TAG_TEXT: u8 = 0x41;
+
+    /// HL-L2350DW 的 Get-Printer-Attributes 响应 (节选)
+    fn response(status: u16) -> Vec<u8> {
+        let mut buf = vec![0x02, 0x00];
+        buf.extend(status.to_be_bytes());
+        buf.extend(1u32.to_be_bytes());
+        buf.push(TAG_OPERATION);
+        attribute(&mut buf, TAG_CHARSET, "attributes-charset", "utf-8");
+        buf.push(TAG_PRINTER);
+        attribute(&mut buf, TAG_KEYWORD, "ipp-versions-supported", "1.1");
+        attribute(&mut buf, TAG_KEYWORD, "", "2.0");
+        attribute(&mut buf, TAG_MIME, "document-format-supported", "application/pdf");
+        attribute(&mut buf, TAG_MIME, "", "image/pwg-raster");
+        attribute(&mut buf, TAG_TEXT, "printer-make-and-model", "Brother HL-L2350DW series");
+        attribute(&mut buf, TAG_KEYWORD, "sides-supported", "one-sided");
+        attribute(&mut buf, TAG_KEYWORD, "", "two-sided-long-edge");
+        buf.extend([TAG_BOOLEAN, 0x00, 0x0f]);
+        buf.extend(b"color-supported");
+        buf.extend([0x00, 0x01, 0x00]);
+        buf.push(TAG_END);
+        buf
+    }
+
+    #[test]
+    fn request_lists_attributes_as_one_set() {
+        let req = request("ipp://10.0.0.5:631/ipp/print");
+        assert_eq!(&req[..8], b"\x02\x00\x00\x0b\x00\x00\x00\x01");
+        // 请求本身就是合法的 IPP 报文，后续值归到 requested-attributes
+        assert_eq!(parse(&req).unwrap()["requested-attributes"], REQUESTED);
+    }
+
+    #[test]
+    fn additional_values_join_previous_attribute() {
+        let attrs = parse(&response(0x0000)).unwrap();
+        assert_eq!(attrs["ipp-versions-supported"], ["1.1", "2.0"]);
+        assert_eq!(attrs["document-format-supported"], ["application/pdf", "image/pwg-raster"]);
+        assert_eq!(attrs["printer-make-and-model"], ["Brother HL-L2350DW series"]);
+        assert_eq!(attrs["sides-supported"], ["one-sided", "two-sided-long-edge"]);
+        // 布尔值按 true / false 记录
+        assert_eq!(attrs["color-supported"], ["false"]);
+        assert!(attrs_driverless(&attrs));
+    }
+
+    #[test]
+    fn error_status_and_truncation_yield_nothing() {
+        // client-error-not-found
+        assert!(parse(&response(0x0406)).is_none());
+        let full = response(0x0000);
+        assert!(parse(&full[..full.len() - 4]).is_none());
+        assert!(parse(&full[..6]).is_none());
+        // 缺少结束标签时到末尾为止
+        assert!(parse(&full[..full.len() - 1]).is_some());
+    }
+
+    #[test]
+    fn driverless_needs_urf_or_ipp2_with_pwg_raster() {
+        let attrs = |pairs: &[(&str, &[&str])]| -> BTreeMap<String, Vec<String>> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect())).collect()
+        };
+        assert!(attrs_driverless(&attrs(&[("urf-supported", &["V1.4", "CP1", "W8"])])));
+        assert!(!attrs_driverless(&attrs(&[("urf-supported", &["none"])])));
+        assert!(!attrs_driverless(&attrs(&[
+            ("ipp-versions-supported", &["1.0", "1.1"]),
+            ("document-format-supported", &["image/pwg-raster"]),
+        ])));
+        assert!(!attrs_driverless(&attrs(&[
+            ("ipp-versions-supported", &["2.0"]),
+            ("document-format-supported", &["application/postscript"]),
+        ])));
+    }
+
+    #[test]
+    fn txt_records_flag_urf_and_pdl() {
+        let txt = |pairs: &[(&str, &str)]| -> BTreeMap<String, String> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        assert!(txt_driverless(&txt(&[("URF", "W8,SRGB24,CP1,RS300")])));
+        assert!(!txt_driverless(&txt(&[("URF", "None")])));
+        assert!(txt_driverless(&txt(&[("pdl", "application/octet-stream, image/urf")])));
+        assert!(!txt_driverless(&txt(&[("pdl", "application/postscript,image/pwg-raster-x")])));
+    }
+
+    #[test]
+    fn txt_flags_need_t() {
+        let txt: BTreeMap<String, String> = [("Color", "T"), ("Duplex", "F"), ("Copies", "t")].iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert!(txt_flag(&txt, "Color"));
+        assert!(!txt_flag(&txt, "Duplex"));
+        assert!(txt_flag(&txt, "Copies"));
+        assert!(!txt_flag(&txt, "Staple"));
+    }
+}
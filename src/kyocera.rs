@@ -0,0 +1,16 @@
+use crate::snmp::VendorMib;
+
+/// Kyocera 私有 MIB (企业号 1347)：kcprtGeneralModelName、kcprtSerialNumber 和各功能的累计页数
+pub const MIB: VendorMib = VendorMib {
+    vendor: "Kyocera",
+    enterprise: 1347,
+    brands: &["kyocera", "ecosys", "taskalfa"],
+    model: &[1, 3, 6, 1, 4, 1, 1347, 43, 5, 1, 1, 1, 1],
+    serial: &[1, 3, 6, 1, 4, 1, 1347, 43, 5, 1, 1, 28, 1],
+    counters: &[
+        ("total", &[1, 3, 6, 1, 4, 1, 1347, 43, 10, 1, 1, 12, 1, 1]),
+        ("print", &[1, 3, 6, 1, 4, 1, 1347, 42, 3, 1, 1, 1, 1, 1]),
+        ("copy", &[1, 3, 6, 1, 4, 1, 1347, 42, 3, 1, 1, 1, 1, 2]),
+        ("fax", &[1, 3, 6, 1, 4, 1, 1347, 42, 3, 1, 1, 1, 1, 4]),
+    ],
+};
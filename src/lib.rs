@@ -0,0 +1,967 @@
+/// 状态信息 (横幅、进度、提示) 输出到 stderr，`--quiet` 时不输出；错误信息始终用 eprintln!
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// 从消息目录取当前语言的文本并填入参数，见 `i18n::Msg`
+macro_rules! tr {
+    ($key:ident $(, $arg:expr)* $(,)?) => {
+        $crate::i18n::Msg::$key.render(&[$(&$arg as &dyn std::fmt::Display),*])
+    };
+}
+
+mod bjnp;
+mod brother;
+mod check;
+mod checkpoint;
+mod cli;
+mod confidence;
+mod diff;
+mod discovery;
+mod escl;
+mod http;
+mod i18n;
+mod ipp;
+mod kyocera;
+mod mdns;
+mod metrics;
+mod monitor;
+mod mqtt;
+mod netbios;
+mod nmap;
+mod neighbors;
+mod notify;
+mod oui;
+mod output;
+mod ping;
+mod pjl;
+mod pjl_monitor;
+mod printer_mib;
+mod probes;
+mod report;
+mod ricoh;
+mod scanner;
+mod serve;
+mod snmp;
+mod snmp_walk;
+mod syn;
+mod ssdp;
+mod supplies;
+mod syslog_sink;
+mod targets;
+mod telnet;
+mod traps;
+mod webhook;
+mod wsd;
+mod zebra;
+
+use cli::TargetArgs;
+use futures::stream::{self, StreamExt};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use rand::seq::SliceRandom;
+use probes::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use targets::{Host, Targets};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// 命令行入口，供 `main.rs` 调用，不属于库接口
+#[doc(hidden)]
+pub use cli::run;
+pub use confidence::Evidence;
+pub use escl::Scanner as EsclScanner;
+pub use http::Certificate;
+pub use output::{ScanReport, ScanSummary, TargetSummary};
+pub use pjl::{FsListing, PjlInfo};
+pub use printer_mib::{Alert, Tray};
+pub use probes::{Context, PortState, Ports, Probe, ProbeResult, register_probe};
+pub use scanner::{ScanError, Scanner, ScannerBuilder};
+pub use snmp::{AuthProtocol, EngineInfo, PrivProtocol, SnmpVersion};
+pub use supplies::Supply;
+pub use telnet::JetDirectConfig;
+pub use zebra::{HostStatus, ZebraConfig};
+
+/// 一台识别出的打印机，扫描结果和保存的 JSON / YAML 中的一项
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PrinterInfo {
+    pub ip: IpAddr,
+    pub model: String,
+    pub source: String,
+    /// `--all-probes` 时采用的型号的置信度 (0 ~ 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// `--all-probes` 时各个回应的协议给出的型号及其可信度
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evidence: Vec<confidence::Evidence>,
+    /// 打印端口建立连接所用的时间
+    pub response_ms: u64,
+    /// 识别时使用的打印端口，旧版本保存的结果没有该字段，按 9100 处理
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// 反向解析得到的主机名
+    #[serde(default)]
+    pub hostname: Option<String>,
+    /// NetBIOS 节点状态查询得到的名称
+    #[serde(default)]
+    pub netbios_name: Option<String>,
+    /// 从本机 ARP / 邻居表查到的 MAC 地址，跨网段的设备没有
+    #[serde(default)]
+    pub mac: Option<String>,
+    /// MAC 前缀 (OUI) 对应的厂商；没有 MAC 时用 SNMP sysObjectID 的企业号认出的厂商
+    #[serde(default)]
+    pub vendor: Option<String>,
+    /// 设备在 mDNS 等组播发现协议中通告的名称
+    #[serde(default)]
+    pub advertised_name: Option<String>,
+    /// 组播发现通告中附带的键值对 (mDNS TXT 记录)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub txt: BTreeMap<String, String>,
+    /// 设备状态 (idle、busy 等)，只有部分协议会返回
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// HTTPS (443) 证书的主题和颁发者，打印机常用型号或序列号作自签名证书的 CN
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<http::Certificate>,
+    /// 多功能一体机的 eSCL 网络扫描能力，不支持扫描的设备为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scanner: Option<escl::Scanner>,
+    /// 支持免驱打印 (AirPrint 或 IPP Everywhere)，可直接用 CUPS 的 `everywhere` 驱动添加
+    #[serde(default)]
+    pub driverless: bool,
+    /// 支持彩色打印，依据 IPP `color-supported`、mDNS TXT 的 `Color` 或 Printer MIB 的基本色数量
+    #[serde(default)]
+    pub color: bool,
+    /// 支持自动双面打印，依据 IPP `sides-supported`、mDNS TXT 的 `Duplex` 或 Printer MIB 的纸路类型
+    #[serde(default)]
+    pub duplex: bool,
+    /// 序列号，依次取自厂商私有 MIB / 网页、Zebra SGD、PJL INFO CONFIG 和 Printer MIB 的 prtGeneralSerialNumber
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+    /// 固件版本，取自 Zebra SGD (`appl.name`)、PJL INFO CONFIG 或 SNMP (ENTITY-MIB、sysDescr)，去掉了 `FW:` 之类的标签
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub firmware: Option<String>,
+    /// 厂商私有 MIB 中的累计页数 (`total`、`print`、`copy` 等)，Zebra 为里程计 (`labels` 等)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub counters: BTreeMap<String, u64>,
+    /// 累计页数，用于按部门统计打印量。来自私有 MIB 或 PJL 的总计数，没有时读取 Printer MIB 的 prtMarkerLifeCount
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<u64>,
+    /// 设备自己报告的网络连接状态 (Zebra Link-OS 的接口类型、速率、无线信号等)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub link: BTreeMap<String, String>,
+    /// 耗材余量，只在指定 `--with-supplies` 时查询
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub supplies: Vec<supplies::Supply>,
+    /// 各进纸盒的纸张和余量，只在 `--with-trays` 时查询
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trays: Vec<printer_mib::Tray>,
+    /// Printer MIB 告警表中的活动告警 (缺纸、卡纸、碳粉不足等)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alerts: Vec<printer_mib::Alert>,
+    /// hrPrinterDetectedErrorState 中置位的状况 (`no paper`、`low toner`、`jammed` 等)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub error_state: Vec<String>,
+    /// `--oid` 指定的额外 OID，按名称列出
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub oids: BTreeMap<String, String>,
+    /// 开放的打印相关端口 (9100、631、515、80、443，SNMP 有回应时也列出 161) 及各自回答的协议
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub open_ports: Vec<PortState>,
+    /// SNMP 回应所用的版本 (v2c 超时后会退回 v1)，没有回应时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snmp_version: Option<snmp::SnmpVersion>,
+    /// 回应的 community，`--community` 给了多个候选时用来确认是哪一个
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snmp_community: Option<String>,
+    /// 允许 SNMP SET 的 community，只在指定 `--audit-snmp-write` 时检查；不为空即是安全隐患
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snmp_writable: Vec<String>,
+    /// SNMPv3 engine ID 发现的结果，不需要 v3 凭据；v2c 没有回应而这里有值，说明设备只开了 v3
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snmp_engine: Option<snmp::EngineInfo>,
+    /// SNMP sysName
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sys_name: Option<String>,
+    /// SNMP sysLocation，通常写着楼层和房间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sys_location: Option<String>,
+    /// SNMP sysContact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sys_contact: Option<String>,
+    /// Printer MIB 的 prtGeneralPrinterName，管理员在设备上设置的名称
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub printer_name: Option<String>,
+    /// 控制面板当前显示的文字 (prtConsoleDisplayBufferText)，如 `LOAD TRAY 2 LETTER`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+    /// JetDirect telnet 配置，只在指定 `--with-telnet` 时查询
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jetdirect: Option<telnet::JetDirectConfig>,
+    /// Zebra `~HS` 主机状态 (缺纸、暂停、打印头打开、标签长度、批次剩余标签数等)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zebra_status: Option<zebra::HostStatus>,
+    /// Zebra 配置标签 (`^HH`) 中的设置，只在指定 `--with-config` 时查询
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zebra_config: Option<zebra::ZebraConfig>,
+    /// `--sgd-vars` 读取的 SGD 变量
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sgd: BTreeMap<String, String>,
+    /// PJL INFO 查询结果，只在指定 `--with-pjl-info` 时查询
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pjl: Option<pjl::PjlInfo>,
+    /// PJL 文件系统列目录的结果，只在指定 `--with-pjl-fs` 时查询
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pjl_fs: Option<pjl::FsListing>,
+}
+
+fn default_port() -> u16 {
+    PRINTER_PORT
+}
+
+/// 地址为 `0.0.0.0`、端口为 9100、其余字段为空的结果。构造结果时只写出已知的字段，其余用 `..Default::default()` 补齐
+impl Default for PrinterInfo {
+    fn default() -> Self {
+        PrinterInfo {
+            ip: Ipv4Addr::UNSPECIFIED.into(),
+            model: String::new(),
+            source: String::new(),
+            confidence: None,
+            evidence: Vec::new(),
+            response_ms: 0,
+            port: PRINTER_PORT,
+            hostname: None,
+            netbios_name: None,
+            mac: None,
+            vendor: None,
+            advertised_name: None,
+            txt: BTreeMap::new(),
+            status: None,
+            certificate: None,
+            scanner: None,
+            driverless: false,
+            color: false,
+            duplex: false,
+            serial: None,
+            firmware: None,
+            counters: BTreeMap::new(),
+            page_count: None,
+            link: BTreeMap::new(),
+            supplies: Vec::new(),
+            trays: Vec::new(),
+            alerts: Vec::new(),
+            error_state: Vec::new(),
+            oids: BTreeMap::new(),
+            open_ports: Vec::new(),
+            snmp_version: None,
+            snmp_community: None,
+            snmp_writable: Vec::new(),
+            snmp_engine: None,
+            sys_name: None,
+            sys_location: None,
+            sys_contact: None,
+            printer_name: None,
+            display: None,
+            jetdirect: None,
+            zebra_status: None,
+            zebra_config: None,
+            sgd: BTreeMap::new(),
+            pjl: None,
+            pjl_fs: None,
+        }
+    }
+}
+
+/// 识别出的型号、识别来源，以及协议顺带返回的设备状态
+struct Identity {
+    model: String,
+    source: &'static str,
+    status: Option<String>,
+    firmware: Option<String>,
+    /// 识别过程中已经取到的 HTTPS 证书，避免补充信息时再握手一次
+    certificate: Option<http::Certificate>,
+    /// 识别过程中已经取到的 IPP 属性，补充信息时不再请求
+    ipp: Option<ipp::Capabilities>,
+}
+
+impl Identity {
+    fn new(model: String, source: &'static str) -> Self {
+        Identity { model, source, status: None, firmware: None, certificate: None, ipp: None }
+    }
+}
+
+/// 单台主机的扫描结果，用于统计端口开放数和平均响应时间
+struct HostScan {
+    response_ms: u64,
+    /// 通常最多一台；多口打印服务器每个端口一台
+    printers: Vec<PrinterInfo>,
+}
+
+/// 所有打印端口都未开放时返回 None
+async fn scan_host(host: &Host, args: &TargetArgs, context: &Context) -> Option<HostScan> {
+    // 1. 严格过滤：打印端口 (默认 9100) 必须通，多个端口时用第一个开放的
+    for &port in &host.ports {
+        let started = Instant::now();
+        if host.known_rtt_ms.is_some() || is_port_open(context.source, host.ip, port, args.timeout_ms).await {
+            let response_ms = host.known_rtt_ms.unwrap_or_else(|| started.elapsed().as_millis() as u64);
+            let addr = SocketAddr::new(host.ip, port);
+            let mac = lookup_mac(host.ip).await;
+            let (identity, confidence, evidence) = match args.all_probes {
+                true => match identify_all(context, addr, args.timeout_ms).await {
+                    Some((identity, confidence, evidence)) => (Some(identity), Some(confidence), evidence),
+                    None => (None, None, Vec::new()),
+                },
+                false => (identify(context, addr, args.timeout_ms, true).await, None, Vec::new()),
+            };
+            let identity = match identity {
+                Some(identity) => identity,
+                // 所有协议都没响应，但 MAC 属于打印机厂商时仍然报告，而不是直接丢弃
+                None => match mac.as_deref().and_then(oui::printer_vendor) {
+                    Some(vendor) => Identity::new(format!("{} (unidentified)", vendor), "OUI"),
+                    None => return Some(HostScan { response_ms, printers: Vec::new() }),
+                },
+            };
+            let mut printer = PrinterInfo {
+                ip: host.ip,
+                model: identity.model,
+                source: identity.source.to_string(),
+                confidence,
+                evidence,
+                response_ms,
+                port,
+                mac,
+                status: identity.status,
+                certificate: identity.certificate,
+                firmware: identity.firmware,
+                ..Default::default()
+            };
+            enrich(&mut printer, identity.ipp, args, context).await;
+            let mut printers = vec![printer];
+            // Star 的 9101 是状态端口，不是第二台打印机
+            if port == PRINTER_PORT && printers[0].source != "Star" {
+                let extra = jetdirect_ports(&printers[0], context, args.timeout_ms).await;
+                printers.extend(extra);
+            }
+            return Some(HostScan { response_ms, printers });
+        }
+    }
+    None
+}
+
+/// 外置 JetDirect 多口打印服务器 (EX Plus3、170X 等) 在 9101、9102 上各接一台打印机，
+/// 逐个识别并作为单独的结果报告。主机名、MAC、证书以及打印服务器自己的 JetDirect / SNMP / system 组信息
+/// 沿用 9100 上的结果；组播通告、能力、序列号、计数器和耗材属于具体的打印机，不沿用
+async fn jetdirect_ports(first: &PrinterInfo, context: &Context, timeout_ms: u64) -> Vec<PrinterInfo> {
+    let mut found = Vec::new();
+    for port in JETDIRECT_PORTS {
+        let started = Instant::now();
+        if !is_port_open(context.source, first.ip, port, timeout_ms).await {
+            continue;
+        }
+        let response_ms = started.elapsed().as_millis() as u64;
+        let Some(identity) = identify(context, SocketAddr::new(first.ip, port), timeout_ms, false).await else { continue };
+        found.push(PrinterInfo {
+            ip: first.ip,
+            model: identity.model,
+            source: identity.source.to_string(),
+            response_ms,
+            port,
+            status: identity.status,
+            firmware: identity.firmware,
+            hostname: first.hostname.clone(),
+            netbios_name: first.netbios_name.clone(),
+            mac: first.mac.clone(),
+            vendor: first.vendor.clone(),
+            certificate: first.certificate.clone(),
+            link: first.link.clone(),
+            jetdirect: first.jetdirect.clone(),
+            snmp_version: first.snmp_version,
+            snmp_community: first.snmp_community.clone(),
+            snmp_writable: first.snmp_writable.clone(),
+            snmp_engine: first.snmp_engine.clone(),
+            sys_name: first.sys_name.clone(),
+            sys_location: first.sys_location.clone(),
+            sys_contact: first.sys_contact.clone(),
+            ..Default::default()
+        });
+    }
+    found
+}
+
+/// 反向解析 (PTR) 打印机的主机名，很多单位把资产编号设成主机名
+async fn reverse_lookup(ip: IpAddr) -> Option<String> {
+    tokio::task::spawn_blocking(move || dns_lookup::lookup_addr(&ip).ok()).await.ok().flatten()
+}
+
+async fn lookup_mac(ip: IpAddr) -> Option<String> {
+    tokio::task::spawn_blocking(move || neighbors::lookup_mac(ip)).await.ok().flatten()
+}
+
+/// 补充主机名、NetBIOS 名称、MAC 和厂商，主动扫描和组播发现的设备都要经过这一步。
+/// `ipp` 是识别时已经取到的 IPP 属性，有了就不再请求
+async fn enrich(printer: &mut PrinterInfo, ipp: Option<ipp::Capabilities>, args: &TargetArgs, context: &Context) {
+    let (source, snmp) = (context.source, &context.snmp);
+    if !args.no_rdns {
+        printer.hostname = reverse_lookup(printer.ip).await;
+    }
+    if printer.mac.is_none() {
+        printer.mac = lookup_mac(printer.ip).await;
+    }
+    if !args.no_netbios
+        && let Some(status) = netbios::node_status(printer.ip, args.timeout_ms).await
+    {
+        printer.netbios_name = Some(status.name);
+        // 跨网段时 ARP 表里没有，NetBIOS 响应里的 MAC 可以补上
+        if printer.mac.is_none() {
+            printer.mac = status.mac;
+        }
+    }
+    printer.vendor = printer.mac.as_deref().and_then(|mac| context.oui.vendor(mac));
+    // 先确认哪些端口开放，HTTPS 证书、eSCL 和 IPP 只问开放的端口，并且与 SNMP 标量的 GET、SNMPv3 engine 发现同时进行，
+    // 不回应的设备上各步的超时不再依次累加。SNMP 走 UDP，事先无法判断端口，总是发送
+    let open = open_tcp_ports(printer, source, args.timeout_ms).await;
+    let timeout_ms = args.timeout_ms;
+    let (certificate, scanner, ipp, scalars, engine) = {
+        let (printer, open) = (&*printer, &open[..]);
+        let certificate = async move {
+            match printer.certificate.is_none() && open.contains(&http::HTTPS_PORT) {
+                true => http::certificate(source, printer.ip, http::HTTPS_PORT, timeout_ms).await,
+                false => None,
+            }
+        };
+        let ipp = async move {
+            match ipp {
+                Some(ipp) => Some(ipp),
+                None if open.contains(&ipp::IPP_PORT) => ipp::capabilities(source, printer.ip, timeout_ms).await,
+                None => None,
+            }
+        };
+        tokio::join!(
+            certificate,
+            escl::capabilities(source, printer.ip, open, timeout_ms),
+            ipp,
+            snmp::scalars(snmp, printer.ip),
+            snmp::discover_engine(snmp, printer.ip)
+        )
+    };
+    printer.certificate = printer.certificate.take().or(certificate);
+    printer.scanner = scanner;
+    printer.driverless = ipp::txt_driverless(&printer.txt) || ipp.as_ref().is_some_and(|c| c.driverless);
+    printer.color = ipp::txt_flag(&printer.txt, "Color") || ipp.as_ref().is_some_and(|c| c.color);
+    printer.duplex = ipp::txt_flag(&printer.txt, "Duplex") || ipp.as_ref().is_some_and(|c| c.duplex);
+    if let Some(values) = &scalars {
+        vendor_mib(printer, snmp, values).await;
+    }
+    brother_details(printer, context, args.timeout_ms).await;
+    zebra_details(printer, args, source).await;
+    if args.with_pjl_info && printer.source == "PJL" {
+        pjl_details(printer, source, args.timeout_ms).await;
+    }
+    if args.with_pjl_fs && printer.source == "PJL" {
+        printer.pjl_fs = pjl::fs_listing(source, SocketAddr::new(printer.ip, printer.port), args.timeout_ms).await;
+    }
+    if args.with_supplies {
+        if http::vendor_of(&printer.model) == Some("HP") {
+            printer.supplies = supplies::hp_ews(source, printer.ip, args.timeout_ms).await;
+        }
+        // 其他品牌以及 LEDM 接口不可用的 HP 从 Printer MIB 读取
+        if printer.supplies.is_empty() {
+            printer.supplies = supplies::printer_mib(snmp, printer.ip).await;
+        }
+    }
+    if args.with_telnet && http::vendor_of(&printer.model) == Some("HP") {
+        printer.jetdirect = telnet::jetdirect_config(source, printer.ip, args.timeout_ms).await;
+        // 跨网段查不到 ARP 时用打印服务器自己报告的 MAC
+        if printer.mac.is_none() {
+            printer.mac = printer.jetdirect.as_ref().and_then(|c| c.mac.clone());
+            printer.vendor = printer.mac.as_deref().and_then(|mac| context.oui.vendor(mac));
+        }
+    }
+    printer.open_ports = open_ports(printer, context, &open, ipp.is_some(), scalars.is_some(), args.timeout_ms).await;
+    if let Some(credential) = snmp.answered(printer.ip) {
+        printer.snmp_version = Some(credential.version);
+        printer.snmp_community = credential.community;
+    }
+    // 只给了 v2c 凭据时，关掉 v1 / v2c 的设备仍会回应 engine 发现
+    printer.snmp_engine = engine;
+    if printer.snmp_engine.is_some() && !printer.open_ports.iter().any(|p| p.port == snmp.port()) {
+        printer.open_ports.push(PortState { port: snmp.port(), protocol: Some("SNMPv3".to_string()) });
+    }
+    // 私有 MIB 或 PJL 已经给出总页数时直接采用，否则用 SNMP 读到的计数器
+    printer.page_count = match printer.counters.get("total") {
+        Some(&total) => Some(total),
+        None => scalars.as_ref().and_then(|values| snmp::page_count(values, http::vendor_of(&printer.model))),
+    };
+    if let Some(values) = &scalars {
+        // 路由过来的网段既没有 ARP 也常常挡掉 NetBIOS，用设备接口表里的 MAC
+        if printer.mac.is_none() {
+            printer.mac = snmp::mac_address(snmp, printer.ip).await;
+            printer.vendor = printer.mac.as_deref().and_then(|mac| context.oui.vendor(mac)).or(printer.vendor.take());
+        }
+        let system = snmp::system_info(values);
+        printer.sys_name = system.name;
+        printer.sys_location = system.location;
+        printer.sys_contact = system.contact;
+        if printer.vendor.is_none() {
+            printer.vendor = system.vendor.map(str::to_string);
+        }
+        printer.printer_name = printer_mib::printer_name(values);
+        printer.display = printer_mib::console_display(snmp, printer.ip).await;
+        printer.alerts = printer_mib::alerts(snmp, printer.ip).await;
+        let (status, errors) = printer_mib::device_status(values);
+        // 识别协议本身报告的状态 (IPP、Zebra、PJL 等) 更具体，没有时才用 Host Resources MIB
+        if printer.status.is_none() {
+            printer.status = status;
+        }
+        printer.error_state = errors;
+        if printer.serial.is_none() {
+            printer.serial = printer_mib::serial_number(values);
+        }
+        if printer.firmware.is_none() {
+            printer.firmware = snmp::firmware(values);
+        }
+        // 没有 IPP 的老机型
+        if !printer.color || !printer.duplex {
+            let (color, duplex) = printer_mib::capabilities(snmp, printer.ip, values).await;
+            printer.color |= color;
+            printer.duplex |= duplex;
+        }
+        printer.oids = snmp::custom_oids(snmp, values);
+        if args.with_trays {
+            printer.trays = printer_mib::trays(snmp, printer.ip).await;
+        }
+        if args.audit_snmp_write {
+            printer.snmp_writable = snmp::writable_communities(snmp, printer.ip).await;
+        }
+    }
+    printer.firmware = printer.firmware.take().and_then(|f| normalize_firmware(&f));
+}
+
+/// 各来源的固件版本写法不一 (`Firmware: 2.63`、`FW:V4.10`、`"V75.20.01Z"`)，
+/// 去掉引号和前面的标签、合并空白，便于跨品牌统计和比较
+fn normalize_firmware(raw: &str) -> Option<String> {
+    const LABELS: &[&str] = &["firmware version", "firmware", "fw version", "fw", "version", "ver."];
+    let mut text = raw.trim().trim_matches('"').trim();
+    if let Some(label) = LABELS.iter().find(|l| text.get(..l.len()).is_some_and(|p| p.eq_ignore_ascii_case(l))) {
+        let rest = &text[label.len()..];
+        // 只去掉后面跟着分隔符的标签，`V75.20.01Z` 中的 V 属于版本号
+        if rest.starts_with([':', '=', ' ']) {
+            text = rest.trim_start_matches([':', '=', ' ']);
+        }
+    }
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// 问主机固定端口的识别来源 (`Ports::Host`) 所用的端口，BJNP、ENPC 是 UDP。
+/// SNMP 的端口可配置，在 `open_ports` 里单独处理
+fn source_port(source: &str) -> Option<u16> {
+    match source {
+        "FTP" => Some(FTP_PORT),
+        "IPP" => Some(ipp::IPP_PORT),
+        "LPD" => Some(LPD_PORT),
+        "HTTP" => Some(http::HTTP_PORT),
+        "HTTPS" => Some(http::HTTPS_PORT),
+        "BJNP" => Some(BJNP_PORT),
+        "ENPC" => Some(ENPC_PORT),
+        _ => None,
+    }
+}
+
+/// 各打印相关端口中开放的，识别所用的端口不再连接
+async fn open_tcp_ports(printer: &PrinterInfo, source: Source, timeout_ms: u64) -> Vec<u16> {
+    let checks = PRINTING_PORTS.map(|port| async move { (port == printer.port || is_port_open(source, printer.ip, port, timeout_ms).await).then_some(port) });
+    futures::future::join_all(checks).await.into_iter().flatten().collect()
+}
+
+/// 记录 `open` 中各端口上回答的协议。扫描端口只在识别来源是往该端口写指令的探测时记为识别来源，
+/// 问主机固定端口的来源 (FTP、BJNP 等) 记在各自的端口上；
+/// 其余端口已经在补充信息时问过的 (IPP、HTTPS 证书、SNMP) 不再重复请求
+async fn open_ports(printer: &PrinterInfo, context: &Context, open: &[u16], ipp: bool, snmp: bool, timeout_ms: u64) -> Vec<PortState> {
+    let (ip, source) = (printer.ip, context.source);
+    let on_scan_port = probes::registry().iter().any(|p| p.name() == printer.source && !matches!(p.ports(), Ports::Host));
+    let identified = if on_scan_port { Some(printer.port) } else { source_port(&printer.source) };
+    let checks = PRINTING_PORTS.map(|port| async move {
+        if !open.contains(&port) && Some(port) != identified {
+            return None;
+        }
+        let protocol = match port {
+            _ if Some(port) == identified => Some(printer.source.clone()),
+            ipp::IPP_PORT => ipp.then(|| "IPP".to_string()),
+            http::HTTPS_PORT => printer.certificate.is_some().then(|| "HTTPS".to_string()),
+            http::HTTP_PORT => http::responds(source, ip, port, timeout_ms).await.then(|| "HTTP".to_string()),
+            LPD_PORT => get_lpd_info(source, SocketAddr::new(ip, port), timeout_ms).await.map(|_| "LPD".to_string()),
+            _ => None,
+        };
+        Some(PortState { port, protocol })
+    });
+    let mut ports: Vec<PortState> = futures::future::join_all(checks).await.into_iter().flatten().collect();
+    // 识别用的是不在列表里的端口 (如 9101 或自定义端口) 时也列出来
+    if !PRINTING_PORTS.contains(&printer.port) {
+        ports.insert(0, PortState { port: printer.port, protocol: on_scan_port.then(|| printer.source.clone()) });
+    }
+    if let Some(port) = identified.filter(|p| !PRINTING_PORTS.contains(p) && *p != printer.port) {
+        ports.push(PortState { port, protocol: Some(printer.source.clone()) });
+    }
+    if printer.source == "SNMP" || snmp {
+        ports.push(PortState { port: context.snmp.port(), protocol: Some("SNMP".to_string()) });
+    }
+    ports
+}
+
+/// PJL INFO 的页数补到没有私有 MIB 计数器的打印机上，状态码的含义作为 `status`
+async fn pjl_details(printer: &mut PrinterInfo, source: Source, timeout_ms: u64) {
+    let Some(info) = pjl::info(source, SocketAddr::new(printer.ip, printer.port), timeout_ms).await else { return };
+    if let Some(pages) = info.page_count {
+        printer.counters.entry("total".to_string()).or_insert(pages);
+    }
+    if printer.status.is_none() {
+        printer.status = info.status_code.map(|code| pjl::describe(code).to_string());
+    }
+    printer.serial = printer.serial.take().or_else(|| info.serial());
+    printer.firmware = printer.firmware.take().or_else(|| info.firmware());
+    printer.pjl = Some(info);
+}
+
+/// Link-OS 斑马打印机从 9200 的 JSON 通道一次读出型号、固件、序列号、网络状态和里程计；
+/// 所有斑马打印机再在打印端口上用 `~HS` 读取主机状态
+async fn zebra_details(printer: &mut PrinterInfo, args: &TargetArgs, source: Source) {
+    let timeout_ms = args.timeout_ms;
+    let hint = format!("{} {}", printer.model, printer.vendor.as_deref().unwrap_or_default());
+    if http::vendor_of(&hint) != Some("Zebra") {
+        return;
+    }
+    if let Some(info) = zebra::link_os(source, printer.ip, timeout_ms).await {
+        // SGD 只认出了型号的一部分、或者只凭 MAC 认出厂商时，以 JSON 通道的完整型号为准
+        if let Some(model) = info.model {
+            printer.model = if model.to_lowercase().starts_with("zebra") { model } else { format!("Zebra {}", model) };
+        }
+        printer.firmware = info.firmware.or(printer.firmware.take());
+        printer.serial = info.serial.or(printer.serial.take());
+        printer.counters.extend(info.odometer);
+        printer.link = info.link;
+    }
+    // 网页、SNMP 等方式识别出的设备，扫描端口不一定是打印端口
+    if ZPL_PORTS.applies(printer.port) {
+        let addr = SocketAddr::new(printer.ip, printer.port);
+        printer.zebra_status = zebra::host_status(source, addr, timeout_ms).await;
+        if args.with_status
+            && let Some(hs) = &mut printer.zebra_status
+            && let Some((errors, warnings)) = zebra::error_status(source, addr, timeout_ms).await
+        {
+            hs.errors = errors;
+            hs.warnings = warnings;
+        }
+        if printer.status.is_none() {
+            printer.status = printer.zebra_status.as_ref().map(|s| s.summary().to_string());
+        }
+        // 没有 9200 JSON 通道的老机型在打印端口上单独读取
+        if printer.serial.is_none() || printer.firmware.is_none() {
+            let mut values = zebra::sgd_vars(source, addr, timeout_ms, &[zebra::SERIAL.to_string(), zebra::FIRMWARE.to_string()]).await;
+            printer.serial = printer.serial.take().or_else(|| values.remove(zebra::SERIAL));
+            printer.firmware = printer.firmware.take().or_else(|| values.remove(zebra::FIRMWARE));
+        }
+        if args.with_config {
+            printer.zebra_config = zebra::config_label(source, addr, timeout_ms).await;
+            if let Some(config) = &printer.zebra_config {
+                printer.serial = printer.serial.take().or_else(|| config.serial.clone());
+                printer.firmware = printer.firmware.take().or_else(|| config.settings.get("firmware").cloned());
+            }
+        }
+        if !args.sgd_vars.is_empty() {
+            printer.sgd = zebra::sgd_vars(source, addr, timeout_ms, &args.sgd_vars).await;
+        }
+    }
+}
+
+/// Brother 打印机的 PJL ID 常常是空的，从私有 MIB 或管理网页补上型号和序列号
+async fn brother_details(printer: &mut PrinterInfo, context: &Context, timeout_ms: u64) {
+    let hint = format!("{} {}", printer.model, printer.vendor.as_deref().unwrap_or_default());
+    if printer.model != UNKNOWN_PJL && !brother::is_brother(&hint) {
+        return;
+    }
+    let Some(details) = brother::details(context, printer.ip, timeout_ms).await else { return };
+    if let Some(model) = details.model
+        && (printer.model == UNKNOWN_PJL || !printer.model.contains(&model))
+    {
+        printer.model = match brother::is_brother(&model) {
+            true => model,
+            false => format!("Brother {}", model),
+        };
+    }
+    printer.serial = printer.serial.take().or(details.serial);
+}
+
+/// Kyocera、Ricoh 等品牌的 sysDescr 不带具体型号，从私有 MIB 补上型号、序列号和计数器。
+/// 只在 `scalars` 的 sysObjectID 或 sysDescr 表明是这些品牌时才再发请求
+async fn vendor_mib(printer: &mut PrinterInfo, snmp: &snmp::Client, scalars: &snmp::Scalars) {
+    let Some((vendor, info)) = snmp::vendor_info(snmp, printer.ip, scalars).await else { return };
+    if let Some(model) = info.model
+        && !printer.model.contains(&model)
+    {
+        // 私有 MIB 的型号有时不带厂商名 (`ECOSYS M2540dn`)
+        printer.model = match model.to_lowercase().contains(&vendor.to_lowercase()) {
+            true => model,
+            false => format!("{} {}", vendor, model),
+        };
+    }
+    printer.serial = printer.serial.take().or(info.serial);
+    printer.counters.extend(info.counters);
+}
+
+/// 依次尝试各协议，返回型号和识别来源。每个协议只在它适用的端口上尝试 (见 `probes::Ports`)
+///
+/// `whole_host` 为 false 时只用端口本身回答的协议 (`Ports::Raw`)：其余协议问的是整台主机，
+/// 在多口打印服务器的其他端口上会把同一台设备重复报告
+async fn identify(context: &Context, addr: SocketAddr, timeout_ms: u64, whole_host: bool) -> Option<Identity> {
+    candidates(context, addr, timeout_ms, whole_host, false).await.into_iter().next()
+}
+
+/// `--all-probes`：不在第一个回应的协议处停下，全部跑完后按 `confidence::merge` 选出型号，
+/// 一并返回置信度和各协议的证据。状态、固件版本、证书和 IPP 属性不论采用哪个来源都沿用第一个取到的
+async fn identify_all(context: &Context, addr: SocketAddr, timeout_ms: u64) -> Option<(Identity, f64, Vec<confidence::Evidence>)> {
+    let mut found = candidates(context, addr, timeout_ms, true, true).await;
+    let pairs: Vec<(&str, &str)> = found.iter().map(|i| (i.source, i.model.as_str())).collect();
+    let (index, confidence, evidence) = confidence::merge(&pairs)?;
+    let status = found.iter().find_map(|i| i.status.clone());
+    let firmware = found.iter().find_map(|i| i.firmware.clone());
+    let certificate = found.iter().find_map(|i| i.certificate.clone());
+    let ipp = found.iter().find_map(|i| i.ipp.clone());
+    let mut identity = found.swap_remove(index);
+    identity.status = identity.status.or(status);
+    identity.firmware = identity.firmware.or(firmware);
+    identity.certificate = identity.certificate.or(certificate);
+    identity.ipp = identity.ipp.or(ipp);
+    Some((identity, confidence, evidence))
+}
+
+/// 按 `probes::registry()` 的顺序尝试各协议，`all` 为 false 时在第一个回应的协议处返回。
+/// 如果彻底沉默，返回空列表 (被过滤)
+async fn candidates(context: &Context, addr: SocketAddr, timeout_ms: u64, whole_host: bool, all: bool) -> Vec<Identity> {
+    let mut found = Vec::new();
+    for probe in probes::registry() {
+        let ports = probe.ports();
+        if !ports.applies(addr.port()) || !(whole_host || matches!(ports, Ports::Raw)) || (probe.fallback() && !found.is_empty()) {
+            continue;
+        }
+        if probe.escape_query() && found.iter().any(|f: &Identity| matches!(f.source, "PJL" | "ENPC")) {
+            continue;
+        }
+        let Some(result) = probe.run(addr, context, timeout_ms).await else { continue };
+        found.push(Identity {
+            model: result.model,
+            source: probe.name(),
+            status: result.status,
+            firmware: result.firmware,
+            certificate: result.certificate,
+            ipp: result.ipp,
+        });
+        if !all {
+            break;
+        }
+    }
+    found
+}
+
+/// 扫描进度条，输出到 stderr；`--quiet` 或 stderr 不是终端时不显示
+fn progress_bar(total: u64) -> ProgressBar {
+    if output::is_quiet() {
+        return ProgressBar::hidden();
+    }
+    let bar = ProgressBar::new(total);
+    if let Ok(style) = ProgressStyle::with_template("{elapsed_precise} [{bar:40}] {pos}/{len} {per_sec} ETA {eta} {msg}") {
+        bar.set_style(style.progress_chars("=> "));
+    }
+    bar
+}
+
+/// 某个目标的进度，显示在总进度条下方
+fn group_bar(label: &str, total: u64) -> ProgressBar {
+    let bar = ProgressBar::new(total).with_prefix(label.to_string());
+    if let Ok(style) = ProgressStyle::with_template("  {prefix:<24} [{bar:30}] {pos}/{len} {msg}") {
+        bar.set_style(style.progress_chars("=> "));
+    }
+    bar
+}
+
+/// 并发扫描整个网段，每发现一台打印机回调一次 `on_found`，返回按 IP 排序的结果和统计。
+/// 多个目标合并成一个任务流，共用 `--concurrency` 上限。
+/// `progress` 为 true 时在 stderr 显示已扫描主机数、速率和预计剩余时间，多个目标时每个目标另有一行。
+/// 给出 `checkpoint` 时跳过其中已经扫过的主机，并把进度持续写回检查点
+async fn scan_network(
+    targets: &Targets,
+    args: &TargetArgs,
+    context: &Context,
+    progress: bool,
+    mut checkpoint: Option<checkpoint::Checkpoint>,
+    mut on_found: impl FnMut(&PrinterInfo),
+) -> (Vec<PrinterInfo>, ScanSummary) {
+    // 组播发现与主动扫描同时进行
+    let discovery = discovery::start(args);
+    let mut summary = ScanSummary::default();
+    let done = checkpoint.as_ref().map(|cp| cp.state().done.clone()).unwrap_or_default();
+    let pending = || targets.hosts().filter(|h| !done.contains(&h.ip));
+    // 进度条的总数和各目标的主机数
+    let (mut total, mut counts) = (0u64, vec![0u64; targets.groups.len()]);
+    let mut count = |h: &Host| {
+        total += 1;
+        if let Some(c) = counts.get_mut(h.group) {
+            *c += 1;
+        }
+    };
+    // 打乱顺序、ping 和 SYN 扫描要先列出全部主机 (`Targets::from_args` 限制了数量)，
+    // 否则边生成地址边扫描，大网段不必先把所有主机放进内存
+    let hosts: Box<dyn Iterator<Item = Host> + Send + '_> = if args.randomize || args.ping_first || args.syn {
+        let mut hosts: Vec<Host> = pending().collect();
+        // 打乱顺序后 ping、SYN 和连接探测都不再按地址顺序扫过网段
+        if args.randomize {
+            hosts.shuffle(&mut rand::rng());
+        }
+        let listed = hosts.len();
+        let hosts = if args.ping_first {
+            let alive = ping::alive(hosts, args.timeout_ms, args.concurrency).await;
+            status!("{}", tr!(PingSweep, alive.len(), listed));
+            summary.hosts_alive = Some(alive.len());
+            alive
+        } else {
+            hosts
+        };
+        // SYN 扫描失败 (没有权限) 时退回逐个连接
+        let hosts = match args.syn {
+            true => syn::scan(hosts.clone(), context.source.v4, args.timeout_ms).await.unwrap_or(hosts),
+            false => hosts,
+        };
+        hosts.iter().for_each(&mut count);
+        Box::new(hosts.into_iter())
+    } else {
+        pending().for_each(|h| count(&h));
+        Box::new(pending())
+    };
+    // 只有一个目标时分组统计和总数相同，不再单独列出
+    let mut by_target: Vec<TargetSummary> = match targets.groups.len() {
+        0 | 1 => Vec::new(),
+        _ => targets.groups.iter().map(|g| TargetSummary { target: g.clone(), ..Default::default() }).collect(),
+    };
+    let multi = match progress && !output::is_quiet() {
+        true => MultiProgress::new(),
+        false => MultiProgress::with_draw_target(ProgressDrawTarget::hidden()),
+    };
+    let bar = multi.add(progress_bar(total));
+    let group_bars: Vec<ProgressBar> = by_target
+        .iter()
+        .enumerate()
+        .map(|(i, t)| multi.add(group_bar(&t.target, counts[i])))
+        .collect();
+    let shared = Arc::new(args.clone());
+    let mut scan_stream = stream::iter(hosts)
+        .map(|host| {
+            let args = shared.clone();
+            async move { (host.ip, host.group, scan_host(&host, &args, context).await) }
+        })
+        .buffer_unordered(args.concurrency);
+
+    let mut results = Vec::new();
+    let mut total_response_ms = 0;
+    // 上次中断前的进度计入本次统计
+    if let Some(state) = checkpoint.as_ref().map(|cp| cp.state()) {
+        summary.hosts_scanned = state.done.len();
+        summary.ports_open = state.ports_open;
+        total_response_ms = state.total_response_ms;
+        for ip in &state.done {
+            if let Some(t) = targets.group_of(*ip).and_then(|g| by_target.get_mut(g)) {
+                t.hosts_scanned += 1;
+            }
+        }
+        for printer in &state.printers {
+            on_found(printer);
+            *summary.by_source.entry(printer.source.clone()).or_default() += 1;
+            if let Some(t) = targets.group_of(printer.ip).and_then(|g| by_target.get_mut(g)) {
+                t.printers += 1;
+            }
+            results.push(printer.clone());
+        }
+    }
+    while let Some((ip, group, res)) = scan_stream.next().await {
+        if let Some(cp) = checkpoint.as_mut() {
+            cp.record(ip, res.as_ref().map(|h| h.response_ms), res.as_ref().map(|h| h.printers.as_slice()).unwrap_or_default());
+        }
+        bar.inc(1);
+        summary.hosts_scanned += 1;
+        let mut target = by_target.get_mut(group);
+        if let Some(t) = target.as_deref_mut() {
+            t.hosts_scanned += 1;
+            group_bars[group].inc(1);
+        }
+        let Some(host) = res else { continue };
+        summary.ports_open += 1;
+        total_response_ms += host.response_ms;
+        for printer in host.printers {
+            // 暂时擦掉进度条，避免回调里的输出和进度条混在同一行
+            multi.suspend(|| on_found(&printer));
+            *summary.by_source.entry(printer.source.clone()).or_default() += 1;
+            results.push(printer);
+            bar.set_message(tr!(ProgressFound, results.len()));
+            if let Some(t) = target.as_deref_mut() {
+                t.printers += 1;
+                group_bars[group].set_message(tr!(ProgressFound, t.printers));
+            }
+        }
+    }
+    let _ = multi.clear();
+    if let Some(cp) = checkpoint {
+        cp.finish();
+    }
+
+    if let Some(Ok(found)) = futures::future::OptionFuture::from(discovery).await {
+        let found = found.into_iter().filter(|d| targets.contains(d.ip)).collect();
+        for mut printer in discovery::merge(&mut results, found) {
+            enrich(&mut printer, None, args, context).await;
+            on_found(&printer);
+            *summary.by_source.entry(printer.source.clone()).or_default() += 1;
+            if let Some(t) = targets.group_of(printer.ip).and_then(|g| by_target.get_mut(g)) {
+                t.printers += 1;
+            }
+            results.push(printer);
+        }
+    }
+    summary.by_target = by_target;
+    if summary.ports_open > 0 {
+        summary.avg_response_ms = Some(total_response_ms / summary.ports_open as u64);
+    }
+
+    results.sort_by_key(|k| (k.ip, k.port));
+    (results, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn firmware_labels_are_stripped() {
+        assert_eq!(normalize_firmware("Firmware: 2.63").as_deref(), Some("2.63"));
+        assert_eq!(normalize_firmware("FW:V4.10").as_deref(), Some("V4.10"));
+        assert_eq!(normalize_firmware("Firmware Version=20230412").as_deref(), Some("20230412"));
+        assert_eq!(normalize_firmware("ver. 1.10").as_deref(), Some("1.10"));
+    }
+
+    #[test]
+    fn firmware_keeps_version_prefixes() {
+        assert_eq!(normalize_firmware("\"V75.20.01Z\"").as_deref(), Some("V75.20.01Z"));
+        // 标签后面没有分隔符时是版本号的一部分
+        assert_eq!(normalize_firmware("Ver.1.10").as_deref(), Some("Ver.1.10"));
+        assert_eq!(normalize_firmware("FWA_2302").as_deref(), Some("FWA_2302"));
+    }
+
+    #[test]
+    fn firmware_whitespace_is_collapsed() {
+        assert_eq!(normalize_firmware("  2409081_052171 \t (build  7) ").as_deref(), Some("2409081_052171 (build 7)"));
+        assert_eq!(normalize_firmware("\" \""), None);
+        assert_eq!(normalize_firmware("FW: "), None);
+    }
+}
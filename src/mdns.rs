@@ -0,0 +1,103 @@
+use crate::discovery::Discovered;
+use mdns_sd::{ResolvedService, ServiceDaemon, ServiceEvent};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::time::{Instant, timeout_at};
+
+/// RAW (9100)、IPP 和 LPD 打印服务
+const SERVICE_TYPES: &[&str] = &["_pdl-datastream._tcp.local.", "_ipp._tcp.local.", "_printer._tcp.local."];
+
+/// 从 TXT 记录中取型号：`ty` 是人类可读的型号，`product` 形如 `(HP LaserJet M404)`，
+/// 最后尝试 IEEE 1284 的 `usb_MFG` + `usb_MDL`
+fn model_from_txt(txt: &BTreeMap<String, String>) -> Option<String> {
+    if let Some(ty) = txt.get("ty").filter(|s| !s.is_empty()) {
+        return Some(ty.clone());
+    }
+    if let Some(product) = txt.get("product").map(|p| p.trim_matches(['(', ')'])).filter(|s| !s.is_empty()) {
+        return Some(product.to_string());
+    }
+    let mdl = txt.get("usb_MDL").filter(|s| !s.is_empty())?;
+    Some(match txt.get("usb_MFG").filter(|s| !s.is_empty()) {
+        Some(mfg) => format!("{} {}", mfg, mdl),
+        None => mdl.clone(),
+    })
+}
+
+fn to_discovered(service: &ResolvedService) -> Vec<Discovered> {
+    let txt: BTreeMap<String, String> =
+        service.txt_properties.iter().map(|p| (p.key().to_string(), p.val_str().to_string())).collect();
+    // 实例名即 fullname 去掉 `._ipp._tcp.local.` 后缀，通常是用户设置的打印机名称
+    let name = service
+        .fullname
+        .strip_suffix(&service.ty_domain)
+        .map(|n| n.trim_end_matches('.').to_string())
+        .filter(|n| !n.is_empty());
+    let model = model_from_txt(&txt);
+    service
+        .addresses
+        .iter()
+        .map(|a| Discovered {
+            ip: a.to_ip_addr(),
+            port: service.port,
+            source: "mDNS",
+            name: name.clone(),
+            model: model.clone(),
+            txt: txt.clone(),
+        })
+        .collect()
+}
+
+/// 浏览打印相关的 mDNS 服务，在 `wait` 时间内收集所有解析出的设备
+pub async fn browse(wait: Duration) -> Vec<Discovered> {
+    let daemon = match ServiceDaemon::new() {
+        Ok(d) => d,
+        Err(e) => { eprintln!("{}", tr!(MdnsFailed, e)); return Vec::new(); }
+    };
+    let deadline = Instant::now() + wait;
+    let browsers = SERVICE_TYPES.iter().filter_map(|ty| daemon.browse(ty).ok()).map(|rx| async move {
+        let mut found = Vec::new();
+        while let Ok(Ok(event)) = timeout_at(deadline, rx.recv_async()).await {
+            if let ServiceEvent::ServiceResolved(service) = event {
+                found.extend(to_discovered(&service));
+            }
+        }
+        found
+    });
+    let found = futures::future::join_all(browsers).await.into_iter().flatten().collect();
+    let _ = daemon.shutdown();
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn txt(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn ty_wins_over_product_and_usb() {
+        let t = txt(&[("ty", "HP LaserJet Pro M404dn"), ("product", "(HP LaserJet M404)"), ("usb_MDL", "M404")]);
+        assert_eq!(model_from_txt(&t).as_deref(), Some("HP LaserJet Pro M404dn"));
+    }
+
+    #[test]
+    fn product_parens_are_trimmed() {
+        let t = txt(&[("ty", ""), ("product", "(Brother HL-L2350DW series)")]);
+        assert_eq!(model_from_txt(&t).as_deref(), Some("Brother HL-L2350DW series"));
+        // 只有括号的 product 视为空
+        let t = txt(&[("product", "()"), ("usb_MDL", "ET-4850")]);
+        assert_eq!(model_from_txt(&t).as_deref(), Some("ET-4850"));
+    }
+
+    #[test]
+    fn usb_fields_join_manufacturer() {
+        let t = txt(&[("usb_MFG", "EPSON"), ("usb_MDL", "ET-4850 Series")]);
+        assert_eq!(model_from_txt(&t).as_deref(), Some("EPSON ET-4850 Series"));
+        let t = txt(&[("usb_MFG", ""), ("usb_MDL", "ET-4850 Series")]);
+        assert_eq!(model_from_txt(&t).as_deref(), Some("ET-4850 Series"));
+        assert_eq!(model_from_txt(&txt(&[("usb_MFG", "EPSON"), ("usb_MDL", "")])), None);
+        assert_eq!(model_from_txt(&txt(&[("rp", "ipp/print")])), None);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::PrinterInfo;
+use crate::output::ScanReport;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::IpAddr;
+use std::time::UNIX_EPOCH;
+
+/// Prometheus 标签值需要转义反斜杠、双引号和换行
+fn label(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+/// 渲染 Prometheus 文本格式的指标
+///
+/// `known` 是服务启动以来出现过的所有打印机，本轮没扫到的以 `printer_up 0` 导出，
+/// 这样可以直接对 `printer_up == 0` 设置告警，而不是依赖序列消失
+pub fn render(report: &ScanReport, known: &BTreeMap<(IpAddr, u16), PrinterInfo>) -> String {
+    let mut out = String::new();
+    let online: BTreeMap<(IpAddr, u16), &PrinterInfo> = report.printers.iter().map(|p| ((p.ip, p.port), p)).collect();
+
+    header(&mut out, "printer_up", "Whether the printer answered in the last scan (1) or has been seen before but not now (0).");
+    for (key, printer) in known {
+        // 在线设备用本轮的型号，离线设备沿用最后一次看到的型号
+        let (printer, up) = match online.get(key) {
+            Some(p) => (*p, 1),
+            None => (printer, 0),
+        };
+        let _ = writeln!(
+            out,
+            "printer_up{{ip=\"{}\",port=\"{}\",model=\"{}\",source=\"{}\"}} {}",
+            key.0,
+            key.1,
+            label(&printer.model),
+            label(&printer.source),
+            up
+        );
+    }
+
+    header(&mut out, "printer_response_seconds", "TCP connect latency to the printer port in the last scan.");
+    for printer in &report.printers {
+        let _ = writeln!(out, "printer_response_seconds{{ip=\"{}\",port=\"{}\"}} {}", printer.ip, printer.port, printer.response_ms as f64 / 1000.0);
+    }
+
+    let mut by_source: BTreeMap<&str, usize> = BTreeMap::new();
+    for printer in &report.printers {
+        *by_source.entry(printer.source.as_str()).or_default() += 1;
+    }
+    header(&mut out, "printer_scanner_detected", "Printers identified in the last scan, by detection source.");
+    for (source, count) in &by_source {
+        let _ = writeln!(out, "printer_scanner_detected{{source=\"{}\"}} {}", label(source), count);
+    }
+
+    header(&mut out, "printer_scanner_printers", "Printers identified in the last scan.");
+    let _ = writeln!(out, "printer_scanner_printers {}", report.printers.len());
+
+    header(&mut out, "printer_scanner_scan_duration_seconds", "Duration of the last completed scan.");
+    let _ = writeln!(out, "printer_scanner_scan_duration_seconds {}", report.duration_ms as f64 / 1000.0);
+
+    if let Ok(ts) = humantime::parse_rfc3339(&report.timestamp)
+        && let Ok(since_epoch) = ts.duration_since(UNIX_EPOCH)
+    {
+        header(&mut out, "printer_scanner_last_scan_timestamp_seconds", "Unix time when the last completed scan started.");
+        let _ = writeln!(out, "printer_scanner_last_scan_timestamp_seconds {}", since_epoch.as_secs());
+    }
+
+    out
+}
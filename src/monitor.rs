@@ -0,0 +1,98 @@
+use crate::cli::{MonitorArgs, exit_error};
+use crate::diff::{self, Change};
+use crate::mqtt::MqttSink;
+use crate::probes::Context;
+use crate::syslog_sink::SyslogSink;
+use crate::targets::Targets;
+use crate::webhook::Webhook;
+use crate::{PrinterInfo, notify, output, scan_network};
+use colored::*;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::time::{Instant, SystemTime};
+use tokio::time::{self, MissedTickBehavior};
+
+/// `--json` 模式下每个变化事件输出的一行
+#[derive(Serialize)]
+struct MonitorEvent<'a> {
+    timestamp: &'a str,
+    #[serde(flatten)]
+    change: &'a Change,
+}
+
+fn write_event(out: &mut dyn Write, json: bool, timestamp: &str, change: &Change) -> io::Result<()> {
+    if json {
+        serde_json::to_writer(&mut *out, &MonitorEvent { timestamp, change })?;
+        writeln!(out)?;
+    } else {
+        writeln!(out, "[{}] {}", timestamp, output::format_change(change))?;
+    }
+    out.flush()
+}
+
+/// 持续扫描：每轮结果与上一轮比较，只输出发生变化的设备。
+/// 第一轮的所有设备都会作为新增上报，相当于打印初始状态
+pub async fn run_monitor(args: MonitorArgs) -> ExitCode {
+    let target = &args.target;
+    let context = match Context::from_args(target) {
+        Ok(c) => c,
+        Err(e) => { eprintln!("{}", e); return exit_error(); }
+    };
+    let targets = match Targets::from_args(target) {
+        Ok(t) => t,
+        Err(e) => { eprintln!("{}", tr!(InvalidNetwork, e)); return exit_error(); }
+    };
+
+    let mut out = match output::open_output(args.output.as_deref()) {
+        Ok(out) => out,
+        Err(e) => { eprintln!("{}", tr!(CreateOutputFailed, e)); return exit_error(); }
+    };
+
+    status!("{}", tr!(MonitorStarted, "👀".green(), targets.label, args.every));
+
+    let mut mqtt = MqttSink::connect(&args.mqtt);
+    let mut webhook = Webhook::new(&args.webhook);
+    let mut syslog = if args.syslog { SyslogSink::connect() } else { None };
+
+    let mut ticker = time::interval(args.every.into());
+    // 单轮扫描超过间隔时顺延，而不是连续补扫
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let mut previous: Vec<PrinterInfo> = Vec::new();
+    let mut round = 0u64;
+    loop {
+        ticker.tick().await;
+        round += 1;
+
+        let started = Instant::now();
+        let (current, _) = scan_network(&targets, target, &context, true, None, |_| {}).await;
+        let timestamp = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+
+        for change in diff::diff(&previous, &current) {
+            if let Err(e) = write_event(&mut out, args.json, &timestamp, &change) {
+                eprintln!("{}", tr!(EventWriteFailed, e));
+            }
+            if args.notify && let Change::Added { printer } = &change {
+                // 监控常驻运行，不必等待通知完成
+                notify::printer_found(printer);
+            }
+            if let Some(sink) = syslog.as_mut() {
+                sink.change(&change);
+            }
+        }
+        if let Some(sink) = syslog.as_mut() {
+            sink.summary(&targets.label, current.len(), started.elapsed().as_millis() as u64);
+        }
+        status!("{}", tr!(MonitorRound, "⏱️".dimmed(), round, current.len()));
+
+        if let Some(sink) = mqtt.as_mut() {
+            sink.sync(&current).await;
+        }
+        if let Some(hook) = webhook.as_mut() {
+            hook.notify_new(&current).await;
+        }
+
+        previous = current;
+    }
+}
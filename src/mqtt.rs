@@ -0,0 +1,132 @@
+use crate::PrinterInfo;
+use crate::cli::MqttArgs;
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+
+/// 订阅后等待 broker 推送保留消息的静默时间，超过即认为已全部收到
+const RETAINED_QUIET: Duration = Duration::from_millis(1000);
+
+/// 把扫描结果以保留消息发布到 `<prefix>/<ip>`，消失的设备发布空的保留消息来清除
+pub struct MqttSink {
+    client: AsyncClient,
+    prefix: String,
+    retained_rx: mpsc::UnboundedReceiver<String>,
+    /// 当前在 broker 上保留着的设备 topic；第一次同步前为 None，届时先从 broker 读取
+    published: Option<BTreeSet<String>>,
+    /// 主动断开后 broker 关闭连接属于正常情况，不再报错
+    closing: Arc<AtomicBool>,
+    task: JoinHandle<()>,
+}
+
+fn parse_broker(broker: &str) -> (String, u16) {
+    match broker.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => match port.parse() {
+            Ok(port) => (host.to_string(), port),
+            Err(_) => (broker.to_string(), 1883),
+        },
+        _ => (broker.to_string(), 1883),
+    }
+}
+
+impl MqttSink {
+    /// 未指定 `--mqtt-broker` 时返回 None
+    pub fn connect(args: &MqttArgs) -> Option<Self> {
+        let broker = args.mqtt_broker.as_deref()?;
+        let (host, port) = parse_broker(broker);
+        let client_id = args
+            .mqtt_client_id
+            .clone()
+            .unwrap_or_else(|| format!("printer_scanner-{}", std::process::id()));
+
+        let mut options = MqttOptions::new(client_id, host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if let Some(user) = &args.mqtt_username {
+            options.set_credentials(user.clone(), args.mqtt_password.clone().unwrap_or_default());
+        }
+
+        let (client, mut eventloop) = AsyncClient::new(options, 64);
+        let (retained_tx, retained_rx) = mpsc::unbounded_channel();
+        let closing = Arc::new(AtomicBool::new(false));
+        let closing_flag = closing.clone();
+        // rumqttc 需要持续 poll eventloop 才会真正收发数据
+        let task = tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(Event::Incoming(Packet::Publish(p))) if p.retain && !p.payload.is_empty() => {
+                        let _ = retained_tx.send(p.topic);
+                    }
+                    Ok(Event::Incoming(Packet::Disconnect)) => break,
+                    Ok(_) => {}
+                    Err(rumqttc::ConnectionError::RequestsDone) => break,
+                    Err(e) => {
+                        if !closing_flag.load(Ordering::SeqCst) {
+                            eprintln!("{}", tr!(MqttConnectFailed, e));
+                        }
+                        break;
+                    }
+                }
+            }
+        });
+
+        Some(MqttSink { client, prefix: args.mqtt_topic_prefix.clone(), retained_rx, published: None, closing, task })
+    }
+
+    fn topic(&self, printer: &PrinterInfo) -> String {
+        format!("{}/{}", self.prefix, printer.ip)
+    }
+
+    /// 读取 broker 上已有的保留消息，这样一次性的 `scan` 也能清除上次留下的离线设备
+    async fn load_retained(&mut self) -> BTreeSet<String> {
+        let filter = format!("{}/+", self.prefix);
+        if self.client.subscribe(&filter, QoS::AtLeastOnce).await.is_err() {
+            return BTreeSet::new();
+        }
+        let mut topics = BTreeSet::new();
+        while let Ok(Some(topic)) = timeout(RETAINED_QUIET, self.retained_rx.recv()).await {
+            topics.insert(topic);
+        }
+        let _ = self.client.unsubscribe(&filter).await;
+        topics
+    }
+
+    /// 让 broker 上的保留消息与本轮扫描结果一致
+    pub async fn sync(&mut self, printers: &[PrinterInfo]) {
+        let previous = match self.published.take() {
+            Some(p) => p,
+            None => self.load_retained().await,
+        };
+
+        let mut current = BTreeSet::new();
+        for printer in printers {
+            let topic = self.topic(printer);
+            let payload = match serde_json::to_vec(printer) {
+                Ok(p) => p,
+                Err(e) => { eprintln!("{}", tr!(JsonFailed, e)); continue; }
+            };
+            if let Err(e) = self.client.publish(&topic, QoS::AtLeastOnce, true, payload).await {
+                eprintln!("{}", tr!(MqttPublishFailed, topic, e));
+            }
+            current.insert(topic);
+        }
+        // 空的保留消息会让 broker 删除该 topic 的保留内容
+        for topic in previous.difference(&current) {
+            if let Err(e) = self.client.publish(topic, QoS::AtLeastOnce, true, Vec::new()).await {
+                eprintln!("{}", tr!(MqttPublishFailed, topic, e));
+            }
+        }
+        self.published = Some(current);
+    }
+
+    /// 断开连接，等待已排队的消息发送完毕
+    pub async fn close(self) {
+        self.closing.store(true, Ordering::SeqCst);
+        let _ = self.client.disconnect().await;
+        let _ = timeout(Duration::from_secs(5), self.task).await;
+    }
+}
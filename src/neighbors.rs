@@ -0,0 +1,47 @@
+use std::net::IpAddr;
+use std::process::Command;
+
+/// 把 `0:1b:a9:2:3:4`、`00-1B-A9-02-03-04` 等写法统一成 `00:1b:a9:02:03:04`，
+/// 不是 MAC 或是全零 (未完成的 ARP 条目) 时返回 None
+fn normalize_mac(s: &str) -> Option<String> {
+    let parts: Vec<&str> = s.split([':', '-']).collect();
+    if parts.len() != 6 || parts.iter().any(|p| p.is_empty() || p.len() > 2) {
+        return None;
+    }
+    let bytes: Vec<u8> = parts.iter().map(|p| u8::from_str_radix(p, 16).ok()).collect::<Option<_>>()?;
+    if bytes.iter().all(|b| *b == 0) {
+        return None;
+    }
+    Some(bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":"))
+}
+
+/// 在 `/proc/net/arp`、`ip neigh`、`arp -a` 的输出中找到 `ip` 所在行的 MAC。
+/// 各系统格式不同，按“同一行里有该 IP 和一个 MAC”来匹配
+fn find_mac(table: &str, ip: IpAddr) -> Option<String> {
+    table.lines().find_map(|line| {
+        let mut tokens = line.split_whitespace().map(|t| t.trim_matches(['(', ')']));
+        if !tokens.clone().any(|t| t.parse::<IpAddr>().ok() == Some(ip)) {
+            return None;
+        }
+        tokens.find_map(normalize_mac)
+    })
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// 从系统 ARP / 邻居表中查找主机的 MAC 地址。
+/// 刚完成 TCP 连接的主机一定在表里，跨网段的主机 (经过路由器) 查不到
+pub fn lookup_mac(ip: IpAddr) -> Option<String> {
+    if ip.is_ipv4()
+        && let Ok(table) = std::fs::read_to_string("/proc/net/arp")
+    {
+        return find_mac(&table, ip);
+    }
+    let ip_str = ip.to_string();
+    let table = command_output("ip", &["neigh", "show", &ip_str])
+        .or_else(|| if cfg!(windows) { command_output("arp", &["-a"]) } else { command_output("arp", &["-an"]) })?;
+    find_mac(&table, ip)
+}
@@ -0,0 +1,76 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+const NETBIOS_NS_PORT: u16 = 137;
+
+/// NetBIOS 节点状态查询的结果
+pub struct NodeStatus {
+    /// 工作站名 (后缀 0x00 的唯一名称)
+    pub name: String,
+    /// 响应中附带的网卡地址，部分设备填全零
+    pub mac: Option<String>,
+}
+
+/// NBSTAT 请求：查询名称 `*`，按 RFC 1002 的一级编码 (每个半字节加 'A') 写成 32 字节
+fn request(id: u16) -> Vec<u8> {
+    let mut packet = id.to_be_bytes().to_vec();
+    packet.extend([0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    packet.push(0x20);
+    let mut name = [0u8; 16];
+    name[0] = b'*';
+    for b in name {
+        packet.extend([b'A' + (b >> 4), b'A' + (b & 0x0f)]);
+    }
+    packet.push(0x00);
+    packet.extend([0x00, 0x21, 0x00, 0x01]);
+    packet
+}
+
+fn parse(raw: &[u8]) -> Option<NodeStatus> {
+    // 跳过 12 字节头部和回显的名称 (可能是压缩指针)
+    let mut pos = 12;
+    loop {
+        match *raw.get(pos)? {
+            0 => { pos += 1; break; }
+            len if len & 0xc0 == 0xc0 => { pos += 2; break; }
+            len => pos += len as usize + 1,
+        }
+    }
+    // 类型、类、TTL、数据长度共 10 字节
+    pos += 10;
+    let count = *raw.get(pos)? as usize;
+    pos += 1;
+    let entries = raw.get(pos..pos + count * 18)?;
+    let name = entries.chunks(18).find_map(|entry| {
+        let (suffix, flags) = (entry[15], u16::from_be_bytes([entry[16], entry[17]]));
+        // 0x8000 是组名标志 (如工作组名)，不是设备自己的名称
+        (suffix == 0x00 && flags & 0x8000 == 0).then(|| String::from_utf8_lossy(&entry[..15]).trim_end().to_string())
+    })?;
+    let mac = raw
+        .get(pos + count * 18..pos + count * 18 + 6)
+        .filter(|m| m.iter().any(|b| *b != 0))
+        .map(|m| m.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":"));
+    Some(NodeStatus { name, mac })
+}
+
+/// 向 UDP 137 发送节点状态查询。很多 Brother 和老式 HP 打印服务器只在 NetBIOS 里提供有意义的名称
+pub async fn node_status(ip: IpAddr, timeout_ms: u64) -> Option<NodeStatus> {
+    // NetBIOS 只有 IPv4
+    if !ip.is_ipv4() {
+        return None;
+    }
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await.ok()?;
+    socket.connect(SocketAddr::new(ip, NETBIOS_NS_PORT)).await.ok()?;
+    let id = std::process::id() as u16;
+    socket.send(&request(id)).await.ok()?;
+
+    let mut buffer = [0; 1024];
+    let n = timeout(Duration::from_millis(timeout_ms.min(1000)), socket.recv(&mut buffer)).await.ok()?.ok()?;
+    let raw = &buffer[..n];
+    if raw.len() < 12 || raw[..2] != id.to_be_bytes() {
+        return None;
+    }
+    parse(raw)
+}
@@ -0,0 +1,149 @@
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// nmap 结果中视为打印服务的端口：RAW (JetDirect)、IPP、LPD
+const PRINT_PORTS: &[u16] = &[9100, 631, 515];
+
+/// nmap 扫到的一台开放了打印端口的主机
+pub struct NmapHost {
+    pub ip: IpAddr,
+    pub ports: BTreeSet<u16>,
+    /// nmap 测得的平滑往返时间 (`<times srtt>`，微秒换算成毫秒)
+    pub rtt_ms: Option<u64>,
+}
+
+/// 所有 `<name ...>` 元素，返回从起始标签到对应结束标签 (或自闭合标签末尾) 的片段。
+/// nmap XML 结构固定，不需要完整的 XML 解析器
+fn elements<'a>(doc: &'a str, name: &str) -> Vec<&'a str> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut found = Vec::new();
+    let mut rest = doc;
+    while let Some(i) = rest.find(&open) {
+        let start = &rest[i..];
+        rest = &rest[i + open.len()..];
+        // 跳过 `<hostnames>`、`<hosthint>` 这类前缀相同的元素
+        if !rest.starts_with([' ', '\t', '\r', '\n', '>', '/']) {
+            continue;
+        }
+        let Some(tag_end) = start.find('>') else { break };
+        let end = if start[..tag_end].ends_with('/') {
+            tag_end + 1
+        } else {
+            start.find(&close).map(|j| j + close.len()).unwrap_or(start.len())
+        };
+        found.push(&start[..end]);
+        rest = &start[end..];
+    }
+    found
+}
+
+/// 元素起始标签里的属性值
+fn attr<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let tag = &element[..element.find('>')?];
+    let pattern = format!(" {}=\"", name);
+    let start = tag.find(&pattern)? + pattern.len();
+    let len = tag[start..].find('"')?;
+    Some(&tag[start..start + len])
+}
+
+fn parse_host(host: &str) -> Option<NmapHost> {
+    let ip = elements(host, "address")
+        .into_iter()
+        .filter(|a| matches!(attr(a, "addrtype"), Some("ipv4" | "ipv6")))
+        .find_map(|a| attr(a, "addr")?.parse().ok())?;
+    let ports: BTreeSet<u16> = elements(host, "port")
+        .into_iter()
+        .filter(|p| attr(p, "protocol") == Some("tcp"))
+        .filter(|p| elements(p, "state").first().and_then(|s| attr(s, "state")) == Some("open"))
+        .filter_map(|p| attr(p, "portid")?.parse().ok())
+        .filter(|port| PRINT_PORTS.contains(port))
+        .collect();
+    if ports.is_empty() {
+        return None;
+    }
+    let rtt_ms = elements(host, "times").first().and_then(|t| attr(t, "srtt")?.parse::<u64>().ok()).map(|us| us / 1000);
+    Some(NmapHost { ip, ports, rtt_ms })
+}
+
+/// 读取 `nmap -oX` 的输出，只保留 9100 / 631 / 515 开放的主机
+pub fn read_hosts(path: &Path) -> Result<Vec<NmapHost>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| tr!(ReadFailed, path.display(), e))?;
+    if !text.contains("<nmaprun") {
+        return Err(tr!(InvalidNmapXml, path.display()));
+    }
+    Ok(elements(&text, "host").into_iter().filter_map(parse_host).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCAN: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<nmaprun scanner="nmap" args="nmap -p 515,631,9100 -oX - 192.168.1.0/24">
+<host starttime="1700000000"><status state="up" reason="arp-response"/>
+<address addr="192.168.1.20" addrtype="ipv4"/>
+<address addr="00:80:77:12:34:56" addrtype="mac" vendor="Brother Industries"/>
+<hostnames><hostname name="brn008077123456.lan" type="PTR"/></hostnames>
+<ports>
+<port protocol="tcp" portid="515"><state state="open" reason="syn-ack"/><service name="printer"/></port>
+<port protocol="tcp" portid="631"><state state="closed" reason="reset"/></port>
+<port protocol="tcp" portid="9100"><state state="open" reason="syn-ack"/><service name="jetdirect"/></port>
+<port protocol="tcp" portid="80"><state state="open" reason="syn-ack"/></port>
+</ports>
+<times srtt="2450" rttvar="1000" to="100000"/>
+</host>
+<host><status state="up"/>
+<address addr="00:11:22:33:44:55" addrtype="mac"/>
+<address addr="fe80::1" addrtype="ipv6"/>
+<ports><port protocol="udp" portid="631"><state state="open"/></port>
+<port protocol="tcp" portid="631"><state state="open"/></port></ports>
+</host>
+<host><status state="up"/><address addr="192.168.1.30" addrtype="ipv4"/>
+<ports><port protocol="tcp" portid="9100"><state state="filtered"/></port>
+<port protocol="udp" portid="9100"><state state="open"/></port></ports>
+</host>
+</nmaprun>
+"#;
+
+    fn hosts(doc: &str) -> Vec<NmapHost> {
+        elements(doc, "host").into_iter().filter_map(parse_host).collect()
+    }
+
+    #[test]
+    fn keeps_open_print_ports_only() {
+        let found = hosts(SCAN);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].ip.to_string(), "192.168.1.20");
+        assert_eq!(found[0].ports.iter().copied().collect::<Vec<_>>(), [515, 9100]);
+        assert_eq!(found[0].rtt_ms, Some(2));
+    }
+
+    #[test]
+    fn mac_address_and_udp_ports_are_ignored() {
+        let found = hosts(SCAN);
+        assert_eq!(found[1].ip.to_string(), "fe80::1");
+        assert_eq!(found[1].ports.iter().copied().collect::<Vec<_>>(), [631]);
+        assert_eq!(found[1].rtt_ms, None);
+    }
+
+    #[test]
+    fn prefixed_element_names_are_not_matched() {
+        let doc = r#"<hostnames><hostname name="x"/></hostnames><hosthint><address addr="10.0.0.1" addrtype="ipv4"/></hosthint>"#;
+        assert!(elements(doc, "host").is_empty());
+        assert_eq!(elements(doc, "hostname"), [r#"<hostname name="x"/>"#]);
+        assert_eq!(attr(r#"<address addr="10.0.0.1" addrtype="ipv4"/>"#, "addrtype"), Some("ipv4"));
+        assert_eq!(attr(r#"<address addrtype="ipv4"/>"#, "addr"), None);
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_nmap_xml() {
+        let path = std::env::temp_dir().join(format!("nmap-test-{}.xml", std::process::id()));
+        std::fs::write(&path, "Nmap scan report for 192.168.1.20\n9100/tcp open jetdirect\n").unwrap();
+        assert!(read_hosts(&path).is_err());
+        std::fs::write(&path, SCAN).unwrap();
+        assert_eq!(read_hosts(&path).unwrap().len(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
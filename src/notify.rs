@@ -0,0 +1,15 @@
+use crate::PrinterInfo;
+use notify_rust::Notification;
+use tokio::task::JoinHandle;
+
+/// 发送一条桌面通知。底层是同步的 D-Bus / 系统调用，放到阻塞线程里执行，
+/// 调用方在退出前 await 返回的句柄，避免进程结束时通知还没发出
+pub fn printer_found(printer: &PrinterInfo) -> JoinHandle<()> {
+    let summary = tr!(NotifySummary, printer.ip);
+    let body = format!("{} ({})", printer.model, printer.source);
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = Notification::new().appname("printer_scanner").summary(&summary).body(&body).show() {
+            eprintln!("{}", tr!(NotifyFailed, e));
+        }
+    })
+}
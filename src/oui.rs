@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// 内置的打印机厂商 OUI (MAC 前 3 字节)。只收录打印机 / 打印服务器厂商，
+/// 完整的厂商库用 `--oui-file` 加载
+const PRINTER_VENDORS: &[([u8; 3], &str)] = &[
+    ([0x00, 0x01, 0xe6], "HP"),
+    ([0x00, 0x01, 0xe7], "HP"),
+    ([0x00, 0x0e, 0x7f], "HP"),
+    ([0x00, 0x17, 0xa4], "HP"),
+    ([0x00, 0x1e, 0x0b], "HP"),
+    ([0x00, 0x21, 0x5a], "HP"),
+    ([0x00, 0x60, 0xb0], "HP"),
+    ([0x08, 0x00, 0x09], "HP"),
+    ([0x10, 0x1f, 0x74], "HP"),
+    ([0x2c, 0x44, 0xfd], "HP"),
+    ([0x38, 0x63, 0xbb], "HP"),
+    ([0x3c, 0xd9, 0x2b], "HP"),
+    ([0x64, 0x51, 0x06], "HP"),
+    ([0x9c, 0x8e, 0x99], "HP"),
+    ([0xa0, 0xd3, 0xc1], "HP"),
+    ([0xb4, 0x99, 0xba], "HP"),
+    ([0xec, 0xb1, 0xd7], "HP"),
+    ([0xf4, 0xce, 0x46], "HP"),
+    ([0x00, 0x80, 0x77], "Brother"),
+    ([0x00, 0x1b, 0xa9], "Brother"),
+    ([0x30, 0x05, 0x5c], "Brother"),
+    ([0x00, 0x00, 0x85], "Canon"),
+    ([0x00, 0x1e, 0x8f], "Canon"),
+    ([0x18, 0x0c, 0xac], "Canon"),
+    ([0x2c, 0x9e, 0xfc], "Canon"),
+    ([0x60, 0x12, 0x8b], "Canon"),
+    ([0x88, 0x87, 0x17], "Canon"),
+    ([0xf4, 0x81, 0x39], "Canon"),
+    ([0x00, 0x00, 0x48], "Epson"),
+    ([0x00, 0x26, 0xab], "Epson"),
+    ([0x38, 0x1a, 0x52], "Epson"),
+    ([0x44, 0xd2, 0x44], "Epson"),
+    ([0x64, 0xeb, 0x8c], "Epson"),
+    ([0x9c, 0xae, 0xd3], "Epson"),
+    ([0xa4, 0xee, 0x57], "Epson"),
+    ([0xac, 0x18, 0x26], "Epson"),
+    ([0xb0, 0xe8, 0x92], "Epson"),
+    ([0xdc, 0xcd, 0x2f], "Epson"),
+    ([0x00, 0x07, 0x4d], "Zebra"),
+    ([0xac, 0x3f, 0xa4], "Zebra"),
+    ([0x00, 0x04, 0x00], "Lexmark"),
+    ([0x00, 0x20, 0x00], "Lexmark"),
+    ([0x00, 0x21, 0xb7], "Lexmark"),
+    ([0x00, 0x00, 0xaa], "Xerox"),
+    ([0x08, 0x00, 0x37], "Fuji Xerox"),
+    ([0x9c, 0x93, 0x4e], "Xerox"),
+    ([0x00, 0x00, 0x74], "Ricoh"),
+    ([0x00, 0x26, 0x73], "Ricoh"),
+    ([0x58, 0x38, 0x79], "Ricoh"),
+    ([0x00, 0xc0, 0xee], "Kyocera"),
+    ([0x00, 0x17, 0xc8], "Kyocera"),
+    ([0x00, 0x20, 0x6b], "Konica Minolta"),
+    ([0x08, 0x00, 0x1f], "Sharp"),
+    ([0x00, 0x80, 0x87], "Oki"),
+    ([0x00, 0x25, 0x36], "Oki"),
+    ([0x00, 0x80, 0x91], "Toshiba TEC"),
+    ([0x00, 0x11, 0x62], "Star Micronics"),
+    ([0x00, 0x10, 0x40], "Intermec"),
+    ([0x00, 0x80, 0x92], "Silex"),
+];
+
+/// `--oui-file` 加载的完整厂商库；不加载文件时为空，只用内置表。各次扫描共用时只复制引用
+#[derive(Clone, Debug, Default)]
+pub struct Database(Arc<HashMap<[u8; 3], String>>);
+
+fn prefix(mac: &str) -> Option<[u8; 3]> {
+    let mut bytes = mac.split([':', '-']).map(|p| u8::from_str_radix(p, 16).ok());
+    Some([bytes.next()??, bytes.next()??, bytes.next()??])
+}
+
+/// 解析一行厂商库。支持 IEEE `oui.txt` (`00-1B-A9   (hex)\t\tBrother Industries, LTD.`)、
+/// Wireshark `manuf` (`00:1B:A9\tBrother\tBrother Industries, LTD.`) 和 nmap 的 `nmap-mac-prefixes`
+fn parse_line(line: &str) -> Option<([u8; 3], String)> {
+    let line = line.trim();
+    let (key, rest) = line.split_once(char::is_whitespace)?;
+    // manuf 里还有 `/28`、`/36` 的细分前缀，3 字节粒度用不上
+    if key.starts_with('#') || key.contains('/') || !key.chars().all(|c| c.is_ascii_hexdigit() || c == ':' || c == '-') {
+        return None;
+    }
+    let hex: String = key.chars().filter(char::is_ascii_hexdigit).collect();
+    if hex.len() != 6 {
+        return None;
+    }
+    let oui = [
+        u8::from_str_radix(&hex[0..2], 16).ok()?,
+        u8::from_str_radix(&hex[2..4], 16).ok()?,
+        u8::from_str_radix(&hex[4..6], 16).ok()?,
+    ];
+    let rest = rest.trim().trim_start_matches("(hex)").trim_start_matches("(base 16)");
+    let name = rest.rsplit('\t').next()?.trim();
+    (!name.is_empty()).then(|| (oui, name.to_string()))
+}
+
+impl Database {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| tr!(ReadFailed, path.display(), e))?;
+        let mut database = HashMap::new();
+        for (oui, name) in text.lines().filter_map(parse_line) {
+            database.entry(oui).or_insert(name);
+        }
+        Ok(Database(Arc::new(database)))
+    }
+
+    /// MAC 对应的厂商名称，加载了 `--oui-file` 时优先用其中更完整的名称
+    pub fn vendor(&self, mac: &str) -> Option<String> {
+        if let Some(name) = self.0.get(&prefix(mac)?) {
+            return Some(name.clone());
+        }
+        printer_vendor(mac).map(str::to_string)
+    }
+}
+
+/// 内置表里的打印机厂商。端口开放但没有协议响应的主机，只有 MAC 属于这些厂商时才报告
+pub fn printer_vendor(mac: &str) -> Option<&'static str> {
+    let oui = prefix(mac)?;
+    PRINTER_VENDORS.iter().find(|(p, _)| *p == oui).map(|(_, name)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ieee_wireshark_and_nmap_lines() {
+        let brother = Some(([0x00, 0x1b, 0xa9], "Brother Industries, LTD.".to_string()));
+        assert_eq!(parse_line("00-1B-A9   (hex)\t\tBrother Industries, LTD."), brother);
+        assert_eq!(parse_line("001BA9     (base 16)\t\tBrother Industries, LTD."), brother);
+        assert_eq!(parse_line("00:1B:A9\tBrother\tBrother Industries, LTD."), brother);
+        assert_eq!(parse_line("001BA9 Brother Industries, LTD."), brother);
+        assert_eq!(parse_line("00:00:01\tXerox"), Some(([0x00, 0x00, 0x01], "Xerox".to_string())));
+    }
+
+    #[test]
+    fn skips_comments_and_long_prefixes() {
+        assert_eq!(parse_line("# 00:1B:A9 Brother"), None);
+        assert_eq!(parse_line("00:55:DA:00:00:00/28\tShinkoTe\tShinko Technos"), None);
+        assert_eq!(parse_line("00:1B:A9:01\tBrother"), None);
+        assert_eq!(parse_line("   Brother Industries, LTD."), None);
+        assert_eq!(parse_line("00-1B-A9   (hex)"), None);
+        assert_eq!(parse_line(""), None);
+    }
+
+    #[test]
+    fn builtin_table_matches_either_separator() {
+        assert_eq!(printer_vendor("00:80:77:12:34:56"), Some("Brother"));
+        assert_eq!(printer_vendor("AC-3F-A4-00-00-01"), Some("Zebra"));
+        assert_eq!(printer_vendor("00:11:22:33:44:55"), None);
+        assert_eq!(printer_vendor("zz:80:77:12:34:56"), None);
+    }
+
+    #[test]
+    fn loaded_file_overrides_builtin_names() {
+        let path = std::env::temp_dir().join(format!("oui-test-{}.txt", std::process::id()));
+        std::fs::write(
+            &path,
+            "# 测试库\n00-80-77   (hex)\t\tBrother Industries, LTD.\n00-80-77   (hex)\t\t重复条目\n00:11:22\tCimsys\tCimsys Inc\n",
+        )
+        .unwrap();
+        let database = Database::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(database.vendor("00:80:77:00:00:01").as_deref(), Some("Brother Industries, LTD."));
+        assert_eq!(database.vendor("00:11:22:00:00:01").as_deref(), Some("Cimsys Inc"));
+        // 文件里没有的前缀仍然用内置表
+        assert_eq!(database.vendor("00:07:4d:00:00:01").as_deref(), Some("Zebra"));
+        assert!(Database::load(&path.with_extension("missing")).is_err());
+        // 没有加载文件的库互不影响，只用内置表
+        assert_eq!(Database::default().vendor("00:80:77:00:00:01").as_deref(), Some("Brother"));
+        assert_eq!(Database::default().vendor("00:11:22:00:00:01"), None);
+    }
+}
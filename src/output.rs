@@ -0,0 +1,662 @@
+use crate::PrinterInfo;
+use crate::diff::Change;
+use crate::probes::{PRINTER_PORT, PortState, ProbeOutcome};
+use clap::ValueEnum;
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 彩色终端文本 (默认)
+    Text,
+    /// JSON，便于脚本和资产系统读取
+    Json,
+    /// CSV，每台打印机一行，方便导入表格
+    Csv,
+    /// NDJSON，扫描过程中每发现一台打印机立即输出一行 JSON
+    Ndjson,
+    /// GitHub 风格 Markdown 表格，可直接粘贴到 Wiki / 工单
+    Markdown,
+    /// YAML，适合提交到资产仓库做 diff
+    Yaml,
+    /// Zabbix 低级别发现 (LLD) JSON，可作为外部发现脚本使用
+    ZabbixLld,
+    /// 仿照 nmap `-oG` 的可 grep 格式，现有的 grep / awk 脚本可以直接使用
+    Grepable,
+}
+
+/// 何时输出 ANSI 颜色
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// stdout 是终端且未设置 NO_COLOR 时启用 (默认)
+    Auto,
+    /// 总是启用，包括写入文件和管道
+    Always,
+    /// 从不启用
+    Never,
+}
+
+/// 一次扫描的完整结果，结构化输出时整体序列化
+#[derive(Serialize, Debug)]
+pub struct ScanReport {
+    pub timestamp: String,
+    pub network: String,
+    pub timeout_ms: u64,
+    pub concurrency: usize,
+    /// 整轮扫描耗时
+    pub duration_ms: u64,
+    pub printers: Vec<PrinterInfo>,
+    pub summary: ScanSummary,
+}
+
+/// 扫描统计，便于调整 `--timeout-ms` 和 `--concurrency`
+#[derive(Serialize, Debug, Default)]
+pub struct ScanSummary {
+    pub hosts_scanned: usize,
+    /// `--ping-first` 时响应 ICMP 的主机数，只有这些主机会做 TCP 探测
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hosts_alive: Option<usize>,
+    /// `--ports` 中至少有一个打印端口开放的主机数，包括未能识别为打印机的
+    pub ports_open: usize,
+    /// 按识别来源统计的打印机数量
+    pub by_source: BTreeMap<String, usize>,
+    /// 端口开放主机的平均连接耗时，没有开放端口时为空
+    pub avg_response_ms: Option<u64>,
+    /// 扫描多个目标时每个目标的统计，顺序与命令行中给出的顺序一致
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub by_target: Vec<TargetSummary>,
+}
+
+/// 单个目标 (`-n` 的一项或整个目标文件) 的统计
+#[derive(Serialize, Debug, Default)]
+pub struct TargetSummary {
+    pub target: String,
+    pub hosts_scanned: usize,
+    pub printers: usize,
+}
+
+/// `probe` 子命令的结果：单台主机上每个协议的探测明细
+#[derive(Serialize, Debug)]
+pub struct ProbeReport {
+    pub timestamp: String,
+    pub ip: IpAddr,
+    pub port_open: bool,
+    pub response_ms: Option<u64>,
+    pub probes: Vec<ProbeOutcome>,
+}
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+static COLOR_ALWAYS: AtomicBool = AtomicBool::new(false);
+
+/// `auto` 交给 colored 按 NO_COLOR / CLICOLOR / CLICOLOR_FORCE 和 stdout 是否为终端判断
+pub fn set_color(choice: ColorChoice) {
+    match choice {
+        ColorChoice::Auto => {}
+        ColorChoice::Always => {
+            COLOR_ALWAYS.store(true, Ordering::Relaxed);
+            colored::control::set_override(true);
+        }
+        ColorChoice::Never => colored::control::set_override(false),
+    }
+}
+
+/// 打开结果输出目标：指定了路径就写文件，否则写 stdout
+pub fn open_output(path: Option<&Path>) -> io::Result<Box<dyn Write>> {
+    match path {
+        Some(path) => {
+            let f = File::create(path)?;
+            // 写文件时不带 ANSI 颜色码，除非指定了 `--color always`
+            if !COLOR_ALWAYS.load(Ordering::Relaxed) {
+                colored::control::set_override(false);
+            }
+            Ok(Box::new(BufWriter::new(f)))
+        }
+        None => Ok(Box::new(io::stdout())),
+    }
+}
+
+pub fn write_report(out: &mut dyn Write, format: OutputFormat, report: &ScanReport) -> io::Result<()> {
+    match format {
+        OutputFormat::Text => write_text(out, report),
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, report)?;
+            writeln!(out)
+        }
+        OutputFormat::Csv => write_csv(out, report),
+        // 结果已在扫描过程中逐行输出
+        OutputFormat::Ndjson => Ok(()),
+        OutputFormat::Markdown => write_markdown(out, report),
+        OutputFormat::Yaml => serde_yaml::to_writer(out, report).map_err(io::Error::other),
+        OutputFormat::ZabbixLld => write_zabbix_lld(out, report),
+        OutputFormat::Grepable => write_grepable(out, report),
+    }
+}
+
+/// nmap 的字段以 `/` 分隔、端口之间以 `,` 分隔，值里出现时要替换掉
+fn grepable_field(s: &str) -> String {
+    s.replace('/', "|").replace(',', ";").replace(['\t', '\r', '\n'], " ")
+}
+
+/// 识别来源作为服务名：`SGD (Zebra)` -> `sgd-zebra`
+fn grepable_service(source: &str) -> String {
+    source.split(|c: char| !c.is_ascii_alphanumeric()).filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-").to_lowercase()
+}
+
+fn grepable_host(ip: IpAddr, hostname: Option<&str>) -> String {
+    format!("Host: {} ({})", ip, hostname.map(grepable_field).unwrap_or_default())
+}
+
+/// nmap 的一个端口项：`端口/open/传输层//服务//版本/`
+fn grepable_port(port: u16, transport: &str, service: &str, version: &str) -> String {
+    format!("{}/open/{}//{}//{}/", port, transport, grepable_service(service), grepable_field(version))
+}
+
+/// 每台打印机两行：`Status: Up` 和 `Ports: 9100/open/tcp//pjl//HP LaserJet M404/, 161/open/udp//snmp///`，
+/// `Ports` 中每个开放端口一项，型号写在识别所用的端口上。首尾是与 nmap 相同格式的注释行
+fn write_grepable(out: &mut dyn Write, report: &ScanReport) -> io::Result<()> {
+    let command = std::env::args().collect::<Vec<_>>().join(" ");
+    writeln!(out, "# printer_scanner scan initiated {} as: {}", report.timestamp, command)?;
+    for printer in &report.printers {
+        let host = grepable_host(printer.ip, printer.hostname.as_deref());
+        writeln!(out, "{}\tStatus: Up", host)?;
+        let mut ports: Vec<String> = printer
+            .open_ports
+            .iter()
+            .map(|p| {
+                let protocol = p.protocol.as_deref().unwrap_or_default();
+                let model = if protocol == printer.source { printer.model.as_str() } else { "" };
+                grepable_port(p.port, p.transport(), protocol, model)
+            })
+            .collect();
+        // 没有端口明细时 (如旧版本保存的结果) 仍列出识别所用的端口
+        if ports.is_empty() {
+            ports.push(grepable_port(printer.port, "tcp", &printer.source, &printer.model));
+        }
+        writeln!(out, "{}\tPorts: {}", host, ports.join(", "))?;
+    }
+    writeln!(
+        out,
+        "# printer_scanner done at {} -- {} IP addresses ({} hosts up) scanned in {:.2} seconds",
+        report.timestamp,
+        report.summary.hosts_scanned,
+        report.printers.len(),
+        report.duration_ms as f64 / 1000.0
+    )
+}
+
+/// Zabbix 发现规则要求的格式: {"data":[{"{#IP}":"...","{#MODEL}":"..."}]}
+fn write_zabbix_lld(out: &mut dyn Write, report: &ScanReport) -> io::Result<()> {
+    let data: Vec<_> = report
+        .printers
+        .iter()
+        .map(|p| {
+            serde_json::json!({
+                "{#IP}": p.ip.to_string(),
+                "{#MODEL}": p.model,
+                "{#SOURCE}": p.source,
+                "{#HOSTNAME}": p.hostname.as_deref().unwrap_or(""),
+                "{#MAC}": p.mac.as_deref().unwrap_or(""),
+                "{#VENDOR}": p.vendor.as_deref().unwrap_or(""),
+                "{#NETBIOS}": p.netbios_name.as_deref().unwrap_or(""),
+                "{#STATUS}": p.status.as_deref().unwrap_or(""),
+            })
+        })
+        .collect();
+    serde_json::to_writer(&mut *out, &serde_json::json!({ "data": data }))?;
+    writeln!(out)
+}
+
+/// 表格单元格里的 `|` 和换行会破坏 Markdown 表格结构
+fn md_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn write_markdown(out: &mut dyn Write, report: &ScanReport) -> io::Result<()> {
+    writeln!(out, "| IP | Hostname | MAC | Model | Source | Status | Response (ms) |")?;
+    writeln!(out, "| --- | --- | --- | --- | --- | --- | ---: |")?;
+    for printer in &report.printers {
+        writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {} | {} |",
+            printer.ip,
+            md_cell(printer.hostname.as_deref().unwrap_or("")),
+            printer.mac.as_deref().unwrap_or(""),
+            md_cell(&printer.model),
+            md_cell(&printer.source),
+            md_cell(printer.status.as_deref().unwrap_or("")),
+            printer.response_ms
+        )?;
+    }
+    Ok(())
+}
+
+pub fn write_ndjson_line(out: &mut dyn Write, printer: &PrinterInfo) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, printer)?;
+    writeln!(out)?;
+    // 写文件时同样要及时落盘，方便 tail -f
+    out.flush()
+}
+
+fn join_numbers(numbers: &[u32], sep: &str) -> String {
+    numbers.iter().map(u32::to_string).collect::<Vec<_>>().join(sep)
+}
+
+fn join_counters<V: std::fmt::Display>(counters: &BTreeMap<String, V>, sep: &str) -> String {
+    counters.iter().map(|(name, count)| format!("{}{}{}", name, sep, count)).collect::<Vec<_>>().join(" ")
+}
+
+/// `9100/PJL 80/HTTP 515`：端口开放但没有协议回答时只写端口号
+fn join_ports(ports: &[PortState]) -> String {
+    ports
+        .iter()
+        .map(|p| match &p.protocol {
+            Some(protocol) => format!("{}/{}", p.port, protocol),
+            None => p.port.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn write_csv(out: &mut dyn Write, report: &ScanReport) -> io::Result<()> {
+    let mut wtr = csv::Writer::from_writer(out);
+    // 新增的列一律排在最后，按位置读取的旧脚本不受影响
+    wtr.write_record(["ip", "model", "source", "response_ms", "port", "hostname", "advertised_name", "mac", "vendor", "netbios_name", "tls_subject", "tls_issuer", "scan_resolutions", "driverless", "serial", "counters", "supplies", "open_ports", "jetdirect_firmware", "firmware", "link", "label_length_dots", "labels_remaining", "zebra_errors", "zebra_warnings", "darkness", "print_speed", "media_type", "sgd", "pjl_status", "memory_total", "pjl_fs", "snmp_version", "snmp_community", "page_count", "alerts", "status", "error_state", "sys_name", "sys_location", "sys_contact", "oids", "color", "duplex", "trays", "printer_name", "display", "snmp_writable", "snmp_engine_id", "snmp_engine_vendor", "confidence", "evidence"])?;
+    for printer in &report.printers {
+        wtr.write_record([
+            printer.ip.to_string(),
+            printer.model.clone(),
+            printer.source.clone(),
+            printer.response_ms.to_string(),
+            printer.port.to_string(),
+            printer.hostname.clone().unwrap_or_default(),
+            printer.advertised_name.clone().unwrap_or_default(),
+            printer.mac.clone().unwrap_or_default(),
+            printer.vendor.clone().unwrap_or_default(),
+            printer.netbios_name.clone().unwrap_or_default(),
+            printer.certificate.as_ref().map(|c| c.subject.clone()).unwrap_or_default(),
+            printer.certificate.as_ref().map(|c| c.issuer.clone()).unwrap_or_default(),
+            printer.scanner.as_ref().map(|s| join_numbers(&s.resolutions, " ")).unwrap_or_default(),
+            printer.driverless.to_string(),
+            printer.serial.clone().unwrap_or_default(),
+            join_counters(&printer.counters, "="),
+            crate::supplies::summary(&printer.supplies),
+            join_ports(&printer.open_ports),
+            printer.jetdirect.as_ref().and_then(|c| c.firmware.clone()).unwrap_or_default(),
+            printer.firmware.clone().unwrap_or_default(),
+            join_counters(&printer.link, "="),
+            printer.zebra_status.as_ref().map(|s| s.label_length_dots.to_string()).unwrap_or_default(),
+            printer.zebra_status.as_ref().map(|s| s.labels_remaining.to_string()).unwrap_or_default(),
+            printer.zebra_status.as_ref().map(|s| s.errors.join("; ")).unwrap_or_default(),
+            printer.zebra_status.as_ref().map(|s| s.warnings.join("; ")).unwrap_or_default(),
+            printer.zebra_config.as_ref().and_then(|c| c.darkness.clone()).unwrap_or_default(),
+            printer.zebra_config.as_ref().and_then(|c| c.print_speed.clone()).unwrap_or_default(),
+            printer.zebra_config.as_ref().and_then(|c| c.media_type.clone()).unwrap_or_default(),
+            join_counters(&printer.sgd, "="),
+            printer.pjl.as_ref().and_then(|p| p.status_text()).unwrap_or_default(),
+            printer.pjl.as_ref().and_then(|p| p.memory_total).map(|n| n.to_string()).unwrap_or_default(),
+            printer.pjl_fs.as_ref().map(|fs| fs.summary()).unwrap_or_default(),
+            printer.snmp_version.map(|v| v.as_str().to_string()).unwrap_or_default(),
+            printer.snmp_community.clone().unwrap_or_default(),
+            printer.page_count.map(|n| n.to_string()).unwrap_or_default(),
+            crate::printer_mib::summary(&printer.alerts),
+            printer.status.clone().unwrap_or_default(),
+            printer.error_state.join("; "),
+            printer.sys_name.clone().unwrap_or_default(),
+            printer.sys_location.clone().unwrap_or_default(),
+            printer.sys_contact.clone().unwrap_or_default(),
+            join_counters(&printer.oids, "="),
+            printer.color.to_string(),
+            printer.duplex.to_string(),
+            crate::printer_mib::tray_summary(&printer.trays),
+            printer.printer_name.clone().unwrap_or_default(),
+            printer.display.clone().unwrap_or_default(),
+            printer.snmp_writable.join("; "),
+            printer.snmp_engine.as_ref().map(|e| e.id.clone()).unwrap_or_default(),
+            printer.snmp_engine.as_ref().and_then(|e| e.vendor.clone()).unwrap_or_default(),
+            printer.confidence.map(|c| c.to_string()).unwrap_or_default(),
+            printer.evidence.iter().map(|e| format!("{}={}", e.source, e.model)).collect::<Vec<_>>().join("; "),
+        ])?;
+    }
+    wtr.flush()
+}
+
+fn write_text(out: &mut dyn Write, report: &ScanReport) -> io::Result<()> {
+    if !is_quiet() {
+        writeln!(out, "\n{}", tr!(ResultsHeader).yellow())?;
+    }
+    if report.printers.is_empty() {
+        if !is_quiet() {
+            writeln!(out, "{}", tr!(NoPrinters))?;
+            writeln!(out, "{}", tr!(NoPrintersHint))?;
+        }
+    } else {
+        for printer in &report.printers {
+            // 非默认端口时把端口一起显示出来
+            let addr = match printer.port {
+                PRINTER_PORT => printer.ip.to_string(),
+                port => std::net::SocketAddr::new(printer.ip, port).to_string(),
+            };
+            match &printer.hostname {
+                Some(name) => writeln!(out, "🖨️  Found: {} ({})", addr.cyan().bold(), name)?,
+                None => writeln!(out, "🖨️  Found: {}", addr.cyan().bold())?,
+            }
+            if let Some(name) = &printer.advertised_name {
+                writeln!(out, "   ├─ Name:  {}", name)?;
+            }
+            if let Some(name) = &printer.netbios_name {
+                writeln!(out, "   ├─ NetBIOS: {}", name)?;
+            }
+            if let Some(name) = &printer.sys_name {
+                writeln!(out, "   ├─ sysName: {}", name)?;
+            }
+            if let Some(location) = &printer.sys_location {
+                writeln!(out, "   ├─ Location: {}", location)?;
+            }
+            if let Some(contact) = &printer.sys_contact {
+                writeln!(out, "   ├─ Contact: {}", contact)?;
+            }
+            if let Some(name) = &printer.printer_name {
+                writeln!(out, "   ├─ Printer name: {}", name)?;
+            }
+            if let Some(display) = &printer.display {
+                writeln!(out, "   ├─ Display: {}", display.cyan())?;
+            }
+            match (&printer.mac, &printer.vendor) {
+                (Some(mac), Some(vendor)) => writeln!(out, "   ├─ MAC:   {} ({})", mac, vendor)?,
+                (Some(mac), None) => writeln!(out, "   ├─ MAC:   {}", mac)?,
+                _ => {}
+            }
+            match &printer.certificate {
+                Some(cert) if cert.issuer == cert.subject => writeln!(out, "   ├─ TLS:   {} (self-signed)", cert.subject)?,
+                Some(cert) => writeln!(out, "   ├─ TLS:   {} (issuer: {})", cert.subject, cert.issuer)?,
+                None => {}
+            }
+            if let Some(scanner) = &printer.scanner {
+                writeln!(out, "   ├─ Scan:  eSCL {} @ {} dpi", scanner.sources.join("/"), join_numbers(&scanner.resolutions, "/"))?;
+            }
+            if printer.driverless {
+                writeln!(out, "   ├─ Driverless: AirPrint / IPP Everywhere")?;
+            }
+            if printer.color || printer.duplex {
+                let features: Vec<&str> = [(printer.color, "color"), (printer.duplex, "duplex")].iter().filter(|(on, _)| *on).map(|(_, f)| *f).collect();
+                writeln!(out, "   ├─ Capabilities: {}", features.join(", "))?;
+            }
+            if let Some(serial) = &printer.serial {
+                writeln!(out, "   ├─ Serial: {}", serial)?;
+            }
+            if let Some(firmware) = &printer.firmware {
+                writeln!(out, "   ├─ Firmware: {}", firmware)?;
+            }
+            if !printer.counters.is_empty() {
+                writeln!(out, "   ├─ Pages: {}", join_counters(&printer.counters, " "))?;
+            } else if let Some(pages) = printer.page_count {
+                writeln!(out, "   ├─ Pages: {}", pages)?;
+            }
+            if !printer.link.is_empty() {
+                writeln!(out, "   ├─ Link: {}", join_counters(&printer.link, "="))?;
+            }
+            if let Some(config) = &printer.jetdirect {
+                writeln!(out, "   ├─ JetDirect: firmware {}", config.firmware.as_deref().unwrap_or("?"))?;
+            }
+            if !printer.open_ports.is_empty() {
+                writeln!(out, "   ├─ Ports: {}", join_ports(&printer.open_ports))?;
+            }
+            match (printer.snmp_version, &printer.snmp_community) {
+                (Some(version), Some(community)) => writeln!(out, "   ├─ SNMP: {} (community {})", version.as_str(), community)?,
+                (Some(version), None) => writeln!(out, "   ├─ SNMP: {}", version.as_str())?,
+                _ => {}
+            }
+            match &printer.snmp_engine {
+                Some(engine) if printer.snmp_version.is_none() => writeln!(out, "   ├─ SNMPv3 engine: {} ({})", engine.id, format!("v1/v2c disabled, {}", engine.vendor.as_deref().unwrap_or("unknown vendor")).yellow())?,
+                Some(engine) => writeln!(out, "   ├─ SNMPv3 engine: {}{}", engine.id, engine.vendor.as_ref().map(|v| format!(" ({})", v)).unwrap_or_default())?,
+                None => {}
+            }
+            if !printer.snmp_writable.is_empty() {
+                writeln!(out, "   ├─ SNMP write: {}", format!("writable with community {}", printer.snmp_writable.join(", ")).red())?;
+            }
+            if !printer.supplies.is_empty() {
+                writeln!(out, "   ├─ Supplies: {}", crate::supplies::summary(&printer.supplies))?;
+            }
+            if !printer.trays.is_empty() {
+                let summary = crate::printer_mib::tray_summary(&printer.trays);
+                let empty = printer.trays.iter().any(crate::printer_mib::Tray::is_empty);
+                writeln!(out, "   ├─ Trays: {}", if empty { summary.yellow() } else { summary.normal() })?;
+            }
+            if !printer.alerts.is_empty() {
+                let summary = crate::printer_mib::summary(&printer.alerts);
+                let critical = printer.alerts.iter().any(|a| a.severity == "critical");
+                writeln!(out, "   ├─ Alerts: {}", if critical { summary.red() } else { summary.yellow() })?;
+            }
+            if !printer.oids.is_empty() {
+                writeln!(out, "   ├─ OIDs: {}", join_counters(&printer.oids, "="))?;
+            }
+            if !printer.error_state.is_empty() {
+                writeln!(out, "   ├─ Error state: {}", printer.error_state.join(", ").yellow())?;
+            }
+            if let Some(hs) = &printer.zebra_status {
+                writeln!(out, "   ├─ Labels: length {} dots, {} left in batch, {} formats queued", hs.label_length_dots, hs.labels_remaining, hs.formats_in_buffer)?;
+                if !hs.errors.is_empty() {
+                    writeln!(out, "   ├─ Errors: {}", hs.errors.join(", "))?;
+                }
+                if !hs.warnings.is_empty() {
+                    writeln!(out, "   ├─ Warnings: {}", hs.warnings.join(", "))?;
+                }
+            }
+            if let Some(config) = &printer.zebra_config {
+                let field = |v: &Option<String>| v.clone().unwrap_or_else(|| "?".to_string());
+                writeln!(
+                    out,
+                    "   ├─ Config: darkness {}, speed {}, media {}",
+                    field(&config.darkness),
+                    field(&config.print_speed),
+                    field(&config.media_type)
+                )?;
+            }
+            if let Some(status) = printer.pjl.as_ref().and_then(|p| p.status_text()) {
+                writeln!(out, "   ├─ PJL:   {}", status)?;
+            }
+            if let Some(fs) = &printer.pjl_fs {
+                let summary = fs.summary();
+                let summary = if fs.browsable { summary.red().to_string() } else { summary };
+                writeln!(out, "   ├─ PJL FS: {}", summary)?;
+            }
+            match printer.sgd.len() {
+                0 => {}
+                // allcv 有几百个变量，文本输出只给个数
+                n if n > 10 => writeln!(out, "   ├─ SGD:   {} variables", n)?,
+                _ => writeln!(out, "   ├─ SGD:   {}", join_counters(&printer.sgd, "="))?,
+            }
+            if let Some(status) = &printer.status {
+                writeln!(out, "   ├─ Status: {}", status)?;
+            }
+            if let Some(confidence) = printer.confidence {
+                let sources = |agrees: bool| printer.evidence.iter().filter(|e| e.agrees == agrees).map(|e| e.source.as_str()).collect::<Vec<_>>().join(", ");
+                let conflicting = sources(false);
+                match conflicting.is_empty() {
+                    true => writeln!(out, "   ├─ Confidence: {:.0}% ({})", confidence * 100.0, sources(true))?,
+                    false => writeln!(out, "   ├─ Confidence: {:.0}% ({}; {})", confidence * 100.0, sources(true), format!("conflicting: {}", conflicting).yellow())?,
+                }
+            }
+            writeln!(out, "   └─ Model: {} ({})", printer.model.green().bold(), printer.source)?;
+            writeln!(out)?;
+        }
+    }
+    if !is_quiet() {
+        write_summary_text(out, report)?;
+    }
+    Ok(())
+}
+
+fn write_summary_text(out: &mut dyn Write, report: &ScanReport) -> io::Result<()> {
+    let summary = &report.summary;
+    let avg = match summary.avg_response_ms {
+        Some(ms) => format!("{} ms", ms),
+        None => "-".to_string(),
+    };
+    // 设备列表每项后已有空行，没有设备时补一个
+    if report.printers.is_empty() {
+        writeln!(out)?;
+    }
+    writeln!(out, "{}", tr!(SummaryHeader).yellow())?;
+    writeln!(
+        out,
+        "{}",
+        tr!(
+            SummaryTotals,
+            summary.hosts_scanned,
+            summary.ports_open,
+            report.printers.len(),
+            humantime::format_duration(Duration::from_millis(report.duration_ms)),
+            avg
+        )
+    )?;
+    if !summary.by_source.is_empty() {
+        let sources: Vec<String> = summary.by_source.iter().map(|(source, n)| format!("{} {}", source, n)).collect();
+        writeln!(out, "{}", tr!(SummarySources, sources.join(", ")))?;
+    }
+    for target in &summary.by_target {
+        writeln!(out, "{}", tr!(SummaryTarget, target.target, target.hosts_scanned, target.printers))?;
+    }
+    Ok(())
+}
+
+pub fn write_probe_report(out: &mut dyn Write, format: OutputFormat, report: &ProbeReport) -> io::Result<()> {
+    match format {
+        OutputFormat::Text => write_probe_text(out, report),
+        // 单机探测没有“发现列表”，LLD 格式下按 JSON 输出明细
+        OutputFormat::Json | OutputFormat::ZabbixLld => {
+            serde_json::to_writer_pretty(&mut *out, report)?;
+            writeln!(out)
+        }
+        OutputFormat::Ndjson => {
+            for probe in &report.probes {
+                serde_json::to_writer(&mut *out, probe)?;
+                writeln!(out)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(out);
+            wtr.write_record(["source", "parsed", "raw"])?;
+            for probe in &report.probes {
+                wtr.write_record([
+                    probe.source.as_str(),
+                    probe.parsed.as_deref().unwrap_or(""),
+                    probe.raw.as_deref().unwrap_or(""),
+                ])?;
+            }
+            wtr.flush()
+        }
+        OutputFormat::Markdown => {
+            writeln!(out, "| Source | Parsed | Raw |")?;
+            writeln!(out, "| --- | --- | --- |")?;
+            for probe in &report.probes {
+                writeln!(
+                    out,
+                    "| {} | {} | `{}` |",
+                    md_cell(&probe.source),
+                    md_cell(probe.parsed.as_deref().unwrap_or("-")),
+                    md_cell(&probe.raw.as_deref().unwrap_or("").escape_debug().to_string())
+                )?;
+            }
+            Ok(())
+        }
+        OutputFormat::Yaml => serde_yaml::to_writer(out, report).map_err(io::Error::other),
+        // 每个识别成功的协议一项，写在它所用的端口上
+        OutputFormat::Grepable => {
+            let host = grepable_host(report.ip, None);
+            writeln!(out, "{}\tStatus: {}", host, if report.port_open { "Up" } else { "Down" })?;
+            let ports: Vec<String> = report
+                .probes
+                .iter()
+                .filter_map(|p| {
+                    let parsed = p.parsed.as_deref()?;
+                    Some(grepable_port(p.port, p.transport, &p.source, parsed))
+                })
+                .collect();
+            if !ports.is_empty() {
+                writeln!(out, "{}\tPorts: {}", host, ports.join(", "))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn write_probe_text(out: &mut dyn Write, report: &ProbeReport) -> io::Result<()> {
+    let port = match report.response_ms {
+        Some(ms) => format!("{} ({} ms)", "open".green(), ms),
+        None => "closed".red().to_string(),
+    };
+    writeln!(out, "\n{} {}", tr!(ProbeHeader).yellow(), report.ip.to_string().cyan().bold())?;
+    writeln!(out, "9100: {}", port)?;
+    for probe in &report.probes {
+        writeln!(out)?;
+        writeln!(out, "[{}]", probe.source.bold())?;
+        match &probe.parsed {
+            Some(model) => writeln!(out, "   ├─ Parsed: {}", model.green().bold())?,
+            None => writeln!(out, "   ├─ Parsed: {}", "-".dimmed())?,
+        }
+        match &probe.raw {
+            // 原始响应里常有控制字符 (STX/ETX、ESC 等)，转义后再显示
+            Some(raw) => writeln!(out, "   └─ Raw:    {}", raw.escape_debug())?,
+            None => writeln!(out, "   └─ Raw:    {}", tr!(NoResponse).dimmed())?,
+        }
+    }
+    Ok(())
+}
+
+/// 单条变化记录的终端展示，`monitor` 和 `diff` 共用
+pub fn format_change(change: &Change) -> String {
+    match change {
+        Change::Added { printer } => format!(
+            "{} {}  {} ({})",
+            "+".green().bold(),
+            printer.ip.to_string().cyan().bold(),
+            printer.model.green(),
+            printer.source
+        ),
+        Change::Removed { printer } => format!(
+            "{} {}  {} ({})",
+            "-".red().bold(),
+            printer.ip.to_string().cyan().bold(),
+            printer.model.dimmed(),
+            printer.source
+        ),
+        Change::Changed { before, after } => format!(
+            "{} {}  {} -> {} ({})",
+            "~".yellow().bold(),
+            after.ip.to_string().cyan().bold(),
+            before.model.dimmed(),
+            after.model.green(),
+            after.source
+        ),
+        Change::Alerts { printer, raised, cleared } => format!(
+            "{} {}  {}  {}",
+            "!".yellow().bold(),
+            printer.ip.to_string().cyan().bold(),
+            printer.model,
+            raised
+                .iter()
+                .map(|a| format!("+{}", a).red().to_string())
+                .chain(cleared.iter().map(|a| format!("-{}", a).green().to_string()))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+    }
+}
@@ -0,0 +1,52 @@
+use crate::targets::Host;
+use futures::{StreamExt, stream};
+use socket2::Type;
+use std::net::IpAddr;
+use std::time::Duration;
+use surge_ping::{Client, Config, ICMP, PingIdentifier, PingSequence};
+
+/// ICMP 请求很轻，可以比 TCP 探测开更高的并发
+const PING_CONCURRENCY: usize = 256;
+
+/// 优先用原始套接字 (需要 root / CAP_NET_RAW)，失败时 surge-ping 会退回
+/// 非特权的 ICMP 数据报套接字 (Linux 需 net.ipv4.ping_group_range 允许)
+fn client(kind: ICMP) -> Option<Client> {
+    match Client::new(&Config::builder().kind(kind).sock_type_hint(Type::RAW).build()) {
+        Ok(client) => Some(client),
+        Err(e) => { eprintln!("{}", tr!(PingUnavailable, e)); None }
+    }
+}
+
+/// 最多发两次回显请求，任意一次有回应即视为在线
+async fn is_alive(client: &Client, ip: IpAddr, ident: u16, timeout: Duration) -> bool {
+    let mut pinger = client.pinger(ip, PingIdentifier(ident)).await;
+    pinger.timeout(timeout);
+    for seq in 0..2 {
+        if pinger.ping(PingSequence(seq), &[0; 16]).await.is_ok() {
+            return true;
+        }
+    }
+    false
+}
+
+/// `--ping-first`：只保留响应 ICMP 的主机。无法创建 ICMP 套接字的地址族不做过滤，
+/// 端口已确认开放的主机 (`--import-nmap`) 一定在线，也不再 ping
+pub async fn alive(hosts: Vec<Host>, timeout_ms: u64, concurrency: usize) -> Vec<Host> {
+    let v4 = hosts.iter().any(|h| h.ip.is_ipv4()).then(|| client(ICMP::V4)).flatten();
+    let v6 = hosts.iter().any(|h| h.ip.is_ipv6()).then(|| client(ICMP::V6)).flatten();
+    let timeout = Duration::from_millis((timeout_ms / 2).max(200));
+
+    let checks = hosts.into_iter().enumerate().map(|(i, host)| {
+        let client = if host.ip.is_ipv4() { v4.clone() } else { v6.clone() };
+        async move {
+            let keep = match client {
+                _ if host.known_rtt_ms.is_some() => true,
+                Some(client) => is_alive(&client, host.ip, i as u16, timeout).await,
+                None => true,
+            };
+            keep.then_some(host)
+        }
+    });
+    // 保持传入的顺序 (`--randomize` 打乱过的顺序不能被排序还原)
+    stream::iter(checks).buffered(concurrency.max(PING_CONCURRENCY)).filter_map(|h| async { h }).collect().await
+}
@@ -0,0 +1,383 @@
+use crate::probes::{self, ProbeOutcome, Source};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::timeout;
+
+/// 通用退出语言 (UEL)，PJL 作业的开头和结尾
+const UEL: &str = "\x1b%-12345X";
+/// 每个 INFO 响应以换页符结束
+const FF: u8 = 0x0c;
+
+const CONFIG: &str = "CONFIG";
+const STATUS: &str = "STATUS";
+const PAGECOUNT: &str = "PAGECOUNT";
+const MEMORY: &str = "MEMORY";
+
+/// `@PJL INFO CONFIG` / `STATUS` / `PAGECOUNT` / `MEMORY` 的解析结果
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PjlInfo {
+    /// 状态码，如 `10001` (就绪)、`41xxx` (缺纸)、`42xxx` (卡纸)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u32>,
+    /// 控制面板上显示的文字
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub online: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<u64>,
+    /// 总内存和最大可用块 (字节)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_total: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_largest: Option<u64>,
+    /// CONFIG 中的各项，枚举类的 (如 `IN TRAYS [3 ENUMERATED]`) 值为逗号连接的列表
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub config: BTreeMap<String, String>,
+}
+
+/// 状态码按千位分组的含义 (PJL Technical Reference 第 10 章)；比具体代码表短，但足够判断要不要派人过去
+pub fn describe(code: u32) -> &'static str {
+    match code {
+        10001 => "ready",
+        10002 => "ready (offline)",
+        10003 => "warming up",
+        10004 => "self test",
+        10005 => "reset",
+        10006 => "toner low",
+        10007 => "cancelling job",
+        10000..=10999 => "informational",
+        11000..=11999 => "tray empty (background)",
+        30000..=30999 => "warning",
+        35000..=35999 => "operator attention",
+        40000..=40999 => "operator intervention",
+        41000..=41999 => "paper out",
+        42000..=42999 | 44000..=44999 => "paper jam",
+        43000..=43999 => "paper handling error",
+        50000..=50999 => "hardware error",
+        _ => "unknown",
+    }
+}
+
+fn request(categories: &[&str]) -> String {
+    let mut cmd = format!("{}@PJL\r\n", UEL);
+    for category in categories {
+        cmd.push_str(&format!("@PJL INFO {}\r\n", category));
+    }
+    cmd.push_str(UEL);
+    cmd
+}
+
+/// 一个作业里发送多条 INFO，读到每条都回了换页符或等待超时为止
+async fn query(source: Source, addr: SocketAddr, timeout_ms: u64, categories: &[&str]) -> Option<Vec<u8>> {
+    let mut stream = timeout(Duration::from_millis(timeout_ms), probes::connect(source, addr)).await.ok()?.ok()?;
+    stream.write_all(request(categories).as_bytes()).await.ok()?;
+    let mut raw = Vec::new();
+    let mut buffer = [0u8; 4096];
+    while let Ok(Ok(n)) = timeout(Duration::from_millis(1500), stream.read(&mut buffer)).await {
+        if n == 0 {
+            break;
+        }
+        raw.extend(&buffer[..n]);
+        if raw.iter().filter(|&&b| b == FF).count() >= categories.len() {
+            break;
+        }
+    }
+    (!raw.is_empty()).then_some(raw)
+}
+
+/// 按回显的 `@PJL INFO <类别>` 拆开各段响应
+fn sections(raw: &[u8]) -> BTreeMap<String, Vec<String>> {
+    let text = String::from_utf8_lossy(raw);
+    let mut sections: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut current = None;
+    for line in text.lines() {
+        let line = line.trim_matches(|c: char| c == '\x0c' || c == '\x1b' || c == '\r');
+        if let Some(category) = line.strip_prefix("@PJL INFO ") {
+            current = Some(category.trim().to_uppercase());
+            continue;
+        }
+        if let Some(category) = &current
+            && !line.trim().is_empty()
+        {
+            sections.entry(category.clone()).or_default().push(line.to_string());
+        }
+    }
+    sections
+}
+
+fn value<'a>(lines: &'a [String], key: &str) -> Option<&'a str> {
+    lines.iter().find_map(|l| {
+        let (k, v) = l.split_once('=')?;
+        k.trim().eq_ignore_ascii_case(key).then(|| v.trim().trim_matches('"'))
+    })
+}
+
+fn apply_status(info: &mut PjlInfo, lines: &[String]) {
+    info.status_code = value(lines, "CODE").and_then(|c| c.parse().ok());
+    info.display = value(lines, "DISPLAY").filter(|d| !d.is_empty()).map(str::to_string);
+    info.online = value(lines, "ONLINE").map(|o| o.eq_ignore_ascii_case("TRUE"));
+}
+
+/// 多数机型只回一个数字，部分回 `PAGECOUNT=12345`
+fn apply_pagecount(info: &mut PjlInfo, lines: &[String]) {
+    let first = lines.first().map(|l| l.trim());
+    info.page_count = value(lines, "PAGECOUNT").or(first).and_then(|n| n.parse().ok());
+}
+
+fn apply_memory(info: &mut PjlInfo, lines: &[String]) {
+    info.memory_total = value(lines, "TOTAL").and_then(|n| n.parse().ok());
+    info.memory_largest = value(lines, "LARGEST").and_then(|n| n.parse().ok());
+}
+
+/// 顶格的行是配置项：`KEY=VALUE`、单独的功能名 (`DUPLEX`)，或 `KEY [n ENUMERATED]` 后接缩进的取值
+fn apply_config(info: &mut PjlInfo, lines: &[String]) {
+    let mut list: Option<(String, Vec<String>)> = None;
+    for line in lines {
+        if line.starts_with([' ', '\t'])
+            && let Some((_, items)) = &mut list
+        {
+            items.push(line.trim().to_string());
+            continue;
+        }
+        if let Some((key, items)) = list.take() {
+            info.config.insert(key, items.join(","));
+        }
+        let line = line.trim();
+        if let Some((key, _)) = line.split_once(" [") {
+            list = Some((key.trim().to_string(), Vec::new()));
+        } else if let Some((key, v)) = line.split_once('=') {
+            info.config.insert(key.trim().to_string(), v.trim().trim_matches('"').to_string());
+        } else {
+            info.config.insert(line.to_string(), "yes".to_string());
+        }
+    }
+    if let Some((key, items)) = list {
+        info.config.insert(key, items.join(","));
+    }
+}
+
+fn apply(info: &mut PjlInfo, category: &str, lines: &[String]) {
+    match category {
+        STATUS => apply_status(info, lines),
+        PAGECOUNT => apply_pagecount(info, lines),
+        MEMORY => apply_memory(info, lines),
+        CONFIG => apply_config(info, lines),
+        _ => {}
+    }
+}
+
+/// 在一个作业里查询 CONFIG、STATUS、PAGECOUNT、MEMORY，四项都没有结果时返回 None
+pub async fn info(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<PjlInfo> {
+    let raw = query(source, addr, timeout_ms, &[CONFIG, STATUS, PAGECOUNT, MEMORY]).await?;
+    let sections = sections(&raw);
+    let mut info = PjlInfo::default();
+    for (category, lines) in &sections {
+        apply(&mut info, category, lines);
+    }
+    (!sections.is_empty()).then_some(info)
+}
+
+impl PjlInfo {
+    /// CONFIG 中的序列号，各厂商的键名不同 (`SERIAL NUMBER`、`SERIALNUMBER` 等)
+    pub fn serial(&self) -> Option<String> {
+        self.config_value("SERIAL")
+    }
+
+    /// CONFIG 中的固件版本 (`FIRMWARE VERSION`、`FIRMWARE` 等)
+    pub fn firmware(&self) -> Option<String> {
+        self.config_value("FIRMWARE")
+    }
+
+    fn config_value(&self, key: &str) -> Option<String> {
+        self.config.iter().find(|(k, v)| k.contains(key) && !v.is_empty()).map(|(_, v)| v.clone())
+    }
+
+    /// `10001 ready "Ready"` 形式的状态摘要
+    pub fn status_text(&self) -> Option<String> {
+        let code = self.status_code?;
+        Some(match &self.display {
+            Some(display) => format!("{} {} \"{}\"", code, describe(code), display),
+            None => format!("{} {}", code, describe(code)),
+        })
+    }
+}
+
+fn parse_section(raw: &[u8], category: &str) -> Option<String> {
+    let sections = sections(raw);
+    let lines = sections.get(category)?;
+    let mut info = PjlInfo::default();
+    apply(&mut info, category, lines);
+    match category {
+        STATUS => info.status_text(),
+        PAGECOUNT => info.page_count.map(|n| format!("{} pages", n)),
+        MEMORY => info.memory_total.map(|n| format!("{} bytes total, {} largest free", n, info.memory_largest.unwrap_or_default())),
+        _ => (!info.config.is_empty()).then(|| info.config.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("; ")),
+    }
+}
+
+fn parse_config(raw: &[u8]) -> Option<String> {
+    parse_section(raw, CONFIG)
+}
+
+fn parse_status(raw: &[u8]) -> Option<String> {
+    parse_section(raw, STATUS)
+}
+
+fn parse_pagecount(raw: &[u8]) -> Option<String> {
+    parse_section(raw, PAGECOUNT)
+}
+
+fn parse_memory(raw: &[u8]) -> Option<String> {
+    parse_section(raw, MEMORY)
+}
+
+/// `probe --full` 的 PJL 明细，每类 INFO 单独发一次，保留各自的原始响应
+pub async fn probe_outcomes(source: Source, addr: SocketAddr, timeout_ms: u64) -> Vec<ProbeOutcome> {
+    vec![
+        ProbeOutcome::new("PJL INFO CONFIG", addr.port(), query(source, addr, timeout_ms, &[CONFIG]).await, parse_config),
+        ProbeOutcome::new("PJL INFO STATUS", addr.port(), query(source, addr, timeout_ms, &[STATUS]).await, parse_status),
+        ProbeOutcome::new("PJL INFO PAGECOUNT", addr.port(), query(source, addr, timeout_ms, &[PAGECOUNT]).await, parse_pagecount),
+        ProbeOutcome::new("PJL INFO MEMORY", addr.port(), query(source, addr, timeout_ms, &[MEMORY]).await, parse_memory),
+    ]
+}
+
+/// 审计时列出的卷，大多数 HP 打印机的 RAM / 磁盘卷是 `0:`
+const FS_VOLUME: &str = "0:\\";
+const FS_COUNT: u32 = 200;
+
+/// 文件系统中的一项
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FsEntry {
+    pub name: String,
+    pub dir: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+/// `@PJL FSDIRLIST` 的结果：能否列出卷根目录，以及根目录下的各项
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FsListing {
+    pub volume: String,
+    pub browsable: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<FsEntry>,
+}
+
+impl FsListing {
+    /// `browsable (5 entries: PJL/, webServer/, ...)` 形式的摘要
+    pub fn summary(&self) -> String {
+        if !self.browsable {
+            return "not browsable".to_string();
+        }
+        let names: Vec<String> = self.entries.iter().map(|e| if e.dir { format!("{}/", e.name) } else { e.name.clone() }).collect();
+        format!("browsable ({} entries: {})", names.len(), names.join(", "))
+    }
+}
+
+/// 每项一行：`名称 TYPE=DIR` 或 `名称 TYPE=FILE SIZE=1234`；卷不存在或禁止访问时回 `FILEERROR=<n>`
+fn parse_fsdirlist(raw: &[u8]) -> Option<FsListing> {
+    let text = String::from_utf8_lossy(raw);
+    if !text.contains("FSDIRLIST") {
+        return None;
+    }
+    let mut listing = FsListing { volume: FS_VOLUME.to_string(), browsable: false, entries: Vec::new() };
+    for line in text.lines().map(|l| l.trim_matches(|c: char| c.is_whitespace() || c == '\x0c' || c == '\x1b')) {
+        if line.starts_with("@PJL") || line.is_empty() {
+            continue;
+        }
+        if line.contains("FILEERROR") {
+            return Some(listing);
+        }
+        let Some((name, attrs)) = line.split_once(" TYPE=") else { continue };
+        listing.browsable = true;
+        if name == "." || name == ".." {
+            continue;
+        }
+        let size = attrs.split_whitespace().find_map(|a| a.strip_prefix("SIZE=")).and_then(|s| s.parse().ok());
+        listing.entries.push(FsEntry { name: name.to_string(), dir: attrs.starts_with("DIR"), size });
+    }
+    Some(listing)
+}
+
+/// 发送 `@PJL FSDIRLIST` 列出卷根目录。能列出说明任何人都能通过 9100 读写打印机上的文件，
+/// 是打印机安全审计的常规检查项。不支持 PJL 文件系统命令的打印机不回应，返回 None
+pub async fn fs_listing(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<FsListing> {
+    let mut stream = timeout(Duration::from_millis(timeout_ms), probes::connect(source, addr)).await.ok()?.ok()?;
+    let cmd = format!("{}@PJL\r\n@PJL FSDIRLIST NAME=\"{}\" ENTRY=1 COUNT={}\r\n{}", UEL, FS_VOLUME, FS_COUNT, UEL);
+    stream.write_all(cmd.as_bytes()).await.ok()?;
+    let mut raw = Vec::new();
+    let mut buffer = [0u8; 4096];
+    while let Ok(Ok(n)) = timeout(Duration::from_millis(1500), stream.read(&mut buffer)).await {
+        if n == 0 {
+            break;
+        }
+        raw.extend(&buffer[..n]);
+        if raw.contains(&FF) {
+            break;
+        }
+    }
+    parse_fsdirlist(&raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// LaserJet 对一个作业里四条 INFO 的回复，每段以换页符结束
+    const REPLY: &[u8] = b"@PJL INFO CONFIG\r\nIN TRAYS [3 ENUMERATED]\r\n\tINTRAY1 MP\r\n\tINTRAY2 PC\r\n\tINTRAY3 LC\r\nDUPLEX\r\nMEMORY=134217728\r\nDISPLAY LINES=2\r\n\x0c\
+@PJL INFO STATUS\r\nCODE=10001\r\nDISPLAY=\"Ready\"\r\nONLINE=TRUE\r\n\x0c\
+@PJL INFO PAGECOUNT\r\n48213\r\n\x0c\
+@PJL INFO MEMORY\r\nTOTAL=134217728\r\nLARGEST=101234688\r\n\x0c";
+
+    #[test]
+    fn config_lists_and_flags() {
+        assert_eq!(
+            parse_config(REPLY).as_deref(),
+            Some("DISPLAY LINES=2; DUPLEX=yes; IN TRAYS=INTRAY1 MP,INTRAY2 PC,INTRAY3 LC; MEMORY=134217728")
+        );
+        // 枚举列表在末尾时也要收尾
+        let raw = b"@PJL INFO CONFIG\r\nLANGUAGES [2 ENUMERATED]\r\n  PCL\r\n  POSTSCRIPT\r\n";
+        assert_eq!(parse_config(raw).as_deref(), Some("LANGUAGES=PCL,POSTSCRIPT"));
+    }
+
+    #[test]
+    fn each_category_reads_its_own_section() {
+        assert_eq!(parse_status(REPLY).as_deref(), Some("10001 ready \"Ready\""));
+        assert_eq!(parse_pagecount(REPLY).as_deref(), Some("48213 pages"));
+        assert_eq!(parse_memory(REPLY).as_deref(), Some("134217728 bytes total, 101234688 largest free"));
+    }
+
+    #[test]
+    fn status_without_display_text() {
+        let raw = b"@PJL INFO status\r\nCODE=41213\r\nDISPLAY=\"\"\r\nONLINE=FALSE\r\n\x0c";
+        let mut info = PjlInfo::default();
+        apply(&mut info, STATUS, &sections(raw)[STATUS]);
+        assert_eq!(info.online, Some(false));
+        assert_eq!(info.display, None);
+        assert_eq!(info.status_text().as_deref(), Some("41213 paper out"));
+    }
+
+    #[test]
+    fn pagecount_accepts_key_value_form() {
+        assert_eq!(parse_pagecount(b"@PJL INFO PAGECOUNT\r\nPAGECOUNT=12345\r\n\x0c").as_deref(), Some("12345 pages"));
+        assert_eq!(parse_pagecount(b"@PJL INFO PAGECOUNT\r\n?\r\n\x0c"), None);
+        // 没有回显 INFO 行的数据不归到任何类别
+        assert_eq!(parse_pagecount(b"48213\r\n\x0c"), None);
+    }
+
+    #[test]
+    fn codes_group_by_thousands() {
+        assert_eq!(describe(10006), "toner low");
+        assert_eq!(describe(10500), "informational");
+        assert_eq!(describe(11101), "tray empty (background)");
+        assert_eq!(describe(42202), "paper jam");
+        assert_eq!(describe(44001), "paper jam");
+        assert_eq!(describe(43000), "paper handling error");
+        assert_eq!(describe(50000), "hardware error");
+        assert_eq!(describe(20000), "unknown");
+    }
+}
@@ -0,0 +1,108 @@
+use crate::cli::{PjlMonitorArgs, exit_error};
+use crate::probes::{self, Source};
+use crate::{output, pjl};
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::net::SocketAddr;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::timeout;
+
+/// 打开设备状态 (含面板提示) 和作业开始 / 结束的主动上报
+const ENABLE: &[u8] = b"\x1b%-12345X@PJL\r\n@PJL USTATUS DEVICE=VERBOSE\r\n@PJL USTATUS JOB=ON\r\n";
+/// 连接断开后重连的等待时间
+const RECONNECT_SECS: u64 = 5;
+
+/// 一条 `@PJL USTATUS` 主动上报
+#[derive(Serialize, Debug)]
+struct StatusEvent {
+    timestamp: String,
+    /// `DEVICE`、`JOB`、`PAGE` 或 `TIMED`
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<u32>,
+    /// 状态码的含义，见 `pjl::describe`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'static str>,
+    /// 其余字段原样保留 (`DISPLAY`、`ONLINE`、作业的 `START` / `END`、`NAME`、`PAGES` 等)
+    fields: BTreeMap<String, String>,
+}
+
+/// 每条上报以 `@PJL USTATUS <类别>` 开头、换页符结束；没有 `=` 的行 (`START`、`END`) 记为值为空的字段
+fn parse_event(message: &str) -> Option<StatusEvent> {
+    let mut lines = message.lines().map(|l| l.trim_matches(|c: char| c.is_whitespace() || c == '\x1b')).filter(|l| !l.is_empty());
+    let kind = lines.find_map(|l| l.strip_prefix("@PJL USTATUS "))?.trim().to_string();
+    let mut fields: BTreeMap<String, String> = lines
+        .map(|l| match l.split_once('=') {
+            Some((k, v)) => (k.trim().to_string(), v.trim().trim_matches('"').to_string()),
+            None => (l.to_string(), String::new()),
+        })
+        .collect();
+    let code = fields.remove("CODE").and_then(|c| c.parse().ok());
+    let timestamp = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    Some(StatusEvent { timestamp, kind, code, description: code.map(pjl::describe), fields })
+}
+
+fn write_event(out: &mut dyn Write, json: bool, event: &StatusEvent) -> io::Result<()> {
+    if json {
+        serde_json::to_writer(&mut *out, event)?;
+        writeln!(out)?;
+    } else {
+        let fields = event.fields.iter().map(|(k, v)| if v.is_empty() { k.clone() } else { format!("{}={}", k, v) }).collect::<Vec<_>>().join(" ");
+        match (event.code, event.description) {
+            (Some(code), Some(description)) => {
+                writeln!(out, "[{}] {} {} {} {}", event.timestamp, event.kind.bold(), code, description.yellow(), fields)?
+            }
+            _ => writeln!(out, "[{}] {} {}", event.timestamp, event.kind.bold(), fields)?,
+        }
+    }
+    out.flush()
+}
+
+/// 保持一个连接，读出所有主动上报直到连接关闭
+async fn listen(addr: SocketAddr, timeout_ms: u64, out: &mut dyn Write, json: bool) -> io::Result<()> {
+    let mut stream = match timeout(Duration::from_millis(timeout_ms), probes::connect(Source::default(), addr)).await {
+        Ok(stream) => stream?,
+        Err(_) => return Err(io::ErrorKind::TimedOut.into()),
+    };
+    stream.write_all(ENABLE).await?;
+    let mut pending = Vec::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut buffer).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        pending.extend(&buffer[..n]);
+        while let Some(end) = pending.iter().position(|&b| b == 0x0c) {
+            let message: Vec<u8> = pending.drain(..=end).collect();
+            if let Some(event) = parse_event(&String::from_utf8_lossy(&message))
+                && let Err(e) = write_event(out, json, &event)
+            {
+                eprintln!("{}", tr!(EventWriteFailed, e));
+            }
+        }
+    }
+}
+
+/// 打开 USTATUS 后持续输出打印机的主动上报 (卡纸、缺纸、作业完成等)，直到被中断。
+/// 打印机空闲一段时间会断开连接，断开后自动重连
+pub async fn run_monitor_pjl(args: PjlMonitorArgs) -> ExitCode {
+    let mut out = match output::open_output(args.output.as_deref()) {
+        Ok(out) => out,
+        Err(e) => { eprintln!("{}", tr!(CreateOutputFailed, e)); return exit_error(); }
+    };
+
+    let addr = SocketAddr::new(args.ip, args.port);
+    status!("{}", tr!(PjlMonitorStarted, "👀".green(), addr));
+    loop {
+        match listen(addr, args.timeout_ms, &mut out, args.json).await {
+            Ok(()) => status!("{}", tr!(PjlMonitorReconnect, "🔌".dimmed(), RECONNECT_SECS)),
+            Err(e) => eprintln!("{}", tr!(ConnectFailed, addr, e)),
+        }
+        tokio::time::sleep(Duration::from_secs(RECONNECT_SECS)).await;
+    }
+}
@@ -0,0 +1,476 @@
+use crate::probes::ProbeOutcome;
+use crate::snmp::{self, Reading, Scalars};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+
+/// Host Resources MIB (RFC 2790) 中第一个设备的 hrDeviceStatus 和 hrPrinterStatus
+const OID_DEVICE_STATUS: &[u64] = &[1, 3, 6, 1, 2, 1, 25, 3, 2, 1, 5, 1];
+const OID_PRINTER_STATUS: &[u64] = &[1, 3, 6, 1, 2, 1, 25, 3, 5, 1, 1, 1];
+/// hrPrinterDetectedErrorState：按位表示的错误状况，第一个字节的最高位是第 0 位
+const OID_DETECTED_ERROR_STATE: &[u64] = &[1, 3, 6, 1, 2, 1, 25, 3, 5, 1, 2, 1];
+
+/// hrPrinterDetectedErrorState 各位的名称，按位序排列
+const ERROR_STATE_FLAGS: &[&str] = &[
+    "low paper",
+    "no paper",
+    "low toner",
+    "no toner",
+    "door open",
+    "jammed",
+    "offline",
+    "service requested",
+    "input tray missing",
+    "output tray missing",
+    "marker supply missing",
+    "output near full",
+    "output full",
+    "input tray empty",
+    "overdue preventive maintenance",
+];
+
+/// prtGeneralTable 中第一台设备的 prtGeneralSerialNumber
+const OID_GENERAL_SERIAL: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 5, 1, 1, 17, 1];
+/// prtGeneralPrinterName，管理员在设备上设置的名称
+const OID_GENERAL_PRINTER_NAME: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 5, 1, 1, 16, 1];
+/// prtConsoleDisplayBufferText 列，行索引为 `hrDeviceIndex.行号`
+const OID_CONSOLE_DISPLAY_TEXT: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 16, 5, 1, 2];
+
+/// 第一个打印引擎的 prtMarkerProcessColorants (可用的基本色数量) 和 prtMediaPathType 列
+const OID_PROCESS_COLORANTS: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 10, 2, 1, 6, 1, 1];
+const OID_MEDIA_PATH_TYPE: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 13, 4, 1, 9];
+/// prtMediaPathType 中的 longEdgeBindingDuplex / shortEdgeBindingDuplex
+const DUPLEX_PATHS: &[u64] = &[4, 5];
+
+/// 随 `snmp::scalars` 一起读取的标量
+pub const SCALARS: &[&[u64]] = &[
+    OID_DEVICE_STATUS,
+    OID_PRINTER_STATUS,
+    OID_DETECTED_ERROR_STATE,
+    OID_GENERAL_SERIAL,
+    OID_GENERAL_PRINTER_NAME,
+    OID_PROCESS_COLORANTS,
+];
+
+/// prtInputTable 的各列，行索引为 `hrDeviceIndex.prtInputIndex`
+const OID_INPUT_DIM_UNIT: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 8, 2, 1, 3];
+const OID_INPUT_FEED_DIM: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 8, 2, 1, 4];
+const OID_INPUT_XFEED_DIM: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 8, 2, 1, 5];
+const OID_INPUT_MAX_CAPACITY: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 8, 2, 1, 9];
+const OID_INPUT_CURRENT_LEVEL: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 8, 2, 1, 10];
+const OID_INPUT_MEDIA_NAME: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 8, 2, 1, 12];
+const OID_INPUT_NAME: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 8, 2, 1, 13];
+const OID_INPUT_DESCRIPTION: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 8, 2, 1, 18];
+/// prtInputDimUnit 中的 tenThousandthsOfInches / micrometers
+const DIM_TEN_THOUSANDTHS_OF_INCHES: u64 = 3;
+const DIM_MICROMETERS: u64 = 4;
+
+/// prtAlertTable (RFC 3805) 的各列，行索引为 `hrDeviceIndex.prtAlertIndex`
+const OID_ALERT_SEVERITY: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 18, 1, 1, 2];
+const OID_ALERT_GROUP: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 18, 1, 1, 4];
+const OID_ALERT_GROUP_INDEX: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 18, 1, 1, 5];
+const OID_ALERT_CODE: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 18, 1, 1, 7];
+const OID_ALERT_DESCRIPTION: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 18, 1, 1, 8];
+
+/// 新增告警时发出的 printerV2AlertTrap；v1 的 printerV1AlertTrap 换算后也是这个 OID
+pub const OID_ALERT_TRAP: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 18, 2, 0, 1];
+
+/// prtAlertSeverityLevel 中的 warningBinaryChangeEvent：盖板关闭、开机之类的一次性事件，不是持续的状况
+const SEVERITY_BINARY_CHANGE: u64 = 5;
+
+/// 一条活动告警
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Alert {
+    /// `critical`、`warning` 或 `other`
+    pub severity: String,
+    /// prtAlertCode，见 RFC 3805 的 PrtAlertCodeTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<u64>,
+    /// 可读的描述，如 `tray 2 empty`、`replace toner`；无法解码的告警码用设备自带的描述
+    pub description: String,
+}
+
+/// 一个进纸盒
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Tray {
+    /// prtInputName，没有时用描述或 `tray <序号>`
+    pub name: String,
+    /// 设定的纸张，如 `A4`、`LETTER`；没有名称时按尺寸写成 `210x297mm` / `8.5x11in`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media: Option<String>,
+    /// 当前张数；设备只报告“还有纸”或未知时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<u64>,
+}
+
+impl Tray {
+    pub fn is_empty(&self) -> bool {
+        self.level == Some(0)
+    }
+}
+
+fn severity_name(level: u64) -> &'static str {
+    match level {
+        3 => "critical",
+        4 => "warning",
+        _ => "other",
+    }
+}
+
+/// 告警所属部件 (prtAlertGroup) 的名称，和部件序号一起组成 `tray 2` 之类的主语
+fn group_name(group: u64) -> Option<&'static str> {
+    Some(match group {
+        6 => "cover",
+        8 => "tray",
+        9 => "output bin",
+        10 => "marker",
+        11 => "supply",
+        13 => "media path",
+        30 => "finisher",
+        _ => return None,
+    })
+}
+
+/// 告警码的含义。`true` 表示描写的是某个部件的状况，前面要加上部件名
+fn code_text(code: u64) -> Option<(&'static str, bool)> {
+    Some(match code {
+        3 => ("cover open", false),
+        5 => ("interlock open", false),
+        8 => ("paper jam", false),
+        9 => ("missing", true),
+        10 => ("life almost over", true),
+        11 => ("life over", true),
+        12 => ("almost empty", true),
+        13 => ("empty", true),
+        14 => ("almost full", true),
+        15 => ("full", true),
+        16 => ("near limit", true),
+        17 => ("at limit", true),
+        18 => ("opened", true),
+        22 => ("offline", true),
+        29 => ("recoverable failure", true),
+        30 => ("unrecoverable failure", true),
+        33 => ("motor failure", true),
+        34 => ("memory exhausted", true),
+        35 => ("under temperature", true),
+        36 => ("over temperature", true),
+        501 => ("door open", false),
+        801 | 901 | 1301 => ("missing", true),
+        807 => ("low", true),
+        808 => ("empty", true),
+        902 | 1302 => ("almost full", true),
+        903 | 1303 => ("full", true),
+        1001 => ("fuser under temperature", false),
+        1002 => ("fuser over temperature", false),
+        1101 => ("replace toner", false),
+        1102 => ("ink empty", false),
+        1103 => ("ribbon empty", false),
+        1104 => ("toner low", false),
+        1105 => ("ink low", false),
+        1106 => ("ribbon low", false),
+        1107 => ("waste toner almost full", false),
+        1108 => ("waste ink almost full", false),
+        1109 => ("waste toner full", false),
+        1110 => ("waste ink full", false),
+        1111 => ("drum life almost over", false),
+        1112 => ("replace drum", false),
+        1113 => ("developer almost empty", false),
+        1114 => ("developer empty", false),
+        1115 => ("toner cartridge missing", false),
+        _ => return None,
+    })
+}
+
+fn describe(code: Option<u64>, group: Option<u64>, index: Option<u64>, own: Option<String>) -> String {
+    match code.and_then(code_text) {
+        Some((text, true)) => match (group.and_then(group_name), index.filter(|&i| i > 0)) {
+            (Some(part), Some(index)) => format!("{} {} {}", part, index, text),
+            (Some(part), None) => format!("{} {}", part, text),
+            (None, _) => text.to_string(),
+        },
+        Some((text, false)) => text.to_string(),
+        None => own.or_else(|| code.map(|c| format!("alert {}", c))).unwrap_or_else(|| "unknown alert".to_string()),
+    }
+}
+
+/// hrDeviceStatus 为 down(5) 或 testing(4) 时以它为准，否则按 hrPrinterStatus 区分空闲、打印中和预热中
+fn status_text(device: Option<u64>, printer: Option<u64>) -> Option<&'static str> {
+    match (device, printer) {
+        (Some(5), _) => Some("down"),
+        (Some(4), _) => Some("testing"),
+        (_, Some(3)) => Some("idle"),
+        (_, Some(4)) => Some("printing"),
+        (_, Some(5)) => Some("warming up"),
+        _ => None,
+    }
+}
+
+/// 把错误状态位图解码为置位的状况名称，未定义的位忽略
+fn decode_error_state(bits: &[u8]) -> Vec<String> {
+    ERROR_STATE_FLAGS
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| bits.get(i / 8).is_some_and(|b| b & (0x80 >> (i % 8)) != 0))
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+/// Host Resources MIB 的两个状态值和错误状态位图
+struct HostStatus {
+    device: Option<u64>,
+    printer: Option<u64>,
+    errors: Option<Vec<u8>>,
+}
+
+fn host_status(values: &Scalars) -> HostStatus {
+    HostStatus {
+        device: values.number(OID_DEVICE_STATUS),
+        printer: values.number(OID_PRINTER_STATUS),
+        errors: values.get(OID_DETECTED_ERROR_STATE).and_then(Reading::bytes).map(<[u8]>::to_vec),
+    }
+}
+
+/// 设备当前的状态 (`idle`、`printing`、`warming up`、`down` 或 `testing`)，
+/// 以及 hrPrinterDetectedErrorState 中置位的状况 (`no paper`、`jammed`、`door open` 等)
+pub fn device_status(values: &Scalars) -> (Option<String>, Vec<String>) {
+    let status = host_status(values);
+    let errors = status.errors.as_deref().map(decode_error_state).unwrap_or_default();
+    (status_text(status.device, status.printer).map(str::to_string), errors)
+}
+
+/// Printer MIB 中的序列号，没有私有 MIB 的品牌也大多填写
+pub fn serial_number(values: &Scalars) -> Option<String> {
+    values.text(OID_GENERAL_SERIAL)
+}
+
+/// 管理员设置的打印机名称，未设置时为 None
+pub fn printer_name(values: &Scalars) -> Option<String> {
+    values.text(OID_GENERAL_PRINTER_NAME)
+}
+
+/// 第一台设备控制面板上当前显示的文字，多行用空格连起来，如 `LOAD TRAY 2 LETTER`
+pub async fn console_display(snmp: &snmp::Client, ip: IpAddr) -> Option<String> {
+    let columns = snmp::walk_columns(snmp, ip, &[OID_CONSOLE_DISPLAY_TEXT]).await?;
+    let lines: Vec<String> = columns
+        .first()?
+        .iter()
+        .filter(|(row, _)| row.first() == Some(&1))
+        .filter_map(|(_, line)| line.text())
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect();
+    (!lines.is_empty()).then(|| lines.join(" "))
+}
+
+/// 是否彩色 (`values` 中的基本色多于一种) 和是否有双面打印的纸路
+pub async fn capabilities(snmp: &snmp::Client, ip: IpAddr, values: &Scalars) -> (bool, bool) {
+    let colorants = values.number(OID_PROCESS_COLORANTS);
+    let paths = snmp::walk_columns(snmp, ip, &[OID_MEDIA_PATH_TYPE]).await.unwrap_or_default();
+    let duplex = paths.iter().flat_map(|column| column.values()).any(|t| t.number().is_some_and(|t| DUPLEX_PATHS.contains(&t)));
+    (colorants.is_some_and(|n| n > 1), duplex)
+}
+
+/// 纸张尺寸，横向 (xfeed) 在前
+fn media_size(unit: Option<u64>, feed: Option<u64>, xfeed: Option<u64>) -> Option<String> {
+    let (feed, xfeed) = (feed.filter(|&d| d > 0)?, xfeed.filter(|&d| d > 0)?);
+    match unit? {
+        DIM_MICROMETERS => Some(format!("{}x{}mm", (xfeed + 500) / 1000, (feed + 500) / 1000)),
+        DIM_TEN_THOUSANDTHS_OF_INCHES => Some(format!("{}x{}in", xfeed as f64 / 10000.0, feed as f64 / 10000.0)),
+        _ => None,
+    }
+}
+
+/// 遍历 prtInputTable，列出各进纸盒的纸张和余量。设备不响应时返回空列表
+pub async fn trays(snmp: &snmp::Client, ip: IpAddr) -> Vec<Tray> {
+    let columns = [
+        OID_INPUT_NAME,
+        OID_INPUT_DESCRIPTION,
+        OID_INPUT_MEDIA_NAME,
+        OID_INPUT_DIM_UNIT,
+        OID_INPUT_FEED_DIM,
+        OID_INPUT_XFEED_DIM,
+        OID_INPUT_MAX_CAPACITY,
+        OID_INPUT_CURRENT_LEVEL,
+    ];
+    let Some(columns) = snmp::walk_columns(snmp, ip, &columns).await else { return Vec::new() };
+    let [names, descriptions, media, units, feeds, xfeeds, capacities, levels] = &columns[..] else { return Vec::new() };
+    // 有的设备不填名称，以有任何一列的行为准
+    let rows: BTreeSet<&Vec<u64>> = columns.iter().flat_map(|c| c.keys()).collect();
+    rows.into_iter()
+        .map(|row| {
+            let number = |column: &BTreeMap<Vec<u64>, Reading>| column.get(row).and_then(Reading::number);
+            let name = names
+                .get(row)
+                .or_else(|| descriptions.get(row))
+                .and_then(Reading::text)
+                .unwrap_or_else(|| format!("tray {}", row.last().copied().unwrap_or_default()));
+            let media = media.get(row).and_then(Reading::text).or_else(|| media_size(number(units), number(feeds), number(xfeeds)));
+            Tray { name, media, level: number(levels), capacity: number(capacities).filter(|&c| c > 0) }
+        })
+        .collect()
+}
+
+/// 文本输出和 CSV 用的一行摘要：`Tray 1 (A4) 150/250, Tray 2 (LETTER) empty`
+pub fn tray_summary(trays: &[Tray]) -> String {
+    trays
+        .iter()
+        .map(|t| {
+            let mut text = t.name.clone();
+            if let Some(media) = &t.media {
+                text.push_str(&format!(" ({})", media));
+            }
+            match (t.level, t.capacity) {
+                (Some(0), _) => text.push_str(" empty"),
+                (Some(level), Some(capacity)) => text.push_str(&format!(" {}/{}", level, capacity)),
+                (Some(level), None) => text.push_str(&format!(" {}", level)),
+                _ => {}
+            }
+            text
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 读取 prtAlertTable 中的活动告警 (缺纸、卡纸、更换碳粉等)，跳过一次性的状态变化事件。
+/// 设备不响应或没有告警时返回空列表
+pub async fn alerts(snmp: &snmp::Client, ip: IpAddr) -> Vec<Alert> {
+    let columns = [OID_ALERT_SEVERITY, OID_ALERT_GROUP, OID_ALERT_GROUP_INDEX, OID_ALERT_CODE, OID_ALERT_DESCRIPTION];
+    let Some(columns) = snmp::walk_columns(snmp, ip, &columns).await else { return Vec::new() };
+    let [severities, groups, indexes, codes, descriptions] = &columns[..] else { return Vec::new() };
+    severities
+        .iter()
+        .filter_map(|(row, severity)| {
+            let severity = severity.number()?;
+            if severity == SEVERITY_BINARY_CHANGE {
+                return None;
+            }
+            let code = codes.get(row).and_then(Reading::number);
+            let description = describe(
+                code,
+                groups.get(row).and_then(Reading::number),
+                indexes.get(row).and_then(Reading::number),
+                descriptions.get(row).and_then(Reading::text),
+            );
+            Some(Alert { severity: severity_name(severity).to_string(), code, description })
+        })
+        .collect()
+}
+
+/// 解码告警 trap 携带的 prtAlertTable 各列 (级别、部件、告警码等，行索引为新告警的那一行)。
+/// 没有级别时返回 None；状态变化事件 (盖板关闭等) 也保留，trap 本身就是一次性的通知
+pub fn trap_alert(variables: &[(Vec<u64>, Reading)]) -> Option<Alert> {
+    let column = |root: &[u64]| variables.iter().find(|(name, _)| name.starts_with(root)).map(|(_, value)| value);
+    let severity = column(OID_ALERT_SEVERITY)?.number()?;
+    let code = column(OID_ALERT_CODE).and_then(Reading::number);
+    let description = describe(
+        code,
+        column(OID_ALERT_GROUP).and_then(Reading::number),
+        column(OID_ALERT_GROUP_INDEX).and_then(Reading::number),
+        column(OID_ALERT_DESCRIPTION).and_then(Reading::text),
+    );
+    Some(Alert { severity: severity_name(severity).to_string(), code, description })
+}
+
+/// 文本输出和 CSV 用的一行摘要：`tray 2 empty, toner low`
+pub fn summary(alerts: &[Alert]) -> String {
+    alerts.iter().map(|a| a.description.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+/// `probe` 中列出的 Host Resources 状态，原始数据为各 OID 的取值 (错误状态位图为十六进制)
+pub fn status_outcome(snmp: &snmp::Client, values: &Scalars) -> ProbeOutcome {
+    let status = host_status(values);
+    let show = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string());
+    let bits = status.errors.as_deref().map(|b| b.iter().map(|b| format!("{:02x}", b)).collect::<String>()).unwrap_or_else(|| "-".to_string());
+    let raw = format!("hrDeviceStatus={} hrPrinterStatus={} hrPrinterDetectedErrorState={}", show(status.device), show(status.printer), bits);
+    let mut parts: Vec<String> = status_text(status.device, status.printer).map(str::to_string).into_iter().collect();
+    parts.extend(status.errors.as_deref().map(decode_error_state).unwrap_or_default());
+    ProbeOutcome {
+        source: "Host Resources status".to_string(),
+        port: snmp.port(),
+        transport: "udp",
+        raw: Some(raw),
+        parsed: (!parts.is_empty()).then(|| parts.join(", ")),
+    }
+}
+
+/// `probe` 中列出的告警表，原始数据为每条告警的级别、告警码和解码结果
+pub async fn alert_outcome(snmp: &snmp::Client, ip: IpAddr) -> ProbeOutcome {
+    let alerts = alerts(snmp, ip).await;
+    let raw = alerts
+        .iter()
+        .map(|a| format!("{} {} {}", a.severity, a.code.map(|c| c.to_string()).unwrap_or_default(), a.description))
+        .collect::<Vec<_>>()
+        .join("\n");
+    ProbeOutcome {
+        source: "Printer MIB alerts".to_string(),
+        port: snmp.port(),
+        transport: "udp",
+        parsed: (!alerts.is_empty()).then(|| summary(&alerts)),
+        raw: (!alerts.is_empty()).then_some(raw),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_alerts_name_the_part() {
+        // 8 = tray，13 = empty
+        assert_eq!(describe(Some(13), Some(8), Some(2), None), "tray 2 empty");
+        assert_eq!(describe(Some(808), Some(8), Some(1), Some("Tray 1 Empty".into())), "tray 1 empty");
+        assert_eq!(describe(Some(1303), Some(9), Some(1), None), "output bin 1 full");
+        // 部件序号为 0 或未知时不写序号
+        assert_eq!(describe(Some(12), Some(11), Some(0), None), "supply almost empty");
+        assert_eq!(describe(Some(12), Some(11), None, None), "supply almost empty");
+    }
+
+    #[test]
+    fn unknown_group_keeps_bare_condition() {
+        assert_eq!(describe(Some(22), Some(99), Some(1), None), "offline");
+        assert_eq!(describe(Some(22), None, Some(1), None), "offline");
+    }
+
+    #[test]
+    fn self_describing_codes_ignore_the_part() {
+        assert_eq!(describe(Some(8), Some(13), Some(1), None), "paper jam");
+        assert_eq!(describe(Some(1104), Some(11), Some(1), Some("Black Cartridge Low".into())), "toner low");
+    }
+
+    #[test]
+    fn undecoded_alerts_fall_back_to_device_text() {
+        assert_eq!(describe(Some(40000), Some(8), Some(1), Some("Load Letter".into())), "Load Letter");
+        assert_eq!(describe(Some(40000), None, None, None), "alert 40000");
+        assert_eq!(describe(None, None, None, None), "unknown alert");
+    }
+
+    #[test]
+    fn severity_and_summary() {
+        assert_eq!((severity_name(3), severity_name(4), severity_name(1)), ("critical", "warning", "other"));
+        let alerts = [
+            Alert { severity: "critical".into(), code: Some(13), description: "tray 2 empty".into() },
+            Alert { severity: "warning".into(), code: Some(1104), description: "toner low".into() },
+        ];
+        assert_eq!(summary(&alerts), "tray 2 empty, toner low");
+        assert_eq!(summary(&[]), "");
+    }
+
+    #[test]
+    fn error_state_bits_count_from_msb() {
+        // 0x40 = 第 1 位 no paper，0x08 = 第 4 位 door open
+        assert_eq!(decode_error_state(&[0x48]), ["no paper", "door open"]);
+        assert_eq!(decode_error_state(&[0x80, 0x80]), ["low paper", "input tray missing"]);
+        assert_eq!(decode_error_state(&[0x00, 0x02]), ["overdue preventive maintenance"]);
+    }
+
+    #[test]
+    fn error_state_ignores_undefined_and_missing_bytes() {
+        // 第 15 位和第三个字节没有定义
+        assert!(decode_error_state(&[0x00, 0x01, 0xff]).is_empty());
+        // 只回一个字节的设备 (RFC 2790 旧版) 只解码前 8 位
+        assert_eq!(decode_error_state(&[0x01]), ["service requested"]);
+        assert!(decode_error_state(&[]).is_empty());
+    }
+}
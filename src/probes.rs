@@ -0,0 +1,1080 @@
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpSocket, TcpStream, UdpSocket};
+use tokio::time::timeout;
+
+pub const PRINTER_PORT: u16 = 9100;
+/// Canon BJNP 打印端口，扫描仪 (MFNP) 用 8612
+pub const BJNP_PORT: u16 = 8611;
+pub const MFNP_PORT: u16 = 8612;
+/// Epson 网络控制端口 (ENPC，EpsonNet Config 使用)
+pub const ENPC_PORT: u16 = 3289;
+/// 行式打印机守护进程 (LPD，RFC 1179)
+pub const LPD_PORT: u16 = 515;
+/// 外置 JetDirect 多口打印服务器的第二、三个端口，各接一台打印机
+pub const JETDIRECT_PORTS: [u16; 2] = [9101, 9102];
+/// 打印机内置的 FTP 打印服务
+pub const FTP_PORT: u16 = 21;
+/// Star 网络接口的状态端口 (StarPRNT / StarIO 在这里读取状态，打印数据仍走 9100)
+pub const STAR_STATUS_PORT: u16 = 9101;
+
+/// 结果中列出开放情况的打印相关 TCP 端口 (SNMP 的 161 是 UDP，另外判断)
+pub const PRINTING_PORTS: [u16; 5] = [PRINTER_PORT, crate::ipp::IPP_PORT, LPD_PORT, crate::http::HTTP_PORT, crate::http::HTTPS_PORT];
+
+/// 主机上开放的一个打印相关端口
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PortState {
+    pub port: u16,
+    /// 在该端口上回答的协议；端口开放但没有协议认出时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+}
+
+impl PortState {
+    /// 传输层协议：SNMP、BJNP 和 ENPC 走 UDP，其余都是 TCP
+    pub fn transport(&self) -> &'static str {
+        match self.protocol.as_deref() {
+            Some("SNMP" | "SNMPv3" | "BJNP" | "ENPC") => "udp",
+            _ => "tcp",
+        }
+    }
+}
+
+/// 有专门协议的服务端口，不往里面写打印语言指令
+const SERVICE_PORTS: &[u16] = &[LPD_PORT, crate::http::HTTP_PORT, crate::http::HTTPS_PORT, crate::ipp::IPP_PORT];
+
+/// 探测适用的端口
+#[derive(Clone, Copy, Debug)]
+pub enum Ports {
+    /// 往扫描到的端口写打印语言指令：除服务端口 (515、80、443、631) 以外的任何端口，
+    /// 打印端口改到非标准端口时也能识别
+    Raw,
+    /// 只在扫描到这些端口时尝试
+    Only(&'static [u16]),
+    /// 与扫描到的端口无关，直接查询主机上的固定端口 (UDP 协议、网页)
+    Host,
+}
+
+impl Ports {
+    pub fn applies(self, port: u16) -> bool {
+        match self {
+            Ports::Raw => !SERVICE_PORTS.contains(&port),
+            Ports::Only(ports) => ports.contains(&port),
+            Ports::Host => true,
+        }
+    }
+}
+
+/// 一个探测 (`Probe::run`) 认出的设备
+#[derive(Clone, Debug, Default)]
+pub struct ProbeResult {
+    pub model: String,
+    /// 协议顺带返回的设备状态
+    pub status: Option<String>,
+    /// 协议顺带返回的固件版本
+    pub firmware: Option<String>,
+    /// 识别过程中已经取到的 HTTPS 证书
+    pub certificate: Option<crate::http::Certificate>,
+    /// 识别过程中已经取到的 IPP 属性
+    pub ipp: Option<crate::ipp::Capabilities>,
+}
+
+impl ProbeResult {
+    pub fn model(model: String) -> Self {
+        ProbeResult { model, ..Default::default() }
+    }
+
+    fn with_status((model, status): (String, Option<String>)) -> Self {
+        ProbeResult { model, status, ..Default::default() }
+    }
+}
+
+/// 一种识别协议。扫描时按 `registry()` 的顺序在适用的端口上逐个尝试，
+/// 新增厂商协议只需实现它并用 `register_probe` 登记，不必改动扫描流程
+pub trait Probe: Sync {
+    /// 识别来源，写入结果的 `source`
+    fn name(&self) -> &'static str;
+
+    fn ports(&self) -> Ports;
+
+    /// 兜底的探测只在其他探测都没有认出设备时尝试
+    fn fallback(&self) -> bool {
+        false
+    }
+
+    /// 往端口写 ESC 开头的二进制查询。普通打印机可能把它当成打印数据吐出乱码页，
+    /// 排在 PJL 和 ENPC 之后，二者已认出设备时 (包括 `--all-probes`) 不再发送
+    fn escape_query(&self) -> bool {
+        false
+    }
+
+    /// 探测扫描到的主机和端口，设备不回应或认不出时返回 None。`context` 是本次扫描的源地址和 SNMP 凭据
+    fn run<'a>(&'a self, addr: SocketAddr, context: &'a Context, timeout_ms: u64) -> BoxFuture<'a, Option<ProbeResult>>;
+}
+
+/// `register_probe` 登记的探测，排在内置探测之后
+static REGISTERED: Mutex<Vec<&'static dyn Probe>> = Mutex::new(Vec::new());
+
+pub fn register_probe(probe: &'static dyn Probe) {
+    REGISTERED.lock().unwrap().push(probe);
+}
+
+/// 全部探测，按尝试顺序排列：内置探测、登记的探测，最后是兜底的探测
+pub fn registry() -> Vec<&'static dyn Probe> {
+    let mut probes: Vec<&'static dyn Probe> = BUILTIN_PROBES.to_vec();
+    probes.extend(REGISTERED.lock().unwrap().iter().copied());
+    probes.sort_by_key(|p| p.fallback());
+    probes
+}
+
+/// 各探测适用的端口，编号与下面各探测的编号一致
+pub const PJL_PORTS: Ports = Ports::Raw;
+pub const SGD_PORTS: Ports = Ports::Raw;
+pub const ZPL_PORTS: Ports = Ports::Raw;
+pub const SNMP_PORTS: Ports = Ports::Host;
+pub const BJNP_PORTS: Ports = Ports::Host;
+pub const ENPC_PORTS: Ports = Ports::Host;
+pub const BANNER_PORTS: Ports = Ports::Raw;
+pub const LPD_PORTS: Ports = Ports::Only(&[LPD_PORT]);
+pub const ESCPOS_PORTS: Ports = Ports::Raw;
+pub const STAR_PORTS: Ports = Ports::Raw;
+pub const LABEL_PORTS: Ports = Ports::Raw;
+pub const IPP_PORTS: Ports = Ports::Only(&[crate::ipp::IPP_PORT]);
+pub const HTTP_PORTS: Ports = Ports::Host;
+pub const HTTPS_PORTS: Ports = Ports::Host;
+pub const FTP_PORTS: Ports = Ports::Host;
+
+/// `--interface` 指定的网卡地址，探测连接从这些地址发出；未指定时由系统选择
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Source {
+    pub v4: Option<Ipv4Addr>,
+    pub v6: Option<Ipv6Addr>,
+}
+
+impl Source {
+    /// 发往 `ip` 时要绑定的源地址 (同一地址族)
+    pub fn addr(self, ip: IpAddr) -> Option<IpAddr> {
+        match ip {
+            IpAddr::V4(_) => self.v4.map(IpAddr::V4),
+            IpAddr::V6(_) => self.v6.map(IpAddr::V6),
+        }
+    }
+}
+
+/// 一次扫描中各探测和补充查询共用的设置：探测连接的源地址、SNMP 版本和凭据、MAC 厂商库。
+/// 按扫描参数构造 (`Context::from_args`)，沿扫描流程传下去，不放在进程级的全局变量里
+#[derive(Clone, Default)]
+pub struct Context {
+    pub(crate) source: Source,
+    pub(crate) snmp: crate::snmp::Client,
+    pub(crate) oui: crate::oui::Database,
+}
+
+impl Context {
+    /// 解析 `--interface`、SNMP 参数和 `--oui-file`
+    pub(crate) fn from_args(args: &crate::cli::TargetArgs) -> Result<Self, crate::ScanError> {
+        use crate::ScanError;
+        let snmp = crate::snmp::Client::from_args(&args.snmp).map_err(ScanError::Snmp)?;
+        let source = match &args.interface {
+            Some(name) => crate::targets::interface_source(name).map_err(ScanError::Targets)?,
+            None => Source::default(),
+        };
+        let oui = match &args.oui_file {
+            Some(path) => crate::oui::Database::load(path).map_err(ScanError::Targets)?,
+            None => crate::oui::Database::default(),
+        };
+        Ok(Context { source, snmp, oui })
+    }
+}
+
+/// 建立到打印机的 TCP 连接。指定了网卡时先绑定该网卡的地址，
+/// 避免多网卡机器上系统把探测包从别的网卡发出去
+pub async fn connect(source: Source, addr: SocketAddr) -> std::io::Result<TcpStream> {
+    let socket = match addr {
+        SocketAddr::V4(_) => TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => TcpSocket::new_v6()?,
+    };
+    if let Some(ip) = source.addr(addr.ip()) {
+        socket.bind(SocketAddr::new(ip, 0))?;
+    }
+    socket.connect(addr).await
+}
+
+pub async fn is_port_open(source: Source, ip: IpAddr, port: u16, timeout_ms: u64) -> bool {
+    let addr = SocketAddr::new(ip, port);
+    matches!(timeout(Duration::from_millis(timeout_ms), connect(source, addr)).await, Ok(Ok(_)))
+}
+
+/// 连接打印端口 (默认 9100)，发送指令 (可为空) 后读取一次响应
+///
+/// `read_ms` 是等待响应的时间，和连接超时分开控制
+async fn exchange(source: Source, addr: SocketAddr, timeout_ms: u64, cmd: &[u8], read_ms: u64) -> Option<Vec<u8>> {
+    let mut stream = timeout(Duration::from_millis(timeout_ms), connect(source, addr)).await.ok()?.ok()?;
+
+    if !cmd.is_empty() && stream.write_all(cmd).await.is_err() { return None; }
+
+    let mut buffer = [0; 1024];
+    match timeout(Duration::from_millis(read_ms), stream.read(&mut buffer)).await {
+        Ok(Ok(n)) if n > 0 => Some(buffer[..n].to_vec()),
+        _ => None,
+    }
+}
+
+/// 1. PJL 探测 (HP, Brother 等)
+async fn query_pjl(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<Vec<u8>> {
+    exchange(source, addr, timeout_ms, b"\x1B%-12345X@PJL INFO ID\r\n\x1B%-12345X", 1000).await
+}
+
+/// 设备回应了 PJL 但 ID 为空 (Brother 常见)，之后由厂商专用的查询补上型号
+pub const UNKNOWN_PJL: &str = "Unknown PJL";
+
+fn parse_pjl(raw: &[u8]) -> Option<String> {
+    let raw = String::from_utf8_lossy(raw);
+    if raw.contains("ID") {
+        let clean = raw.replace("ID=", "").replace("ID =", "").replace("\"", "").trim().to_string();
+        // 第一行通常是指令的回显 `@PJL INFO ID`，型号在它后面
+        let model_line = clean
+            .lines()
+            .map(|l| l.trim_matches(|c: char| c.is_whitespace() || c == '\x0c' || c == '\x1b'))
+            .find(|l| !l.is_empty() && !l.starts_with("@PJL") && !l.starts_with("%-12345X"))
+            .unwrap_or(UNKNOWN_PJL)
+            .to_string();
+        return Some(model_line);
+    }
+    None
+}
+
+pub async fn get_pjl_info(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<String> {
+    parse_pjl(&query_pjl(source, addr, timeout_ms).await?)
+}
+
+/// 2. Zebra SGD 探测 (最稳的斑马识别法)
+///
+/// 发送: ! U1 getvar "device.product_name"
+async fn query_zebra_sgd(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<Vec<u8>> {
+    // 注意: 命令必须以换行符结尾
+    // SGD 响应很快，通常就是一行纯文本，例如 "GX430t"
+    exchange(source, addr, timeout_ms, b"! U1 getvar \"device.product_name\"\r\n", 1500).await
+}
+
+fn parse_zebra_sgd(raw: &[u8]) -> Option<String> {
+    let raw = String::from_utf8_lossy(raw).trim().to_string();
+    // 过滤掉空响应或乱码
+    if !raw.is_empty() && raw.len() > 2 && raw.chars().all(|c| c.is_ascii() && !c.is_control()) {
+        // 有时候会返回双引号，去掉它
+        let clean = raw.replace("\"", "");
+        return Some(format!("Zebra {}", clean));
+    }
+    None
+}
+
+pub async fn get_zebra_sgd_info(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<String> {
+    parse_zebra_sgd(&query_zebra_sgd(source, addr, timeout_ms).await?)
+}
+
+/// 3. Zebra ZPL ~HI 探测 (老式备用)
+async fn query_zpl_hi(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<Vec<u8>> {
+    exchange(source, addr, timeout_ms, b"~HI", 1000).await
+}
+
+fn parse_zpl_hi(raw: &[u8]) -> Option<String> {
+    let raw = String::from_utf8_lossy(raw).to_string();
+    if raw.contains(",") {
+        // 尝试粗暴提取：取逗号分隔后的最长那一段，通常是型号
+        let parts: Vec<&str> = raw.split(',').collect();
+        if let Some(longest) = parts.iter().max_by_key(|p| p.len())
+            && longest.len() > 3
+        {
+            return Some(format!("Zebra ZPL ({})", longest.trim()));
+        }
+    }
+    None
+}
+
+pub async fn get_zpl_hi_info(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<String> {
+    parse_zpl_hi(&query_zpl_hi(source, addr, timeout_ms).await?)
+}
+
+/// 4. SNMP 探测
+pub async fn get_snmp_info(snmp: &crate::snmp::Client, ip: IpAddr) -> Option<String> {
+    crate::snmp::system_description(snmp, ip).await
+}
+
+/// BJNP 报文头：4 字节标识、设备类型、命令、错误码、序号、会话号、负载长度 (大端)
+pub fn bjnp_packet(port: u16, cmd: u8, seq: u16) -> Vec<u8> {
+    let (magic, dev_type) = if port == MFNP_PORT { (b"MFNP", 2) } else { (b"BJNP", 1) };
+    let mut packet = magic.to_vec();
+    packet.extend([dev_type, cmd, 0, 0]);
+    packet.extend(seq.to_be_bytes());
+    packet.extend([0, 0, 0, 0, 0, 0]);
+    packet
+}
+
+/// 响应的设备类型是请求的类型加上 0x80，命令码不变
+pub fn is_bjnp_reply(raw: &[u8], cmd: u8) -> bool {
+    raw.len() >= 16 && (&raw[..4] == b"BJNP" || &raw[..4] == b"MFNP") && raw[4] & 0x80 != 0 && raw[5] == cmd
+}
+
+pub const BJNP_DISCOVER: u8 = 0x01;
+const BJNP_GET_ID: u8 = 0x30;
+
+/// 5. Canon BJNP 探测 (UDP 8611/8612)
+///
+/// 很多 Canon 喷墨机和 imageRUNNER 不在 9100 上响应 PJL，但会通过 BJNP 返回 IEEE 1284 设备 ID。
+/// 返回设备 ID 文本 (去掉报文头和长度字段)
+pub async fn query_bjnp(ip: IpAddr, port: u16, timeout_ms: u64) -> Option<Vec<u8>> {
+    let bind: SocketAddr = if ip.is_ipv4() { (Ipv4Addr::UNSPECIFIED, 0).into() } else { (Ipv6Addr::UNSPECIFIED, 0).into() };
+    let socket = UdpSocket::bind(bind).await.ok()?;
+    socket.connect(SocketAddr::new(ip, port)).await.ok()?;
+    socket.send(&bjnp_packet(port, BJNP_GET_ID, 1)).await.ok()?;
+
+    let mut buffer = [0; 2048];
+    let n = timeout(Duration::from_millis(timeout_ms.min(1000)), socket.recv(&mut buffer)).await.ok()?.ok()?;
+    let raw = &buffer[..n];
+    if !is_bjnp_reply(raw, BJNP_GET_ID) || raw.len() < 18 {
+        return None;
+    }
+    // 负载以 2 字节长度开头 (包含自身)，后面是 `MFG:Canon;MDL:...;` 形式的设备 ID
+    let len = (u16::from_be_bytes([raw[16], raw[17]]) as usize).clamp(2, raw.len() - 16);
+    Some(raw[18..16 + len].to_vec())
+}
+
+/// 解析 IEEE 1284 设备 ID (`MFG:...;MDL:...;`)，ENPC 和 Brother 私有 MIB 返回的也是这种格式
+pub fn parse_bjnp(raw: &[u8]) -> Option<String> {
+    let raw = String::from_utf8_lossy(raw);
+    let field = |keys: &[&str]| {
+        raw.split(';').find_map(|kv| {
+            let (key, value) = kv.split_once(':')?;
+            keys.contains(&key.trim()).then(|| value.trim().to_string()).filter(|v| !v.is_empty())
+        })
+    };
+    let model = field(&["MDL", "MODEL"])?;
+    Some(match field(&["MFG", "MANUFACTURER"]) {
+        Some(mfg) if !model.starts_with(&mfg) => format!("{} {}", mfg, model),
+        _ => model,
+    })
+}
+
+pub async fn get_bjnp_info(ip: IpAddr, port: u16, timeout_ms: u64) -> Option<String> {
+    parse_bjnp(&query_bjnp(ip, port, timeout_ms).await?)
+}
+
+/// 6. Epson ENPC 探测 (UDP 3289)
+///
+/// 发送 `EPSONQ` 查询，响应以 `EPSONq` 开头，后面是设备名称等以 NUL 分隔的字段。
+/// 很多 Epson 设备不响应 PJL，SNMP 也只返回通用的 `EPSON Built-in` 之类的字符串
+async fn query_enpc(ip: IpAddr, timeout_ms: u64) -> Option<Vec<u8>> {
+    let bind: SocketAddr = if ip.is_ipv4() { (Ipv4Addr::UNSPECIFIED, 0).into() } else { (Ipv6Addr::UNSPECIFIED, 0).into() };
+    let socket = UdpSocket::bind(bind).await.ok()?;
+    socket.connect(SocketAddr::new(ip, ENPC_PORT)).await.ok()?;
+    socket.send(b"EPSONQ\x03\x00\x00\x00\x00\x00\x00\x00").await.ok()?;
+
+    let mut buffer = [0; 2048];
+    let n = timeout(Duration::from_millis(timeout_ms.min(1000)), socket.recv(&mut buffer)).await.ok()?.ok()?;
+    buffer[..n].starts_with(b"EPSONq").then(|| buffer[..n].to_vec())
+}
+
+/// 响应中的可打印字段。有 IEEE 1284 设备 ID (`MDL:`) 时用它，否则取第一个像型号的字段
+fn parse_enpc(raw: &[u8]) -> Option<String> {
+    let fields: Vec<String> = raw
+        .get(6..)?
+        .split(|b| !(0x20..0x7f).contains(b))
+        .map(|f| String::from_utf8_lossy(f).trim().to_string())
+        .filter(|f| f.len() >= 3)
+        .collect();
+    if let Some(id) = fields.iter().find(|f| f.contains("MDL:")) {
+        return parse_bjnp(id.as_bytes());
+    }
+    // `@BDC` / `@EJL` 开头的是状态块，不是名称
+    let name = fields
+        .iter()
+        .find(|f| f.chars().any(|c| c.is_ascii_alphabetic()) && !f.starts_with("@BDC") && !f.starts_with("@EJL"))?;
+    Some(if name.to_uppercase().starts_with("EPSON") { name.clone() } else { format!("EPSON {}", name) })
+}
+
+/// 部分型号在响应里附带 `@BDC ST2` 状态块：2 字节长度 (小端) 后是若干 `类型, 长度, 数据`，
+/// 类型 0x01 为打印机状态
+fn parse_enpc_status(raw: &[u8]) -> Option<String> {
+    let start = raw.windows(8).position(|w| w == b"@BDC ST2")? + 8;
+    let block = raw.get(start..)?;
+    let block = block.strip_prefix(b"\r\n").unwrap_or(block);
+    let mut fields = block.get(2..)?;
+    while let [kind, len, rest @ ..] = fields {
+        let (data, next) = rest.split_at_checked(*len as usize)?;
+        if *kind == 0x01 {
+            let status = match data.first()? {
+                0x00 => "error",
+                0x01 => "self-printing",
+                0x02 => "busy",
+                0x03 => "waiting",
+                0x04 => "idle",
+                0x05 => "paused",
+                0x07 => "cleaning",
+                0x08 => "factory",
+                0x0a => "shutting down",
+                other => return Some(format!("0x{:02x}", other)),
+            };
+            return Some(status.to_string());
+        }
+        fields = next;
+    }
+    None
+}
+
+/// 返回型号和 (如果设备提供) 状态
+pub async fn get_enpc_info(ip: IpAddr, timeout_ms: u64) -> Option<(String, Option<String>)> {
+    let raw = query_enpc(ip, timeout_ms).await?;
+    Some((parse_enpc(&raw)?, parse_enpc_status(&raw)))
+}
+
+/// 7. 兜底策略：如果上面都失败了，但端口能读出数据，就把数据打印出来
+///
+/// 很多老式打印机会在连接建立时发送 "Press Enter..." 或者型号 Banner
+async fn query_raw_banner(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<Vec<u8>> {
+    // 此时不发任何指令，只是干等 500ms，看它会不会吐出 banner
+    exchange(source, addr, timeout_ms, b"", 500).await
+}
+
+fn parse_raw_banner(raw: &[u8]) -> Option<String> {
+    let raw = String::from_utf8_lossy(raw).replace(['\r', '\n'], " ").trim().to_string();
+    if raw.len() > 3 && raw.chars().any(|c| c.is_alphabetic()) {
+        return Some(raw);
+    }
+    None
+}
+
+pub async fn get_raw_banner(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<String> {
+    parse_raw_banner(&query_raw_banner(source, addr, timeout_ms).await?)
+}
+
+/// 8. LPD 队列状态 (TCP 515)
+///
+/// 老式行式打印机和一些打印服务器只开 515。发送“短格式队列状态”命令 (0x03 + 队列名)，
+/// 服务器返回一段描述队列的文本后关闭连接。队列名不对时多数实现也会回一句错误，同样说明是 LPD
+async fn query_lpd(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<Vec<u8>> {
+    exchange(source, addr, timeout_ms, b"\x03lp\n", 1500).await
+}
+
+fn parse_lpd(raw: &[u8]) -> Option<String> {
+    let raw = String::from_utf8_lossy(raw);
+    let text = raw.lines().map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join("; ");
+    (text.chars().any(|c| c.is_alphabetic())).then_some(text)
+}
+
+pub async fn get_lpd_info(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<String> {
+    parse_lpd(&query_lpd(source, addr, timeout_ms).await?)
+}
+
+/// 9. ESC/POS 探测 (Epson TM 系列及兼容的小票打印机)
+///
+/// 在同一个连接上依次发送：`DLE EOT 1` 实时状态、`DLE EOT 4` 纸张状态 (各回 1 字节)，
+/// `GS I 66` 厂商、`GS I 67` 型号 (回 `_` + 文本 + NUL)。返回的原始数据按这个顺序拼接
+async fn query_escpos(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<Vec<u8>> {
+    let mut stream = timeout(Duration::from_millis(timeout_ms), connect(source, addr)).await.ok()?.ok()?;
+    let mut raw = Vec::new();
+    for cmd in [&b"\x10\x04\x01"[..], b"\x10\x04\x04"] {
+        stream.write_all(cmd).await.ok()?;
+        let mut byte = [0u8; 1];
+        match timeout(Duration::from_millis(500), stream.read_exact(&mut byte)).await {
+            Ok(Ok(_)) => raw.push(byte[0]),
+            _ => break,
+        }
+    }
+    // 状态字节格式不对就不是 ESC/POS 设备，不再发送 GS I (否则可能被当作文本打印出来)
+    if raw.first().is_none_or(|b| b & 0x93 != 0x12) {
+        return (!raw.is_empty()).then_some(raw);
+    }
+    for cmd in [b"\x1dIB", b"\x1dIC"] {
+        stream.write_all(cmd).await.ok()?;
+        let mut reply = Vec::new();
+        let mut byte = [0u8; 1];
+        while let Ok(Ok(_)) = timeout(Duration::from_millis(500), stream.read_exact(&mut byte)).await {
+            reply.push(byte[0]);
+            if byte[0] == 0 || reply.len() > 64 {
+                break;
+            }
+        }
+        raw.extend(reply);
+    }
+    Some(raw)
+}
+
+fn parse_escpos(raw: &[u8]) -> Option<String> {
+    let (&status, rest) = raw.split_first()?;
+    if status & 0x93 != 0x12 {
+        return None;
+    }
+    // 跳过纸张状态字节，后面是以 NUL 结尾的 `_EPSON`、`_TM-T88V`
+    let names: Vec<String> = rest
+        .get(1..)
+        .unwrap_or_default()
+        .split(|b| *b == 0)
+        .filter_map(|s| s.strip_prefix(b"_"))
+        .map(|s| String::from_utf8_lossy(s).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    Some(match names.as_slice() {
+        [] => "ESC/POS receipt printer".to_string(),
+        [mfg, model, ..] if !model.starts_with(mfg.as_str()) => format!("{} {}", mfg, model),
+        [.., model] => model.clone(),
+    })
+}
+
+/// 实时状态第 3 位为离线；纸张状态第 5、6 位为纸尽，第 2、3 位为纸将尽
+fn parse_escpos_status(raw: &[u8]) -> Option<String> {
+    let status = *raw.first()?;
+    let paper = raw.get(1).copied().unwrap_or(0);
+    let text = if paper & 0x60 == 0x60 {
+        "paper out"
+    } else if status & 0x08 != 0 {
+        "offline"
+    } else if paper & 0x0c == 0x0c {
+        "paper low"
+    } else {
+        "idle"
+    };
+    Some(text.to_string())
+}
+
+/// 返回型号和状态
+pub async fn get_escpos_info(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<(String, Option<String>)> {
+    let raw = query_escpos(source, addr, timeout_ms).await?;
+    Some((parse_escpos(&raw)?, parse_escpos_status(&raw)))
+}
+
+/// 10. Star 探测 (Star Micronics 小票 / 标签打印机)
+///
+/// `ESC ACK SOH` 请求自动状态 (ASB)，响应第一个字节是头部：第 0 位为 1、第 4、7 位为 0，
+/// 第 1–3、5 位是 ASB 的总长度；再用 `ESC # * LF NUL` 取版本信息 (`TSP143IIIW Ver1.1`)。
+/// 返回的原始数据是 ASB 后接版本字符串
+async fn query_star(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<Vec<u8>> {
+    let mut stream = timeout(Duration::from_millis(timeout_ms), connect(source, addr)).await.ok()?.ok()?;
+    stream.write_all(b"\x1b\x06\x01").await.ok()?;
+    let mut buffer = [0u8; 64];
+    let n = match timeout(Duration::from_millis(500), stream.read(&mut buffer)).await {
+        Ok(Ok(n)) if n > 0 => n,
+        _ => return None,
+    };
+    let mut raw = buffer[..n].to_vec();
+    if star_asb_len(&raw).is_none() {
+        return Some(raw);
+    }
+    stream.write_all(b"\x1b#*\n\x00").await.ok()?;
+    while let Ok(Ok(n)) = timeout(Duration::from_millis(500), stream.read(&mut buffer)).await {
+        raw.extend(&buffer[..n]);
+        if n == 0 || buffer[..n].contains(&0) {
+            break;
+        }
+    }
+    Some(raw)
+}
+
+fn star_asb_len(raw: &[u8]) -> Option<usize> {
+    let header = *raw.first()?;
+    if header & 0x91 != 0x01 {
+        return None;
+    }
+    let len = ((header >> 1) & 0x07) as usize | ((header >> 2) & 0x08) as usize;
+    (len >= 7 && raw.len() >= len).then_some(len)
+}
+
+/// 版本字符串中型号前缀对应的产品系列
+const STAR_SERIES: &[(&str, &str)] = &[
+    ("TSP1", "TSP100"),
+    ("TSP6", "TSP650"),
+    ("TSP7", "TSP700"),
+    ("TSP8", "TSP800"),
+    ("TUP9", "TUP900"),
+    ("SP7", "SP700"),
+    ("MCP", "mC-Print"),
+    ("MCL", "mC-Label"),
+    ("FVP", "FVP10"),
+    ("BSC", "BSC10"),
+    ("SM-", "SM mobile"),
+];
+
+fn parse_star(raw: &[u8]) -> Option<String> {
+    let len = star_asb_len(raw)?;
+    let version = String::from_utf8_lossy(&raw[len..]);
+    let model = version.trim_matches(|c: char| c.is_whitespace() || c == '\0').split_whitespace().next();
+    Some(match model {
+        Some(model) => match STAR_SERIES.iter().find(|(prefix, _)| model.to_uppercase().starts_with(prefix)) {
+            Some((_, series)) if !model.to_uppercase().starts_with(&series.to_uppercase()) => format!("Star {} ({})", model, series),
+            _ => format!("Star {}", model),
+        },
+        None => "Star printer".to_string(),
+    })
+}
+
+/// ASB 第 3 字节第 3 位为离线、第 5 位为上盖打开；第 6 字节第 3 位为纸尽、第 2 位为纸将尽
+fn parse_star_status(raw: &[u8]) -> Option<String> {
+    star_asb_len(raw)?;
+    let text = if raw[5] & 0x08 != 0 {
+        "paper out"
+    } else if raw[2] & 0x20 != 0 {
+        "cover open"
+    } else if raw[2] & 0x08 != 0 {
+        "offline"
+    } else if raw[5] & 0x04 != 0 {
+        "paper low"
+    } else {
+        "idle"
+    };
+    Some(text.to_string())
+}
+
+/// 先在打印端口查询，没有响应时再试 Star 的状态端口
+pub async fn get_star_info(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<(String, Option<String>)> {
+    for addr in [addr, SocketAddr::new(addr.ip(), STAR_STATUS_PORT)] {
+        if let Some(raw) = query_star(source, addr, timeout_ms).await
+            && let Some(model) = parse_star(&raw)
+        {
+            return Some((model, parse_star_status(&raw)));
+        }
+    }
+    None
+}
+
+/// 11. Dymo LabelWriter / Seiko SLP 标签打印机
+///
+/// 两家的标签机用同一套 ESC 指令 (LabelWriter 最早就是 Seiko 代工的)。先发 `ESC V` 取版本字符串
+/// (`LWW 93089v0J`、`SLP650 ...`)，认出来后再发 `ESC A` 取 1 字节状态。返回版本字符串后接状态字节
+async fn query_label(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<Vec<u8>> {
+    let mut stream = timeout(Duration::from_millis(timeout_ms), connect(source, addr)).await.ok()?.ok()?;
+    stream.write_all(b"\x1bV").await.ok()?;
+    let mut buffer = [0u8; 64];
+    let n = match timeout(Duration::from_millis(500), stream.read(&mut buffer)).await {
+        Ok(Ok(n)) if n > 0 => n,
+        _ => return None,
+    };
+    let mut raw = buffer[..n].to_vec();
+    if parse_label(&raw).is_none() {
+        return Some(raw);
+    }
+    // 版本字符串可能自带 NUL 结尾，统一去掉后再用一个 NUL 隔开状态字节
+    raw.truncate(raw.iter().position(|b| *b == 0).unwrap_or(raw.len()));
+    stream.write_all(b"\x1bA").await.ok()?;
+    let mut byte = [0u8; 1];
+    if let Ok(Ok(_)) = timeout(Duration::from_millis(500), stream.read_exact(&mut byte)).await {
+        raw.push(0);
+        raw.push(byte[0]);
+    }
+    Some(raw)
+}
+
+/// 版本字符串前缀对应的型号
+const LABEL_MODELS: &[(&str, &str)] = &[
+    ("LWW", "DYMO LabelWriter Wireless"),
+    ("LW55", "DYMO LabelWriter 550"),
+    ("LW45", "DYMO LabelWriter 450"),
+    ("LW4XL", "DYMO LabelWriter 4XL"),
+    ("LW5XL", "DYMO LabelWriter 5XL"),
+    ("LW", "DYMO LabelWriter"),
+    ("SLP", "Seiko SLP"),
+];
+
+/// 版本字符串 (到 NUL 为止) 以已知前缀开头才认为是标签机，其余字节可能是其他设备的回显
+fn parse_label(raw: &[u8]) -> Option<String> {
+    let end = raw.iter().position(|b| *b == 0).unwrap_or(raw.len());
+    let version = String::from_utf8_lossy(&raw[..end]).trim().to_string();
+    let upper = version.to_uppercase();
+    let (prefix, model) = LABEL_MODELS.iter().find(|(prefix, _)| upper.starts_with(prefix))?;
+    // Seiko 的版本串本身就是型号 (`SLP650 V1.02`)，只补上厂商名
+    Some(match *prefix {
+        "SLP" => format!("Seiko {}", version),
+        _ => format!("{} ({})", model, version),
+    })
+}
+
+/// 状态字节第 5 位为缺标签纸，第 0 位为正在打印
+fn parse_label_status(raw: &[u8]) -> Option<String> {
+    let end = raw.iter().position(|b| *b == 0)?;
+    let status = *raw.get(end + 1)?;
+    let text = if status & 0x20 != 0 {
+        "paper out"
+    } else if status & 0x01 != 0 {
+        "printing"
+    } else {
+        "idle"
+    };
+    Some(text.to_string())
+}
+
+/// 返回型号和状态
+pub async fn get_label_info(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<(String, Option<String>)> {
+    let raw = query_label(source, addr, timeout_ms).await?;
+    Some((parse_label(&raw)?, parse_label_status(&raw)))
+}
+
+/// 12. FTP 欢迎信息 (TCP 21)
+///
+/// 很多打印机和打印服务器带 FTP 打印服务，连接后的 `220` 欢迎行里就有确切的型号和固件版本，
+/// 如 `220 HP LaserJet Pro M404n FTP Server Version 2.0 ready.`
+async fn query_ftp(source: Source, ip: IpAddr, timeout_ms: u64) -> Option<Vec<u8>> {
+    exchange(source, SocketAddr::new(ip, FTP_PORT), timeout_ms, b"", 1500).await
+}
+
+/// 返回型号和固件版本。型号后面的 `FTP server (Ver. 2.30)`、`FTP Server Version 2.0` 是服务程序的名称，
+/// 去掉后把版本号作为固件版本
+fn parse_ftp(raw: &[u8]) -> Option<(String, Option<String>)> {
+    let raw = String::from_utf8_lossy(raw);
+    // 多行欢迎信息是 `220-...`，最后一行是 `220 ...`；NAS、路由器等也开 FTP，只认带厂商名或 print 字样的行
+    let line = raw
+        .lines()
+        .filter_map(|line| line.strip_prefix("220"))
+        .map(|line| line.trim_start_matches('-').trim().trim_end_matches('.').trim_end_matches("ready").trim_end_matches(',').trim())
+        .find(|line| crate::http::vendor_of(line).is_some() || line.to_lowercase().contains("print"))?;
+    let Some(at) = line.to_lowercase().find("ftp server") else { return Some((line.to_string(), None)) };
+    let model = line[..at].trim();
+    let version = line[at + "ftp server".len()..].trim().trim_start_matches('(').trim_end_matches(')').trim();
+    if model.is_empty() {
+        return Some((line.to_string(), None));
+    }
+    Some((model.to_string(), (!version.is_empty()).then(|| version.to_string())))
+}
+
+pub async fn get_ftp_info(source: Source, ip: IpAddr, timeout_ms: u64) -> Option<(String, Option<String>)> {
+    parse_ftp(&query_ftp(source, ip, timeout_ms).await?)
+}
+
+/// 单个协议的探测明细，供 `probe` 子命令展示
+#[derive(Serialize, Debug)]
+pub struct ProbeOutcome {
+    pub source: String,
+    /// 探测所用的端口和传输层协议 (`tcp` / `udp`)
+    pub port: u16,
+    pub transport: &'static str,
+    /// 设备返回的原始数据 (非 UTF-8 字节按替换字符处理)
+    pub raw: Option<String>,
+    /// 解析出的型号，`None` 表示该协议无法识别
+    pub parsed: Option<String>,
+}
+
+impl ProbeOutcome {
+    /// TCP 端口 `port` 上的探测
+    pub fn new(source: &str, port: u16, raw: Option<Vec<u8>>, parse: fn(&[u8]) -> Option<String>) -> Self {
+        ProbeOutcome {
+            source: source.to_string(),
+            port,
+            transport: "tcp",
+            parsed: raw.as_deref().and_then(parse),
+            raw: raw.map(|r| String::from_utf8_lossy(&r).to_string()),
+        }
+    }
+
+    /// UDP 端口 `port` 上的探测
+    pub fn udp(source: &str, port: u16, raw: Option<Vec<u8>>, parse: fn(&[u8]) -> Option<String>) -> Self {
+        ProbeOutcome { transport: "udp", ..ProbeOutcome::new(source, port, raw, parse) }
+    }
+}
+
+/// 依次执行所有探测，不在第一个成功处停下，保留每个协议的原始响应
+pub async fn run_all_probes(context: &Context, addr: SocketAddr, timeout_ms: u64) -> Vec<ProbeOutcome> {
+    let source = context.source;
+    let snmp = get_snmp_info(&context.snmp, addr.ip()).await;
+    let web = crate::http::fingerprint(source, addr.ip(), crate::http::HTTP_PORT, timeout_ms).await;
+    let tls = crate::http::https_probe(source, addr.ip(), crate::http::HTTPS_PORT, timeout_ms).await;
+    let tls_raw = tls.as_ref().map(|t| match &t.certificate {
+        Some(cert) => format!("subject: {}\nissuer: {}", cert.subject, cert.issuer),
+        None => "(no certificate)".to_string(),
+    });
+    vec![
+        ProbeOutcome::new("SGD (Zebra)", addr.port(), query_zebra_sgd(source, addr, timeout_ms).await, parse_zebra_sgd),
+        ProbeOutcome::new("PJL", addr.port(), query_pjl(source, addr, timeout_ms).await, parse_pjl),
+        ProbeOutcome::new("ZPL", addr.port(), query_zpl_hi(source, addr, timeout_ms).await, parse_zpl_hi),
+        ProbeOutcome::new("Star", addr.port(), query_star(source, addr, timeout_ms).await, parse_star),
+        ProbeOutcome::new("ESC/POS", addr.port(), query_escpos(source, addr, timeout_ms).await, parse_escpos),
+        ProbeOutcome::new("Dymo / Seiko", addr.port(), query_label(source, addr, timeout_ms).await, parse_label),
+        ProbeOutcome::udp("BJNP (Canon)", BJNP_PORT, query_bjnp(addr.ip(), BJNP_PORT, timeout_ms).await, parse_bjnp),
+        ProbeOutcome::udp("ENPC (Epson)", ENPC_PORT, query_enpc(addr.ip(), timeout_ms).await, parse_enpc),
+        ProbeOutcome { source: "SNMP".to_string(), port: context.snmp.port(), transport: "udp", raw: snmp.clone(), parsed: snmp },
+        ProbeOutcome::new("FTP", FTP_PORT, query_ftp(source, addr.ip(), timeout_ms).await, |raw| parse_ftp(raw).map(|(model, _)| model)),
+        ProbeOutcome::new("Raw Banner", addr.port(), query_raw_banner(source, addr, timeout_ms).await, parse_raw_banner),
+        ProbeOutcome::new("LPD", LPD_PORT, query_lpd(source, SocketAddr::new(addr.ip(), LPD_PORT), timeout_ms).await, parse_lpd),
+        ProbeOutcome { source: "HTTP".to_string(), port: crate::http::HTTP_PORT, transport: "tcp", raw: web.clone(), parsed: web },
+        ProbeOutcome { source: "HTTPS".to_string(), port: crate::http::HTTPS_PORT, transport: "tcp", raw: tls_raw, parsed: tls.and_then(|t| t.model) },
+    ]
+}
+
+/// 定义只调用一个 `get_*_info` 函数的探测
+macro_rules! probe {
+    ($probe:ident, $name:expr, $ports:expr, escape, |$addr:ident, $context:ident, $timeout_ms:ident| $run:expr) => {
+        probe!(@impl $probe, $name, $ports, true, |$addr, $context, $timeout_ms| $run);
+    };
+    ($probe:ident, $name:expr, $ports:expr, |$addr:ident, $context:ident, $timeout_ms:ident| $run:expr) => {
+        probe!(@impl $probe, $name, $ports, false, |$addr, $context, $timeout_ms| $run);
+    };
+    (@impl $probe:ident, $name:expr, $ports:expr, $escape:expr, |$addr:ident, $context:ident, $timeout_ms:ident| $run:expr) => {
+        pub struct $probe;
+
+        impl Probe for $probe {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn ports(&self) -> Ports {
+                $ports
+            }
+
+            fn escape_query(&self) -> bool {
+                $escape
+            }
+
+            fn run<'a>(&'a self, $addr: SocketAddr, $context: &'a Context, $timeout_ms: u64) -> BoxFuture<'a, Option<ProbeResult>> {
+                async move { $run }.boxed()
+            }
+        }
+    };
+}
+
+probe!(SgdProbe, "SGD (Zebra)", SGD_PORTS, |addr, context, timeout_ms| get_zebra_sgd_info(context.source, addr, timeout_ms).await.map(ProbeResult::model));
+probe!(PjlProbe, "PJL", PJL_PORTS, |addr, context, timeout_ms| get_pjl_info(context.source, addr, timeout_ms).await.map(ProbeResult::model));
+probe!(ZplProbe, "ZPL", ZPL_PORTS, |addr, context, timeout_ms| get_zpl_hi_info(context.source, addr, timeout_ms).await.map(ProbeResult::model));
+probe!(StarProbe, "Star", STAR_PORTS, escape, |addr, context, timeout_ms| get_star_info(context.source, addr, timeout_ms).await.map(ProbeResult::with_status));
+probe!(EscPosProbe, "ESC/POS", ESCPOS_PORTS, escape, |addr, context, timeout_ms| get_escpos_info(context.source, addr, timeout_ms).await.map(ProbeResult::with_status));
+probe!(LabelProbe, "Dymo/Seiko", LABEL_PORTS, escape, |addr, context, timeout_ms| get_label_info(context.source, addr, timeout_ms).await.map(ProbeResult::with_status));
+probe!(BjnpProbe, "BJNP", BJNP_PORTS, |addr, _context, timeout_ms| get_bjnp_info(addr.ip(), BJNP_PORT, timeout_ms).await.map(ProbeResult::model));
+probe!(EnpcProbe, "ENPC", ENPC_PORTS, |addr, _context, timeout_ms| get_enpc_info(addr.ip(), timeout_ms).await.map(ProbeResult::with_status));
+probe!(SnmpProbe, "SNMP", SNMP_PORTS, |addr, context, _timeout_ms| get_snmp_info(&context.snmp, addr.ip()).await.map(ProbeResult::model));
+// 设备不报型号，记录返回的队列描述
+probe!(LpdProbe, "LPD", LPD_PORTS, |addr, context, timeout_ms| get_lpd_info(context.source, addr, timeout_ms).await.map(|queue| ProbeResult::model(format!("LPD: {}", queue))));
+probe!(IppProbe, "IPP", IPP_PORTS, |addr, context, timeout_ms| {
+    let ipp = crate::ipp::capabilities(context.source, addr.ip(), timeout_ms).await?;
+    Some(ProbeResult { model: ipp.make_and_model.clone()?, ipp: Some(ipp), ..Default::default() })
+});
+probe!(HttpProbe, "HTTP", HTTP_PORTS, |addr, context, timeout_ms| crate::http::fingerprint(context.source, addr.ip(), crate::http::HTTP_PORT, timeout_ms).await.map(ProbeResult::model));
+// 自签名证书的 CN 常是型号或出厂主机名，认不出时再看网页
+probe!(HttpsProbe, "HTTPS", HTTPS_PORTS, |addr, context, timeout_ms| {
+    let tls = crate::http::https_probe(context.source, addr.ip(), crate::http::HTTPS_PORT, timeout_ms).await?;
+    Some(ProbeResult { model: tls.model?, certificate: tls.certificate, ..Default::default() })
+});
+probe!(FtpProbe, "FTP", FTP_PORTS, |addr, context, timeout_ms| {
+    let (model, firmware) = get_ftp_info(context.source, addr.ip(), timeout_ms).await?;
+    Some(ProbeResult { model, firmware, ..Default::default() })
+});
+
+/// 兜底：端口通了且有数据回显时当作未知设备显示出来
+pub struct BannerProbe;
+
+impl Probe for BannerProbe {
+    fn name(&self) -> &'static str {
+        "Raw Banner"
+    }
+
+    fn ports(&self) -> Ports {
+        BANNER_PORTS
+    }
+
+    fn fallback(&self) -> bool {
+        true
+    }
+
+    fn run<'a>(&'a self, addr: SocketAddr, context: &'a Context, timeout_ms: u64) -> BoxFuture<'a, Option<ProbeResult>> {
+        async move { get_raw_banner(context.source, addr, timeout_ms).await.map(|raw| ProbeResult::model(format!("Raw: {}", raw))) }.boxed()
+    }
+}
+
+/// 内置探测的尝试顺序。Zebra SGD 最稳，排在最前；ESC 二进制查询放在 PJL 和 ENPC 之后；
+/// Star 小票机在 ESC/POS 模拟模式下也会回应，要先于 ESC/POS；问整台主机的 UDP / 网页探测放在打印语言之后
+const BUILTIN_PROBES: &[&dyn Probe] = &[
+    &SgdProbe,
+    &PjlProbe,
+    &ZplProbe,
+    &BjnpProbe,
+    &EnpcProbe,
+    &StarProbe,
+    &EscPosProbe,
+    &LabelProbe,
+    &SnmpProbe,
+    &LpdProbe,
+    &IppProbe,
+    &HttpProbe,
+    &HttpsProbe,
+    &FtpProbe,
+    &BannerProbe,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENPC_ST2: &[u8] = b"EPSONq\x03\x00\x00\x00\x00\x00\x10ET-4850 Series\x00\x00@BDC ST2\r\n\x06\x00\x01\x01\x04\x02\x01\x00";
+
+    #[test]
+    fn enpc_name_gets_vendor_prefix() {
+        assert_eq!(parse_enpc(ENPC_ST2).as_deref(), Some("EPSON ET-4850 Series"));
+        assert_eq!(parse_enpc(b"EPSONq\x03\x00\x10EPSON WF-3820\x00").as_deref(), Some("EPSON WF-3820"));
+    }
+
+    #[test]
+    fn enpc_device_id_takes_precedence() {
+        let raw = b"EPSONq\x03\x00\x10L3150\x00MFG:EPSON;CMD:ESCPL2;MDL:L3150 Series;\x00";
+        assert_eq!(parse_enpc(raw).as_deref(), Some("EPSON L3150 Series"));
+    }
+
+    #[test]
+    fn enpc_status_blocks_are_not_names() {
+        assert_eq!(parse_enpc(b"EPSONq\x03\x00@EJL ID\x00@BDC ST2\x00"), None);
+        assert_eq!(parse_enpc(b"EPSONq"), None);
+        assert_eq!(parse_enpc(b"EPS"), None);
+    }
+
+    #[test]
+    fn enpc_status_walks_fields() {
+        assert_eq!(parse_enpc_status(ENPC_ST2).as_deref(), Some("idle"));
+        // 没有 CRLF，状态字段不在第一位
+        let raw = b"EPSONq@BDC ST2\x08\x00\x0f\x02\xaa\xbb\x01\x01\x07";
+        assert_eq!(parse_enpc_status(raw).as_deref(), Some("cleaning"));
+        assert_eq!(parse_enpc_status(b"@BDC ST2\x03\x00\x01\x01\x06").as_deref(), Some("0x06"));
+    }
+
+    #[test]
+    fn enpc_status_rejects_truncated_fields() {
+        assert_eq!(parse_enpc_status(b"@BDC ST2\r\n\x06\x00\x01\x05\x04"), None);
+        assert_eq!(parse_enpc_status(b"@BDC ST2\r\n\x06\x00\x0f\x01\x00"), None);
+        assert_eq!(parse_enpc_status(b"EPSONq\x03\x00ET-4850"), None);
+    }
+
+    #[test]
+    fn escpos_joins_vendor_and_model() {
+        assert_eq!(parse_escpos(b"\x16\x72_EPSON\x00_TM-T88V\x00").as_deref(), Some("EPSON TM-T88V"));
+        // 型号里已经带了厂商就不重复
+        assert_eq!(parse_escpos(b"\x16\x00_EPSON\x00_EPSON TM-m30\x00").as_deref(), Some("EPSON TM-m30"));
+        assert_eq!(parse_escpos(b"\x12\x00_TM-T20II\x00").as_deref(), Some("TM-T20II"));
+    }
+
+    #[test]
+    fn escpos_status_byte_has_fixed_bits() {
+        // 只有状态字节也算 ESC/POS 设备
+        assert_eq!(parse_escpos(b"\x1e").as_deref(), Some("ESC/POS receipt printer"));
+        assert_eq!(parse_escpos(b"\x16\x00EPSON\x00").as_deref(), Some("ESC/POS receipt printer"));
+        // 第 1 位必须为 1、第 0/4/7 位必须为 0
+        assert_eq!(parse_escpos(b"\x14\x00_EPSON\x00"), None);
+        assert_eq!(parse_escpos(b"\x92\x00_EPSON\x00"), None);
+        assert_eq!(parse_escpos(b"@PJL"), None);
+        assert_eq!(parse_escpos(b""), None);
+    }
+
+    #[test]
+    fn escpos_paper_out_beats_offline() {
+        assert_eq!(parse_escpos_status(b"\x16\x00").as_deref(), Some("idle"));
+        assert_eq!(parse_escpos_status(b"\x1e\x6c").as_deref(), Some("paper out"));
+        assert_eq!(parse_escpos_status(b"\x1e\x0c").as_deref(), Some("offline"));
+        assert_eq!(parse_escpos_status(b"\x16\x0c").as_deref(), Some("paper low"));
+        // 只有一个近端传感器位不算纸将尽
+        assert_eq!(parse_escpos_status(b"\x16\x04").as_deref(), Some("idle"));
+        assert_eq!(parse_escpos_status(b"\x1e").as_deref(), Some("offline"));
+    }
+
+    const STAR_ASB: &[u8] = b"\x23\x86\x00\x00\x00\x04\x00\x00\x00TSP143IIIW Ver1.1\n\x00";
+
+    #[test]
+    fn star_header_encodes_asb_length() {
+        assert_eq!(star_asb_len(STAR_ASB), Some(9));
+        assert_eq!(star_asb_len(b"\x0f\x00\x00\x00\x00\x00\x00"), Some(7));
+        // 第 5 位是长度的最高位：0x2f -> 7 + 8
+        assert_eq!(star_asb_len(&[0x2f; 15]), Some(15));
+        assert_eq!(star_asb_len(&[0x2f; 14]), None);
+        // 头部固定位不对，或长度不足 7
+        assert_eq!(star_asb_len(b"\x33\x00\x00\x00\x00\x00\x00\x00\x00"), None);
+        assert_eq!(star_asb_len(b"\x05\x00\x00"), None);
+        assert_eq!(star_asb_len(b""), None);
+    }
+
+    #[test]
+    fn star_version_maps_to_series() {
+        assert_eq!(parse_star(STAR_ASB).as_deref(), Some("Star TSP143IIIW (TSP100)"));
+        let asb = b"\x0f\x00\x00\x00\x00\x00\x00";
+        let with = |version: &str| [&asb[..], version.as_bytes()].concat();
+        assert_eq!(parse_star(&with("TSP100IV Ver2.0\n\0")).as_deref(), Some("Star TSP100IV"));
+        assert_eq!(parse_star(&with("mcp31l ver1.0")).as_deref(), Some("Star mcp31l (mC-Print)"));
+        assert_eq!(parse_star(&with("XYZ1 Ver1.0")).as_deref(), Some("Star XYZ1"));
+        assert_eq!(parse_star(&with("\0")).as_deref(), Some("Star printer"));
+        assert_eq!(parse_star(b"\x16\x72_EPSON\x00"), None);
+    }
+
+    #[test]
+    fn star_status_prefers_paper_out() {
+        assert_eq!(parse_star_status(STAR_ASB).as_deref(), Some("paper low"));
+        let asb = |b2: u8, b5: u8| [0x0f, 0, b2, 0, 0, b5, 0];
+        assert_eq!(parse_star_status(&asb(0x28, 0x0c)).as_deref(), Some("paper out"));
+        assert_eq!(parse_star_status(&asb(0x28, 0x04)).as_deref(), Some("cover open"));
+        assert_eq!(parse_star_status(&asb(0x08, 0x04)).as_deref(), Some("offline"));
+        assert_eq!(parse_star_status(&asb(0x00, 0x00)).as_deref(), Some("idle"));
+        assert_eq!(parse_star_status(&asb(0x00, 0x00)[..6]), None);
+    }
+
+    #[test]
+    fn label_version_picks_most_specific_prefix() {
+        assert_eq!(parse_label(b"LWW 93089v0J\x00\x20").as_deref(), Some("DYMO LabelWriter Wireless (LWW 93089v0J)"));
+        assert_eq!(parse_label(b"LW550 v1.3").as_deref(), Some("DYMO LabelWriter 550 (LW550 v1.3)"));
+        assert_eq!(parse_label(b"lw4xl 93085v0N\r\n").as_deref(), Some("DYMO LabelWriter 4XL (lw4xl 93085v0N)"));
+        assert_eq!(parse_label(b"LW400 93089v0F").as_deref(), Some("DYMO LabelWriter (LW400 93089v0F)"));
+    }
+
+    #[test]
+    fn seiko_version_is_the_model() {
+        assert_eq!(parse_label(b"SLP650 V1.02\x00").as_deref(), Some("Seiko SLP650 V1.02"));
+    }
+
+    #[test]
+    fn label_ignores_bytes_after_nul() {
+        // NUL 之后的内容 (状态字节或其他设备的回显) 不参与识别
+        assert_eq!(parse_label(b"\x00LWW 93089v0J"), None);
+        assert_eq!(parse_label(b"@PJL INFO ID\r\n"), None);
+        assert_eq!(parse_label(b""), None);
+    }
+
+    #[test]
+    fn label_status_follows_nul() {
+        assert_eq!(parse_label_status(b"LWW 93089v0J\x00\x20").as_deref(), Some("paper out"));
+        assert_eq!(parse_label_status(b"LWW 93089v0J\x00\x21").as_deref(), Some("paper out"));
+        assert_eq!(parse_label_status(b"LWW 93089v0J\x00\x01").as_deref(), Some("printing"));
+        assert_eq!(parse_label_status(b"LWW 93089v0J\x00\x00").as_deref(), Some("idle"));
+        // 设备没有回状态字节
+        assert_eq!(parse_label_status(b"LWW 93089v0J"), None);
+        assert_eq!(parse_label_status(b"LWW 93089v0J\x00"), None);
+    }
+
+    fn found(model: &str, firmware: Option<&str>) -> Option<(String, Option<String>)> {
+        Some((model.to_string(), firmware.map(str::to_string)))
+    }
+
+    #[test]
+    fn ftp_server_name_splits_off_the_version() {
+        assert_eq!(parse_ftp(b"220 KONICA MINOLTA bizhub C454e FTP server (2.30) ready.\r\n"), found("KONICA MINOLTA bizhub C454e", Some("2.30")));
+        assert_eq!(parse_ftp(b"220 Lexmark MX622ade FTP Server Version 2.0.0 ready.\r\n"), found("Lexmark MX622ade", Some("Version 2.0.0")));
+        // 没有版本号时只有型号
+        assert_eq!(parse_ftp(b"220 RICOH MP C3004 FTP server ready\r\n"), found("RICOH MP C3004", None));
+    }
+
+    #[test]
+    fn ftp_banner_without_server_name_is_kept_whole() {
+        // Brother 的欢迎信息不带厂商名，但有 print 字样
+        assert_eq!(parse_ftp(b"220 FTP print service:V-1.13/Use the command HELP.\r\n"), found("FTP print service:V-1.13/Use the command HELP", None));
+        // `FTP server` 前面没有内容时不拆
+        assert_eq!(parse_ftp(b"220 FTP server (HP JetDirect) ready.\r\n"), found("FTP server (HP JetDirect)", None));
+    }
+
+    #[test]
+    fn ftp_multiline_greeting_picks_vendor_line() {
+        let raw = b"220-Welcome to the FTP service\r\n220-Lexmark MX622ade\r\n220 Ready.\r\n";
+        assert_eq!(parse_ftp(raw), found("Lexmark MX622ade", None));
+    }
+
+    #[test]
+    fn ftp_ignores_other_servers() {
+        assert_eq!(parse_ftp(b"220 (vsFTPd 3.0.3)\r\n"), None);
+        assert_eq!(parse_ftp(b"220 Synology DiskStation FTP server ready.\r\n"), None);
+        // 厂商名按单词匹配，Hook 里的 ok 不算 Oki
+        assert_eq!(parse_ftp(b"220 Hook FTP server\r\n"), None);
+        // 只看 220 欢迎行
+        assert_eq!(parse_ftp(b"421 Too many users, HP LaserJet busy\r\n"), None);
+        assert_eq!(parse_ftp(b""), None);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::output::ScanReport;
+use std::fmt::Write as _;
+use std::path::Path;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", "PingFang SC", sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.4em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 6px 12px; text-align: left; }
+th { background: #f3f3f3; }
+#printers th { cursor: pointer; user-select: none; }
+#printers th:hover { background: #e6e6e6; }
+"#;
+
+// 点击表头排序，数字列按数值比较
+const SCRIPT: &str = r#"
+document.querySelectorAll('#printers th').forEach(function (th, col) {
+  th.addEventListener('click', function () {
+    var body = th.closest('table').tBodies[0];
+    var asc = th.dataset.dir !== 'asc';
+    th.dataset.dir = asc ? 'asc' : 'desc';
+    var key = function (tr) { return tr.children[col].dataset.sort || tr.children[col].textContent; };
+    Array.from(body.rows)
+      .sort(function (a, b) {
+        var x = key(a), y = key(b);
+        var r = (!isNaN(x) && !isNaN(y)) ? x - y : x.localeCompare(y);
+        return asc ? r : -r;
+      })
+      .forEach(function (tr) { body.appendChild(tr); });
+  });
+});
+"#;
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 把 IP 转成可按数值排序的键，避免 10.0.0.10 排在 10.0.0.9 前面
+fn ip_sort_key(ip: &std::net::IpAddr) -> String {
+    match ip {
+        std::net::IpAddr::V4(v4) => u32::from(*v4).to_string(),
+        std::net::IpAddr::V6(v6) => u128::from(*v6).to_string(),
+    }
+}
+
+pub fn render_html(report: &ScanReport) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"zh\">\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>打印机扫描报告 - {}</title>", escape(&report.network));
+    let _ = writeln!(html, "<style>{}</style>\n</head>\n<body>", STYLE);
+    let _ = writeln!(html, "<h1>打印机扫描报告</h1>");
+
+    html.push_str("<h2>扫描参数</h2>\n<table>\n");
+    let _ = writeln!(html, "<tr><th>网段</th><td>{}</td></tr>", escape(&report.network));
+    let _ = writeln!(html, "<tr><th>时间</th><td>{}</td></tr>", escape(&report.timestamp));
+    let _ = writeln!(html, "<tr><th>超时 (ms)</th><td>{}</td></tr>", report.timeout_ms);
+    let _ = writeln!(html, "<tr><th>并发数</th><td>{}</td></tr>", report.concurrency);
+    let _ = writeln!(html, "<tr><th>耗时 (ms)</th><td>{}</td></tr>", report.duration_ms);
+    let _ = writeln!(html, "<tr><th>扫描主机</th><td>{}</td></tr>", report.summary.hosts_scanned);
+    let _ = writeln!(html, "<tr><th>端口开放</th><td>{}</td></tr>", report.summary.ports_open);
+    if let Some(avg) = report.summary.avg_response_ms {
+        let _ = writeln!(html, "<tr><th>平均响应 (ms)</th><td>{}</td></tr>", avg);
+    }
+    let _ = writeln!(html, "<tr><th>发现设备</th><td>{}</td></tr>", report.printers.len());
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>识别来源统计</h2>\n<table>\n<tr><th>来源</th><th>数量</th></tr>\n");
+    for (source, count) in &report.summary.by_source {
+        let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", escape(source), count);
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>设备列表</h2>\n<table id=\"printers\">\n<thead><tr>");
+    html.push_str("<th>IP</th><th>主机名</th><th>MAC</th><th>厂商</th><th>型号</th><th>来源</th><th>状态</th><th>响应 (ms)</th>");
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for printer in &report.printers {
+        let _ = writeln!(
+            html,
+            "<tr><td data-sort=\"{}\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            ip_sort_key(&printer.ip),
+            printer.ip,
+            escape(printer.hostname.as_deref().unwrap_or("")),
+            escape(printer.mac.as_deref().unwrap_or("")),
+            escape(printer.vendor.as_deref().unwrap_or("")),
+            escape(&printer.model),
+            escape(&printer.source),
+            escape(printer.status.as_deref().unwrap_or("")),
+            printer.response_ms,
+        );
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    let _ = writeln!(html, "<script>{}</script>\n</body>\n</html>", SCRIPT);
+    html
+}
+
+pub fn write_html_report(path: &Path, report: &ScanReport) -> std::io::Result<()> {
+    std::fs::write(path, render_html(report))
+}
@@ -0,0 +1,16 @@
+use crate::snmp::VendorMib;
+
+/// Ricoh 私有 MIB (企业号 367)：ricohSysProductName、ricohEngSerialNumber 和引擎计数器。
+/// Savin、Lanier、Gestetner、Nashuatec 都是 Ricoh 贴牌
+pub const MIB: VendorMib = VendorMib {
+    vendor: "Ricoh",
+    enterprise: 367,
+    brands: &["ricoh", "aficio", "savin", "lanier", "gestetner", "nashuatec"],
+    model: &[1, 3, 6, 1, 4, 1, 367, 3, 2, 1, 1, 1, 1, 0],
+    serial: &[1, 3, 6, 1, 4, 1, 367, 3, 2, 1, 2, 1, 4, 0],
+    counters: &[
+        ("total", &[1, 3, 6, 1, 4, 1, 367, 3, 2, 1, 2, 19, 1, 0]),
+        ("print", &[1, 3, 6, 1, 4, 1, 367, 3, 2, 1, 2, 19, 2, 0]),
+        ("copy", &[1, 3, 6, 1, 4, 1, 367, 3, 2, 1, 2, 19, 4, 0]),
+    ],
+};
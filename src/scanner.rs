@@ -0,0 +1,308 @@
+use crate::cli::TargetArgs;
+use crate::output::ScanReport;
+use crate::probes::Context;
+use crate::snmp::{AuthProtocol, PrivProtocol, SnmpVersion};
+use crate::targets::Targets;
+use clap::Parser;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+/// 只用来取 `TargetArgs` 在命令行上的默认值，库和命令行的默认行为保持一致
+#[derive(Parser)]
+struct Defaults {
+    #[command(flatten)]
+    target: TargetArgs,
+}
+
+/// `Scanner::scan` 的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanError {
+    /// SNMP 参数有误 (OID 写法、v3 缺少用户名、读不了 community 文件)
+    Snmp(String),
+    /// 扫描目标、排除列表、nmap 结果、网卡或 OUI 文件有误
+    Targets(String),
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::Snmp(e) => f.write_str(e),
+            ScanError::Targets(e) => f.write_str(&tr!(InvalidNetwork, e)),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+/// 在其他程序里直接扫描，不必调用命令行程序：
+/// `Scanner::builder().network("192.168.1.0/24").timeout(Duration::from_secs(2)).concurrency(100).scan().await`。
+/// 未设置的选项与 `printer_scanner scan` 的默认值相同，不显示进度条
+pub struct Scanner {
+    args: TargetArgs,
+}
+
+impl Scanner {
+    pub fn builder() -> ScannerBuilder {
+        ScannerBuilder { args: Defaults::parse_from(["printer_scanner"]).target }
+    }
+
+    /// 扫描全部目标，返回按 IP 排序的打印机和统计
+    pub async fn scan(&self) -> Result<ScanReport, ScanError> {
+        let context = Context::from_args(&self.args)?;
+        let targets = Targets::from_args(&self.args).map_err(ScanError::Targets)?;
+        let timestamp = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+        let started = Instant::now();
+        let (printers, summary) = crate::scan_network(&targets, &self.args, &context, false, None, |_| {}).await;
+        Ok(ScanReport {
+            timestamp,
+            network: targets.label,
+            timeout_ms: self.args.timeout_ms,
+            concurrency: self.args.concurrency,
+            duration_ms: started.elapsed().as_millis() as u64,
+            printers,
+            summary,
+        })
+    }
+}
+
+/// 各设置与 `scan` 子命令的同名选项相同
+pub struct ScannerBuilder {
+    args: TargetArgs,
+}
+
+impl ScannerBuilder {
+    /// 扫描目标，写法同 `-n` (CIDR、地址范围、IP 或主机名，可带端口)，可多次调用。
+    /// 不设置时扫描本机各网卡所在的 IPv4 网段
+    pub fn network(mut self, target: impl Into<String>) -> Self {
+        self.args.network.push(target.into());
+        self
+    }
+
+    /// 从文件读取扫描目标，同 `--targets-file`
+    pub fn targets_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.args.targets_file = Some(path.into());
+        self
+    }
+
+    /// 导入 nmap XML 结果，同 `--import-nmap`
+    pub fn import_nmap(mut self, path: impl Into<PathBuf>) -> Self {
+        self.args.import_nmap = Some(path.into());
+        self
+    }
+
+    /// 目标没有写端口时尝试的端口，按顺序尝试，同 `--ports`
+    pub fn ports(mut self, ports: impl IntoIterator<Item = u16>) -> Self {
+        self.args.ports = ports.into_iter().collect();
+        self
+    }
+
+    /// 跳过的地址，写法同 `--exclude`，可多次调用
+    pub fn exclude(mut self, target: impl Into<String>) -> Self {
+        self.args.exclude.push(target.into());
+        self
+    }
+
+    pub fn exclude_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.args.exclude_file = Some(path.into());
+        self
+    }
+
+    /// 从指定网卡发出探测，同 `--interface`
+    pub fn interface(mut self, name: impl Into<String>) -> Self {
+        self.args.interface = Some(name.into());
+        self
+    }
+
+    /// 每个连接和探测的超时，同 `--timeout-ms`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.args.timeout_ms = timeout.as_millis() as u64;
+        self
+    }
+
+    /// 同时扫描的主机数，同 `--concurrency`
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.args.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn ping_first(mut self, enabled: bool) -> Self {
+        self.args.ping_first = enabled;
+        self
+    }
+
+    pub fn randomize(mut self, enabled: bool) -> Self {
+        self.args.randomize = enabled;
+        self
+    }
+
+    pub fn syn(mut self, enabled: bool) -> Self {
+        self.args.syn = enabled;
+        self
+    }
+
+    /// 运行全部识别协议并给出置信度，同 `--all-probes`
+    pub fn all_probes(mut self, enabled: bool) -> Self {
+        self.args.all_probes = enabled;
+        self
+    }
+
+    /// 反向 DNS 解析，默认开启 (`--no-rdns` 关闭)
+    pub fn reverse_dns(mut self, enabled: bool) -> Self {
+        self.args.no_rdns = !enabled;
+        self
+    }
+
+    /// NetBIOS 名称查询，默认开启 (`--no-netbios` 关闭)
+    pub fn netbios(mut self, enabled: bool) -> Self {
+        self.args.no_netbios = !enabled;
+        self
+    }
+
+    pub fn with_supplies(mut self, enabled: bool) -> Self {
+        self.args.with_supplies = enabled;
+        self
+    }
+
+    pub fn with_trays(mut self, enabled: bool) -> Self {
+        self.args.with_trays = enabled;
+        self
+    }
+
+    pub fn with_telnet(mut self, enabled: bool) -> Self {
+        self.args.with_telnet = enabled;
+        self
+    }
+
+    pub fn with_status(mut self, enabled: bool) -> Self {
+        self.args.with_status = enabled;
+        self
+    }
+
+    pub fn with_config(mut self, enabled: bool) -> Self {
+        self.args.with_config = enabled;
+        self
+    }
+
+    /// 对斑马打印机读取的 SGD 变量，同 `--sgd-vars`
+    pub fn sgd_vars(mut self, vars: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.sgd_vars = vars.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_pjl_info(mut self, enabled: bool) -> Self {
+        self.args.with_pjl_info = enabled;
+        self
+    }
+
+    pub fn with_pjl_fs(mut self, enabled: bool) -> Self {
+        self.args.with_pjl_fs = enabled;
+        self
+    }
+
+    pub fn audit_snmp_write(mut self, enabled: bool) -> Self {
+        self.args.audit_snmp_write = enabled;
+        self
+    }
+
+    /// 完整的 MAC 厂商库，同 `--oui-file`
+    pub fn oui_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.args.oui_file = Some(path.into());
+        self
+    }
+
+    pub fn mdns(mut self, enabled: bool) -> Self {
+        self.args.mdns = enabled;
+        self
+    }
+
+    pub fn wsd(mut self, enabled: bool) -> Self {
+        self.args.wsd = enabled;
+        self
+    }
+
+    pub fn ssdp(mut self, enabled: bool) -> Self {
+        self.args.ssdp = enabled;
+        self
+    }
+
+    pub fn bjnp(mut self, enabled: bool) -> Self {
+        self.args.bjnp = enabled;
+        self
+    }
+
+    /// 组播发现等待响应的时间，同 `--discover-wait`
+    pub fn discover_wait(mut self, wait: Duration) -> Self {
+        self.args.discover_wait = wait.into();
+        self
+    }
+
+    /// SNMP 版本，同 `--snmp`
+    pub fn snmp_version(mut self, version: SnmpVersion) -> Self {
+        self.args.snmp.version = version;
+        self
+    }
+
+    /// v1 / v2c 依次尝试的 community，替换默认的 `public`，同 `--community`
+    pub fn communities(mut self, communities: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.snmp.community = communities.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn community_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.args.snmp.community_file = Some(path.into());
+        self
+    }
+
+    pub fn snmp_port(mut self, port: u16) -> Self {
+        self.args.snmp.snmp_port = port;
+        self
+    }
+
+    /// 每个 SNMP 请求的超时，同 `--snmp-timeout-ms`
+    pub fn snmp_timeout(mut self, timeout: Duration) -> Self {
+        self.args.snmp.snmp_timeout_ms = timeout.as_millis() as u64;
+        self
+    }
+
+    /// 额外读取的 OID，写成 `名称=OID`，可多次调用，同 `--oid`
+    pub fn oid(mut self, spec: impl Into<String>) -> Self {
+        self.args.snmp.oid.push(spec.into());
+        self
+    }
+
+    pub fn oid_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.args.snmp.oid_file = Some(path.into());
+        self
+    }
+
+    /// SNMPv3 用户名，同时把版本设为 v3
+    pub fn snmp_user(mut self, user: impl Into<String>) -> Self {
+        self.args.snmp.version = SnmpVersion::V3;
+        self.args.snmp.snmp_user = Some(user.into());
+        self
+    }
+
+    /// SNMPv3 认证算法和密码，不设置时为 noAuthNoPriv
+    pub fn snmp_auth(mut self, protocol: AuthProtocol, password: impl Into<String>) -> Self {
+        self.args.snmp.snmp_auth_protocol = protocol;
+        self.args.snmp.snmp_auth = Some(password.into());
+        self
+    }
+
+    /// SNMPv3 加密算法和密码，需要同时设置 `snmp_auth`
+    pub fn snmp_priv(mut self, protocol: PrivProtocol, password: impl Into<String>) -> Self {
+        self.args.snmp.snmp_priv_protocol = protocol;
+        self.args.snmp.snmp_priv = Some(password.into());
+        self
+    }
+
+    pub fn build(self) -> Scanner {
+        Scanner { args: self.args }
+    }
+
+    /// 等同于 `build()` 后扫描
+    pub async fn scan(self) -> Result<ScanReport, ScanError> {
+        self.build().scan().await
+    }
+}
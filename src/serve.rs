@@ -0,0 +1,159 @@
+use crate::cli::{ServeArgs, TargetArgs, exit_error};
+use crate::output::ScanReport;
+use crate::probes::Context;
+use crate::targets::Targets;
+use crate::{PrinterInfo, metrics, scan_network};
+use axum::extract::State;
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use colored::*;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Instant, SystemTime};
+use tokio::sync::RwLock;
+use tokio::time::{self, MissedTickBehavior};
+
+struct AppState {
+    targets: Targets,
+    target: TargetArgs,
+    context: Context,
+    /// 最近一次完成的扫描结果
+    last: RwLock<Option<Arc<ScanReport>>>,
+    /// 服务启动以来出现过的所有打印机，离线设备在 /metrics 中导出为 0
+    known: RwLock<BTreeMap<(IpAddr, u16), PrinterInfo>>,
+    /// 同一时间只允许一轮扫描
+    scanning: AtomicBool,
+}
+
+/// 离开作用域时清除 `scanning`，扫描中途 panic 时也会清除，之后的扫描请求不会一直返回 409
+struct ScanGuard(Arc<AppState>);
+
+impl Drop for ScanGuard {
+    fn drop(&mut self) {
+        self.0.scanning.store(false, Ordering::SeqCst);
+    }
+}
+
+/// 后台跑一轮扫描并更新 `last`，调用前需已把 `scanning` 置为 true
+async fn scan_in_background(state: Arc<AppState>) {
+    let _guard = ScanGuard(state.clone());
+    let timestamp = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    let started = Instant::now();
+    let (printers, summary) = scan_network(&state.targets, &state.target, &state.context, false, None, |_| {}).await;
+    status!("{}", tr!(ScanFinished, "✅".green(), printers.len()));
+
+    let report = ScanReport {
+        timestamp,
+        network: state.targets.label.clone(),
+        timeout_ms: state.target.timeout_ms,
+        concurrency: state.target.concurrency,
+        duration_ms: started.elapsed().as_millis() as u64,
+        printers,
+        summary,
+    };
+    {
+        let mut known = state.known.write().await;
+        for printer in &report.printers {
+            known.insert((printer.ip, printer.port), printer.clone());
+        }
+    }
+    *state.last.write().await = Some(Arc::new(report));
+}
+
+/// 尝试启动一轮扫描，已有扫描在进行时返回 false
+fn try_start_scan(state: &Arc<AppState>) -> bool {
+    if state.scanning.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    tokio::spawn(scan_in_background(state.clone()));
+    true
+}
+
+/// POST /scan: 触发一次后台扫描，立即返回
+async fn post_scan(State(state): State<Arc<AppState>>) -> Response {
+    if try_start_scan(&state) {
+        (StatusCode::ACCEPTED, Json(json!({ "status": "started" }))).into_response()
+    } else {
+        (StatusCode::CONFLICT, Json(json!({ "status": "already_running" }))).into_response()
+    }
+}
+
+/// GET /printers: 最近一次扫描的完整结果
+async fn get_printers(State(state): State<Arc<AppState>>) -> Response {
+    match state.last.read().await.as_ref() {
+        Some(report) => Json(report.as_ref()).into_response(),
+        None => (StatusCode::SERVICE_UNAVAILABLE, Json(json!({ "status": "no_scan_yet" }))).into_response(),
+    }
+}
+
+/// GET /metrics: Prometheus 指标
+async fn get_metrics(State(state): State<Arc<AppState>>) -> Response {
+    let Some(report) = state.last.read().await.clone() else {
+        return (StatusCode::SERVICE_UNAVAILABLE, "no scan yet\n").into_response();
+    };
+    let body = metrics::render(&report, &*state.known.read().await);
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response()
+}
+
+pub async fn run_serve(args: ServeArgs) -> ExitCode {
+    let context = match Context::from_args(&args.target) {
+        Ok(c) => c,
+        Err(e) => { eprintln!("{}", e); return exit_error(); }
+    };
+    let targets = match Targets::from_args(&args.target) {
+        Ok(t) => t,
+        Err(e) => { eprintln!("{}", tr!(InvalidNetwork, e)); return exit_error(); }
+    };
+
+    let label = targets.label.clone();
+    let state = Arc::new(AppState {
+        targets,
+        target: args.target,
+        context,
+        last: RwLock::new(None),
+        known: RwLock::new(BTreeMap::new()),
+        scanning: AtomicBool::new(false),
+    });
+
+    match args.every {
+        // 定时重扫，作为 Prometheus exporter 时指标才不会过期。
+        // interval 的第一次 tick 立即触发，相当于启动时扫一轮
+        Some(every) => {
+            let state = state.clone();
+            tokio::spawn(async move {
+                let mut ticker = time::interval(every.into());
+                ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                loop {
+                    ticker.tick().await;
+                    try_start_scan(&state);
+                }
+            });
+        }
+        // 启动时先扫一轮，让 /printers 尽快有数据
+        None => { try_start_scan(&state); }
+    }
+
+    let app = Router::new()
+        .route("/scan", post(post_scan))
+        .route("/printers", get(get_printers))
+        .route("/metrics", get(get_metrics))
+        .with_state(state);
+
+    let listener = match tokio::net::TcpListener::bind(args.listen).await {
+        Ok(l) => l,
+        Err(e) => { eprintln!("{}", tr!(ListenFailed, args.listen, e)); return exit_error(); }
+    };
+    status!("{}", tr!(ServeStarted, "🌐".green(), args.listen, label));
+
+    if let Err(e) = axum::serve(listener, app).await {
+        eprintln!("{}", tr!(ServeFailed, e));
+        return exit_error();
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,890 @@
+use crate::cli::SnmpArgs;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use snmp2::asn1::TYPE_SEQUENCE;
+use snmp2::{AsnReader, AsyncSession, MessageType, Oid, Pdu, Value, v3};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+pub const SNMP_PORT: u16 = 161;
+pub const OID_SYS_DESCR: &[u64] = &[1, 3, 6, 1, 2, 1, 1, 1, 0];
+const OID_SYS_OBJECT_ID: &[u64] = &[1, 3, 6, 1, 2, 1, 1, 2, 0];
+const OID_SYS_UPTIME: &[u64] = &[1, 3, 6, 1, 2, 1, 1, 3, 0];
+const OID_SYS_CONTACT: &[u64] = &[1, 3, 6, 1, 2, 1, 1, 4, 0];
+const OID_SYS_NAME: &[u64] = &[1, 3, 6, 1, 2, 1, 1, 5, 0];
+const OID_SYS_LOCATION: &[u64] = &[1, 3, 6, 1, 2, 1, 1, 6, 0];
+/// 厂商私有 MIB 都挂在 `1.3.6.1.4.1.<企业号>` 下面
+const ENTERPRISES: &[u64] = &[1, 3, 6, 1, 4, 1];
+
+/// IANA 企业号对应的打印机厂商，名称与 `http::vendor_of` 一致
+const ENTERPRISE_VENDORS: &[(u64, &str)] = &[
+    (11, "HP"),
+    (236, "Samsung"),
+    (253, "Xerox"),
+    (297, "Xerox"),
+    (367, "Ricoh"),
+    (641, "Lexmark"),
+    (674, "Dell"),
+    (1129, "Toshiba"),
+    (1248, "Epson"),
+    (1347, "Kyocera"),
+    (1602, "Canon"),
+    (2001, "Oki"),
+    (2385, "Sharp"),
+    (2435, "Brother"),
+    (10642, "Zebra"),
+    (18334, "Konica Minolta"),
+];
+
+/// 默认等待响应的时间 (毫秒)
+pub const SNMP_TIMEOUT_MS: u64 = 1000;
+
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SnmpVersion {
+    /// 只用 v1，适合只懂 v1 的老式打印服务器
+    V1,
+    /// community `public`，超时后再用 v1 重试
+    V2c,
+    /// 用户名 + 认证 / 加密密码 (USM)
+    V3,
+}
+
+impl SnmpVersion {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SnmpVersion::V1 => "v1",
+            SnmpVersion::V2c => "v2c",
+            SnmpVersion::V3 => "v3",
+        }
+    }
+}
+
+/// SNMPv3 认证算法
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthProtocol {
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+/// SNMPv3 加密算法
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrivProtocol {
+    Des,
+    Aes128,
+    Aes192,
+    Aes256,
+}
+
+/// SNMP 请求使用的版本、凭据、端口和超时，由 `--snmp` 等参数构造 (`Client::from_args`)。
+/// 每次扫描各用一份，同一进程中参数不同的几次扫描 (如库里的两个 `Scanner`) 互不影响
+#[derive(Clone)]
+pub struct Client {
+    version: SnmpVersion,
+    /// `--snmp-port` 和 `--snmp-timeout-ms`，部分打印服务器把 SNMP 代理在其他端口上，广域网需要更长的超时
+    port: u16,
+    timeout: Duration,
+    /// v1 / v2c 依次尝试的 community
+    communities: Vec<String>,
+    /// `--snmp v3` 时的 USM 凭据
+    security: Option<v3::Security>,
+    /// `--oid` / `--oid-file` 要额外读取的 OID 及其名称
+    custom_oids: Vec<(String, Vec<u64>)>,
+    /// 每台主机上次回应所用的版本和 community，之后的请求直接用它们，不必每次等前面的候选超时。
+    /// 克隆出的 Client 共用同一份；用它超时后删掉，退回完整的候选列表
+    answered: Arc<Mutex<BTreeMap<IpAddr, Credential>>>,
+}
+
+/// 不带参数时用 v2c、community `public`
+impl Default for Client {
+    fn default() -> Self {
+        Client {
+            version: SnmpVersion::V2c,
+            port: SNMP_PORT,
+            timeout: Duration::from_millis(SNMP_TIMEOUT_MS),
+            communities: vec!["public".to_string()],
+            security: None,
+            custom_oids: Vec::new(),
+            answered: Arc::default(),
+        }
+    }
+}
+
+/// 一次请求使用的版本和 community (v3 时为空)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Credential {
+    pub version: SnmpVersion,
+    pub community: Option<String>,
+}
+
+/// community 文件和 OID 文件，每行一项；community 本身可能带 `#`，只跳过以 `#` 开头的行
+fn read_lines(path: &Path) -> Result<Vec<String>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| tr!(ReadFailed, path.display(), e))?;
+    Ok(text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')).map(str::to_string).collect())
+}
+
+/// `--oid` 指定的 `名称=OID`，如 `color_pages=1.3.6.1.4.1.1347.42.3.1.2.1.1.1.3`，开头的 `.` 可有可无
+fn parse_oid_spec(spec: &str) -> Result<(String, Vec<u64>), String> {
+    let invalid = || tr!(InvalidOidSpec, spec);
+    let (name, oid) = spec.split_once('=').ok_or_else(invalid)?;
+    let oid = parse_oid(oid).ok_or_else(invalid)?;
+    if name.trim().is_empty() {
+        return Err(invalid());
+    }
+    Ok((name.trim().to_string(), oid))
+}
+
+/// 点分形式的 OID，开头的 `.` 可有可无
+pub fn parse_oid(text: &str) -> Option<Vec<u64>> {
+    let oid: Vec<u64> = text.trim().trim_start_matches('.').split('.').map(|n| n.parse().ok()).collect::<Option<_>>()?;
+    (oid.len() >= 2).then_some(oid)
+}
+
+pub fn dotted(oid: &[u64]) -> String {
+    oid.iter().map(u64::to_string).collect::<Vec<_>>().join(".")
+}
+
+impl Client {
+    /// 按命令行参数构造，读取 community 文件和 OID 文件
+    pub fn from_args(args: &SnmpArgs) -> Result<Self, String> {
+        let mut specs = args.oid.clone();
+        if let Some(path) = &args.oid_file {
+            specs.extend(read_lines(path)?);
+        }
+        let custom_oids = specs.iter().map(|s| parse_oid_spec(s)).collect::<Result<_, _>>()?;
+        let (communities, security) = match args.version {
+            SnmpVersion::V3 => (Vec::new(), Some(security(args)?)),
+            _ => {
+                let mut communities: Vec<String> = args.community.iter().filter(|c| !c.is_empty()).cloned().collect();
+                if let Some(path) = &args.community_file {
+                    communities.extend(read_lines(path)?);
+                }
+                let mut seen = BTreeSet::new();
+                communities.retain(|c| seen.insert(c.clone()));
+                (communities, None)
+            }
+        };
+        Ok(Client {
+            version: args.version,
+            port: args.snmp_port,
+            timeout: Duration::from_millis(args.snmp_timeout_ms),
+            communities,
+            security,
+            custom_oids,
+            answered: Arc::default(),
+        })
+    }
+
+    /// 所有 SNMP 请求发往的 UDP 端口
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// 主机回应过的 SNMP 版本和 community，没有回应过或之后超时时返回 None
+    pub fn answered(&self, ip: IpAddr) -> Option<Credential> {
+        self.answered.lock().unwrap().get(&ip).cloned()
+    }
+
+    /// 记下主机回应所用的组合；`answered` 为 false 表示这个组合超时，是记下的那个时删掉
+    fn record(&self, ip: IpAddr, credential: &Credential, answered: bool) {
+        let mut cache = self.answered.lock().unwrap();
+        match answered {
+            true => {
+                cache.insert(ip, credential.clone());
+            }
+            false if cache.get(&ip) == Some(credential) => {
+                cache.remove(&ip);
+            }
+            false => {}
+        }
+    }
+}
+
+/// `--snmp v3` 的 USM 凭据
+fn security(args: &SnmpArgs) -> Result<v3::Security, String> {
+    let user = args.snmp_user.as_deref().ok_or_else(|| tr!(SnmpUserRequired))?;
+    let protocol = match args.snmp_auth_protocol {
+        AuthProtocol::Md5 => v3::AuthProtocol::Md5,
+        AuthProtocol::Sha1 => v3::AuthProtocol::Sha1,
+        AuthProtocol::Sha224 => v3::AuthProtocol::Sha224,
+        AuthProtocol::Sha256 => v3::AuthProtocol::Sha256,
+        AuthProtocol::Sha384 => v3::AuthProtocol::Sha384,
+        AuthProtocol::Sha512 => v3::AuthProtocol::Sha512,
+    };
+    let cipher = match args.snmp_priv_protocol {
+        PrivProtocol::Des => v3::Cipher::Des,
+        PrivProtocol::Aes128 => v3::Cipher::Aes128,
+        PrivProtocol::Aes192 => v3::Cipher::Aes192,
+        PrivProtocol::Aes256 => v3::Cipher::Aes256,
+    };
+    let auth = match (&args.snmp_auth, &args.snmp_priv) {
+        (None, _) => v3::Auth::NoAuthNoPriv,
+        (Some(_), None) => v3::Auth::AuthNoPriv,
+        (Some(_), Some(password)) => v3::Auth::AuthPriv { cipher, privacy_password: password.as_bytes().to_vec() },
+    };
+    let password = args.snmp_auth.as_deref().unwrap_or_default();
+    Ok(v3::Security::new(user.as_bytes(), password.as_bytes()).with_auth_protocol(protocol).with_auth(auth))
+}
+
+/// 打开会话。v3 需要先向设备发现 engine ID 和时间，同样受超时限制
+async fn session(client: &Client, ip: IpAddr, credential: &Credential) -> Option<AsyncSession> {
+    let addr = SocketAddr::new(ip, client.port);
+    let community = credential.community.as_deref().unwrap_or_default().as_bytes();
+    match credential.version {
+        SnmpVersion::V1 => AsyncSession::new_v1(addr, community, 0).await.ok(),
+        SnmpVersion::V2c => AsyncSession::new_v2c(addr, community, 0).await.ok(),
+        SnmpVersion::V3 => {
+            let mut sess = AsyncSession::new_v3(addr, 0, client.security.clone()?).await.ok()?;
+            timeout(client.timeout, sess.init()).await.ok()?.ok()?;
+            Some(sess)
+        }
+    }
+}
+
+/// 依次要尝试的版本和 community：已经回应过的主机先用上次的组合，它超时再试其余的；
+/// 否则按顺序试每个 community，每个 community 的 v2c 没有回应时退回 v1
+fn credentials(client: &Client, ip: IpAddr) -> Vec<Credential> {
+    let versions = match client.version {
+        SnmpVersion::V3 => return vec![Credential { version: SnmpVersion::V3, community: None }],
+        SnmpVersion::V2c => vec![SnmpVersion::V2c, SnmpVersion::V1],
+        SnmpVersion::V1 => vec![SnmpVersion::V1],
+    };
+    let last = client.answered(ip);
+    let all = client.communities.iter().flat_map(|community| versions.iter().map(|&version| Credential { version, community: Some(community.clone()) }));
+    last.clone().into_iter().chain(all.filter(|c| Some(c) != last.as_ref())).collect()
+}
+
+/// 变量值，和会话的接收缓冲区脱钩
+#[derive(Clone, Debug)]
+pub enum Reading {
+    /// OCTET STRING 的原始字节，多数是文本，也有位图 (如 hrPrinterDetectedErrorState)
+    Bytes(Vec<u8>),
+    Number(u64),
+    Oid(Vec<u64>),
+}
+
+impl Reading {
+    pub fn text(&self) -> Option<String> {
+        match self {
+            Reading::Bytes(b) => Some(String::from_utf8_lossy(b).trim_matches(|c: char| c.is_whitespace() || c == '\0').to_string()).filter(|s| !s.is_empty()),
+            Reading::Number(n) => Some(n.to_string()),
+            Reading::Oid(_) => None,
+        }
+    }
+
+    pub fn number(&self) -> Option<u64> {
+        match self {
+            Reading::Number(n) => Some(*n),
+            // 部分机型把计数器做成字符串
+            Reading::Bytes(_) => self.text()?.parse().ok(),
+            Reading::Oid(_) => None,
+        }
+    }
+
+    pub fn bytes(&self) -> Option<&[u8]> {
+        match self {
+            Reading::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+fn reading(value: &Value) -> Option<Reading> {
+    Some(match value {
+        Value::OctetString(bytes) => Reading::Bytes(bytes.to_vec()),
+        Value::Integer(n) => Reading::Number(u64::try_from(*n).ok()?),
+        Value::Counter32(n) | Value::Unsigned32(n) | Value::Timeticks(n) => Reading::Number(*n as u64),
+        Value::Counter64(n) => Reading::Number(*n),
+        Value::ObjectIdentifier(oid) => Reading::Oid(oid.iter()?.collect()),
+        // NoSuchObject / NoSuchInstance 等：设备不支持这个 OID
+        _ => return None,
+    })
+}
+
+/// snmpTrapOID.0：v2c trap 的第二个变量，值为 trap 本身的 OID
+const OID_SNMP_TRAP: &[u64] = &[1, 3, 6, 1, 6, 3, 1, 1, 4, 1, 0];
+/// coldStart 等通用 trap 的前缀，v1 的 generic-trap 0..5 对应其下的 1..6
+const OID_GENERIC_TRAPS: &[u64] = &[1, 3, 6, 1, 6, 3, 1, 1, 5];
+
+/// 收到的一个 trap
+pub struct Trap {
+    pub version: SnmpVersion,
+    /// trap 的 OID，v1 trap 按 RFC 3584 换算成对应的 v2 形式
+    pub oid: Vec<u64>,
+    /// 携带的变量，不含 sysUpTime.0 和 snmpTrapOID.0
+    pub variables: Vec<(Vec<u64>, Reading)>,
+}
+
+/// 解析 v1 / v2c 的 trap 报文；v3 trap、inform 和其他报文返回 None
+pub fn parse_trap(bytes: &[u8]) -> Option<Trap> {
+    let pdu = Pdu::from_bytes(bytes).ok()?;
+    let (version, mut oid) = match (&pdu.message_type, &pdu.v1_trap_info) {
+        (MessageType::TrapV1, Some(info)) => {
+            let oid = match info.generic_trap {
+                // enterpriseSpecific：enterprise.0.specific-trap
+                6 => [info.enterprise.iter()?.collect::<Vec<u64>>(), vec![0, info.specific_trap as u64]].concat(),
+                generic => [OID_GENERIC_TRAPS, &[generic as u64 + 1]].concat(),
+            };
+            (SnmpVersion::V1, Some(oid))
+        }
+        (MessageType::Trap, _) => (SnmpVersion::V2c, None),
+        _ => return None,
+    };
+    let mut variables = Vec::new();
+    for (name, value) in pdu.varbinds {
+        let name: Vec<u64> = name.iter()?.collect();
+        match reading(&value) {
+            Some(Reading::Oid(trap)) if name == OID_SNMP_TRAP => oid = Some(trap),
+            Some(reading) if name.as_slice() != OID_SYS_UPTIME => variables.push((name, reading)),
+            _ => {}
+        }
+    }
+    Some(Trap { version, oid: oid?, variables })
+}
+
+/// 响应超出设备报文大小上限时的 error-status
+const TOO_BIG: u32 = 1;
+/// v1 的 GET 中有一个 OID 不存在时整个请求返回的 error-status，error-index 指出是第几个
+const NO_SUCH_NAME: u32 = 2;
+
+/// 用一个请求读取多个 OID，结果与 `oids` 一一对应。依次尝试 `credentials` 给出的组合，
+/// 回应的版本和 community 记录下来供后续请求和结果使用。
+/// 设备不响应时返回 None，单个 OID 不存在时对应位置为 None。
+/// 设备嫌响应太大 (tooBig) 时对半拆开重发；v1 报 noSuchName 时去掉那个 OID 重发其余的。
+/// 每个请求用一个异步 UDP 会话，不占用阻塞线程，受扫描并发数的限制
+pub async fn get(client: &Client, ip: IpAddr, oids: &[&[u64]]) -> Option<Vec<Option<Reading>>> {
+    let oids: Vec<Oid> = oids.iter().map(|oid| Oid::from(oid)).collect::<Result<_, _>>().ok()?;
+    for credential in credentials(client, ip) {
+        let Some(mut sess) = session(client, ip, &credential).await else { continue };
+        let mut readings = vec![None; oids.len()];
+        let mut pending = vec![(0..oids.len()).collect::<Vec<usize>>()];
+        let mut answered = false;
+        while let Some(mut batch) = pending.pop() {
+            let refs: Vec<&Oid> = batch.iter().map(|&i| &oids[i]).collect();
+            let Ok(Ok(response)) = timeout(client.timeout, sess.get_many(&refs)).await else { break };
+            answered = true;
+            match (response.error_status, response.error_index as usize) {
+                (0, _) => {
+                    for (&i, (_, value)) in batch.iter().zip(response.varbinds) {
+                        readings[i] = reading(&value);
+                    }
+                }
+                (TOO_BIG, _) if batch.len() > 1 => {
+                    let rest = batch.split_off(batch.len() / 2);
+                    pending.extend([batch, rest]);
+                }
+                (NO_SUCH_NAME, index @ 1..) if index <= batch.len() => {
+                    batch.remove(index - 1);
+                    if !batch.is_empty() {
+                        pending.push(batch);
+                    }
+                }
+                _ => {}
+            }
+        }
+        client.record(ip, &credential, answered);
+        if answered {
+            return Some(readings);
+        }
+    }
+    None
+}
+
+/// 不带用户名和 engine ID 的 v3 GET，设备按 RFC 3414 回一个 Report，其中带有自己的 engine ID。
+/// 没有任何凭据也能得到回应，用来发现关掉了 v1 / v2c 但仍可通过 v3 管理的设备
+const ENGINE_DISCOVERY: &[u8] = &[
+    0x30, 0x3a, 0x02, 0x01, 0x03, // SEQUENCE, msgVersion 3
+    0x30, 0x0f, 0x02, 0x02, 0x12, 0x34, 0x02, 0x03, 0x00, 0xff, 0xe3, 0x04, 0x01, 0x04, 0x02, 0x01, 0x03, // msgID、msgMaxSize、reportable、USM
+    0x04, 0x10, 0x30, 0x0e, 0x04, 0x00, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x04, 0x00, 0x04, 0x00, 0x04, 0x00, // 空的 USM 参数
+    0x30, 0x12, 0x04, 0x00, 0x04, 0x00, 0xa0, 0x0c, 0x02, 0x02, 0x12, 0x34, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30, 0x00, // 空 GET
+];
+
+/// engine ID 第 5 个字节的格式，5 以后的内容由厂商自定
+const ENGINE_FORMATS: &[(u8, &str)] = &[(1, "ipv4"), (2, "ipv6"), (3, "mac"), (4, "text"), (5, "octets")];
+
+/// 设备的 SNMPv3 engine ID (RFC 3411 SnmpEngineID)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EngineInfo {
+    /// 十六进制的 engine ID
+    pub id: String,
+    /// 前 4 个字节中的 IANA 企业号
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enterprise: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+    /// 其余部分的格式 (`mac`、`text` 等)，老的 RFC 1910 格式或厂商自定时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// snmpEngineBoots，设备重启的次数
+    pub boots: u64,
+}
+
+fn parse_engine(bytes: &[u8]) -> Option<EngineInfo> {
+    let mut message = AsnReader::from_bytes(AsnReader::from_bytes(bytes).read_raw(TYPE_SEQUENCE).ok()?);
+    if message.read_asn_integer().ok()? != 3 {
+        return None;
+    }
+    message.read_raw(TYPE_SEQUENCE).ok()?;
+    let params = message.read_asn_octetstring().ok()?;
+    let mut usm = AsnReader::from_bytes(AsnReader::from_bytes(params).read_raw(TYPE_SEQUENCE).ok()?);
+    let id = usm.read_asn_octetstring().ok()?;
+    let boots = usm.read_asn_integer().ok()?;
+    if id.is_empty() {
+        return None;
+    }
+    // 最高位为 1 时是 RFC 3411 格式：企业号 + 格式字节；否则是 RFC 1910 的 12 字节格式，前 4 个字节同样是企业号
+    let enterprise = (id.len() >= 4).then(|| u64::from(u32::from_be_bytes([id[0] & 0x7f, id[1], id[2], id[3]])));
+    let format = (id[0] & 0x80 != 0).then(|| id.get(4)).flatten().and_then(|f| ENGINE_FORMATS.iter().find(|(n, _)| n == f)).map(|(_, name)| name.to_string());
+    Some(EngineInfo {
+        id: id.iter().map(|b| format!("{:02x}", b)).collect(),
+        enterprise,
+        vendor: enterprise.and_then(enterprise_vendor).map(str::to_string),
+        format,
+        boots: u64::try_from(boots).unwrap_or_default(),
+    })
+}
+
+/// 发送 v3 engine 发现请求，不需要 v3 凭据。设备不支持 v3 或不回应时返回 None
+pub async fn discover_engine(client: &Client, ip: IpAddr) -> Option<EngineInfo> {
+    let local: IpAddr = if ip.is_ipv4() { Ipv4Addr::UNSPECIFIED.into() } else { Ipv6Addr::UNSPECIFIED.into() };
+    let socket = UdpSocket::bind((local, 0)).await.ok()?;
+    socket.connect(SocketAddr::new(ip, client.port)).await.ok()?;
+    socket.send(ENGINE_DISCOVERY).await.ok()?;
+    let mut buffer = [0; 1500];
+    let n = timeout(client.timeout, socket.recv(&mut buffer)).await.ok()?.ok()?;
+    parse_engine(&buffer[..n])
+}
+
+/// `--audit-snmp-write` 试写的常见 community，排在 `--community` 给出的候选之后
+const WRITE_AUDIT_COMMUNITIES: &[&str] = &["public", "private", "admin", "write", "internal"];
+
+/// 安全审计：找出允许 SET 的 community。先用该 community 读出 sysContact，再把原值写回，
+/// 设备确认 (error-status 为 0) 即视为可写；写入的就是原值，不改动设备配置。
+/// 只检查 v1 / v2c，v3 和不回应 SNMP 的设备返回空列表
+pub async fn writable_communities(client: &Client, ip: IpAddr) -> Vec<String> {
+    let version = client.answered(ip).map(|c| c.version).unwrap_or(SnmpVersion::V2c);
+    if version == SnmpVersion::V3 {
+        return Vec::new();
+    }
+    let Ok(oid) = Oid::from(OID_SYS_CONTACT) else { return Vec::new() };
+    let mut seen = BTreeSet::new();
+    let candidates = client.communities.iter().map(String::as_str).chain(WRITE_AUDIT_COMMUNITIES.iter().copied());
+    let mut writable = Vec::new();
+    for community in candidates.filter(|c| seen.insert(*c)) {
+        let credential = Credential { version, community: Some(community.to_string()) };
+        let Some(mut sess) = session(client, ip, &credential).await else { continue };
+        // 错误的 community 设备通常不回应，读不到就不必试写
+        let contact = match timeout(client.timeout, sess.get_many(&[&oid])).await {
+            Ok(Ok(mut response)) => match response.varbinds.next() {
+                Some((_, Value::OctetString(bytes))) => bytes.to_vec(),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        if let Ok(Ok(response)) = timeout(client.timeout, sess.set(&[(&oid, Value::OctetString(&contact))])).await
+            && response.error_status == 0
+        {
+            writable.push(community.to_string());
+        }
+    }
+    writable
+}
+
+/// sysObjectID 中的企业号
+fn enterprise_of(object_id: Option<&Reading>) -> Option<u64> {
+    match object_id {
+        Some(Reading::Oid(oid)) => oid.strip_prefix(ENTERPRISES).and_then(|rest| rest.first().copied()),
+        _ => None,
+    }
+}
+
+fn enterprise_vendor(enterprise: u64) -> Option<&'static str> {
+    ENTERPRISE_VENDORS.iter().find(|(number, _)| *number == enterprise).map(|(_, vendor)| *vendor)
+}
+
+/// 读取 sysDescr 作为型号。不少机型的 sysDescr 只有固件版本之类的字符串，
+/// 其中没有品牌名时按 sysObjectID 的企业号在前面补上厂商
+pub async fn system_description(client: &Client, ip: IpAddr) -> Option<String> {
+    let values = get(client, ip, &[OID_SYS_DESCR, OID_SYS_OBJECT_ID]).await?;
+    let descr = values[0].as_ref()?.text()?;
+    match enterprise_of(values[1].as_ref()).and_then(enterprise_vendor) {
+        Some(vendor) if crate::http::vendor_of(&descr).is_none() => Some(format!("{} {}", vendor, descr)),
+        _ => Some(descr),
+    }
+}
+
+/// 一台主机的标量 OID 取值。补充信息时用一个 GET 读出，型号、名称、状态、序列号、固件、页数等都从这里解析，
+/// 不再逐项发请求
+pub struct Scalars(BTreeMap<Vec<u64>, Reading>);
+
+impl Scalars {
+    pub fn get(&self, oid: &[u64]) -> Option<&Reading> {
+        self.0.get(oid)
+    }
+
+    pub fn text(&self, oid: &[u64]) -> Option<String> {
+        self.get(oid)?.text()
+    }
+
+    pub fn number(&self, oid: &[u64]) -> Option<u64> {
+        self.get(oid)?.number()
+    }
+}
+
+/// system 组、ENTITY-MIB 的版本号和各页数计数器，`scalars` 总是读取
+const SCALARS: &[&[u64]] = &[
+    OID_SYS_DESCR,
+    OID_SYS_OBJECT_ID,
+    OID_SYS_NAME,
+    OID_SYS_LOCATION,
+    OID_SYS_CONTACT,
+    OID_ENT_FIRMWARE_REV,
+    OID_ENT_SOFTWARE_REV,
+    OID_MARKER_LIFE_COUNT,
+];
+
+/// 用一个 GET 读出 `SCALARS`、各品牌的页数计数器、Printer MIB 的标量 (`printer_mib::SCALARS`) 和 `--oid` 指定的 OID。
+/// 设备不响应时返回 None
+pub async fn scalars(client: &Client, ip: IpAddr) -> Option<Scalars> {
+    let mut oids: Vec<&[u64]> = SCALARS.to_vec();
+    oids.extend(VENDOR_PAGE_COUNTERS.iter().map(|(_, oid)| *oid));
+    oids.extend(crate::printer_mib::SCALARS);
+    oids.extend(client.custom_oids.iter().map(|(_, oid)| oid.as_slice()));
+    let values = get(client, ip, &oids).await?;
+    Some(Scalars(oids.into_iter().zip(values).filter_map(|(oid, value)| Some((oid.to_vec(), value?))).collect()))
+}
+
+/// system 组中管理员填写的名称、位置和联系人
+pub struct SystemInfo {
+    pub name: Option<String>,
+    pub location: Option<String>,
+    pub contact: Option<String>,
+    /// sysObjectID 的企业号对应的厂商
+    pub vendor: Option<&'static str>,
+}
+
+/// sysName、sysLocation 和 sysContact，管理员常在位置里写明楼层和房间
+pub fn system_info(values: &Scalars) -> SystemInfo {
+    SystemInfo {
+        name: values.text(OID_SYS_NAME),
+        location: values.text(OID_SYS_LOCATION),
+        contact: values.text(OID_SYS_CONTACT),
+        vendor: enterprise_of(values.get(OID_SYS_OBJECT_ID)).and_then(enterprise_vendor),
+    }
+}
+
+/// 用户指定的 OID，按名称返回。OID 值写成点分形式，不存在的 OID 不列出
+pub fn custom_oids(client: &Client, values: &Scalars) -> BTreeMap<String, String> {
+    client
+        .custom_oids
+        .iter()
+        .filter_map(|(name, oid)| {
+            let text = match values.get(oid)? {
+                Reading::Oid(oid) => dotted(oid),
+                value => value.text()?,
+            };
+            Some((name.clone(), text))
+        })
+        .collect()
+}
+
+/// ENTITY-MIB 中第一个物理实体的固件版本和软件版本，较新的机型大多支持
+const OID_ENT_FIRMWARE_REV: &[u64] = &[1, 3, 6, 1, 2, 1, 47, 1, 1, 1, 1, 9, 1];
+const OID_ENT_SOFTWARE_REV: &[u64] = &[1, 3, 6, 1, 2, 1, 47, 1, 1, 1, 1, 10, 1];
+
+/// sysDescr 中固件版本前的标记：HP JetDirect 的 `EEPROM JSI24030084`、Lexmark 的 `version NH63.YE.P751`、
+/// Xerox 的 `System Software 073.040.075.34540` 等
+const FIRMWARE_MARKERS: &[&str] = &["eeprom ", "system software ", "firmware ", "fw:", "fw ", "version ", "ver."];
+
+fn firmware_in(descr: &str) -> Option<String> {
+    let lower = descr.to_ascii_lowercase();
+    FIRMWARE_MARKERS.iter().find_map(|marker| {
+        let start = lower.find(marker)? + marker.len();
+        let value: String = descr[start..].trim_start().chars().take_while(|c| !c.is_whitespace() && !matches!(c, ',' | ';' | ')')).collect();
+        value.contains(|c: char| c.is_ascii_digit()).then_some(value)
+    })
+}
+
+/// 固件版本：ENTITY-MIB 的固件 / 软件版本，都没有时从 sysDescr 中找
+pub fn firmware(values: &Scalars) -> Option<String> {
+    values
+        .text(OID_ENT_FIRMWARE_REV)
+        .or_else(|| values.text(OID_ENT_SOFTWARE_REV))
+        .or_else(|| values.text(OID_SYS_DESCR).and_then(|descr| firmware_in(&descr)))
+}
+
+/// IF-MIB 的 ifPhysAddress 列和 IP-MIB 的 ipAdEntIfIndex 列 (行索引为 IPv4 地址的 4 段)
+const OID_IF_PHYS_ADDRESS: &[u64] = &[1, 3, 6, 1, 2, 1, 2, 2, 1, 6];
+const OID_IP_AD_ENT_IF_INDEX: &[u64] = &[1, 3, 6, 1, 2, 1, 4, 20, 1, 2];
+
+/// 设备自己报告的 MAC 地址，跨网段查不到 ARP 时使用。优先取目标地址所在的接口，
+/// 地址表查不到时取第一个以太网地址 (6 字节、非全零)
+pub async fn mac_address(client: &Client, ip: IpAddr) -> Option<String> {
+    let columns = walk_columns(client, ip, &[OID_IP_AD_ENT_IF_INDEX, OID_IF_PHYS_ADDRESS]).await?;
+    let [addresses, interfaces] = &columns[..] else { return None };
+    let ethernet = |value: &Reading| value.bytes().filter(|b| b.len() == 6 && b.iter().any(|&b| b != 0)).map(<[u8]>::to_vec);
+    let own = match ip {
+        IpAddr::V4(v4) => {
+            let row: Vec<u64> = v4.octets().iter().map(|&b| b as u64).collect();
+            addresses.get(&row).and_then(Reading::number).and_then(|index| interfaces.get(&vec![index])).and_then(ethernet)
+        }
+        IpAddr::V6(_) => None,
+    };
+    let mac = own.or_else(|| interfaces.values().find_map(ethernet))?;
+    Some(mac.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":"))
+}
+
+/// Printer MIB 的 prtMarkerLifeCount，第一个打印引擎的累计页数
+const OID_MARKER_LIFE_COUNT: &[u64] = &[1, 3, 6, 1, 2, 1, 43, 10, 2, 1, 4, 1, 1];
+
+/// 常见品牌私有的总页数计数器，比 prtMarkerLifeCount 更接近计费用的数字
+/// (部分机型的 prtMarkerLifeCount 按引擎计数，包括清洁页和卡纸重打)。
+/// Kyocera / Ricoh 的总计数器在 `VENDOR_MIBS` 中，随其他信息一起读取
+const VENDOR_PAGE_COUNTERS: &[(&str, &[u64])] = &[
+    ("HP", &[1, 3, 6, 1, 4, 1, 11, 2, 3, 9, 4, 2, 1, 4, 1, 2, 5, 0]),
+    ("Canon", &[1, 3, 6, 1, 4, 1, 1602, 1, 11, 1, 3, 1, 4, 101]),
+    ("Xerox", &[1, 3, 6, 1, 4, 1, 253, 8, 53, 13, 2, 1, 6, 1, 20, 1]),
+];
+
+/// 累计页数：`vendor` 有私有计数器且读得到时用它，否则用 prtMarkerLifeCount
+pub fn page_count(values: &Scalars, vendor: Option<&str>) -> Option<u64> {
+    VENDOR_PAGE_COUNTERS
+        .iter()
+        .filter(|(v, _)| Some(*v) == vendor)
+        .map(|(_, oid)| *oid)
+        .chain([OID_MARKER_LIFE_COUNT])
+        .find_map(|oid| values.number(oid))
+}
+
+/// 一次遍历中每列最多读取的变量数，防止设备的 GETNEXT 实现有问题时无休止地读下去
+const MAX_WALK: usize = 1000;
+/// GETBULK 每个请求中每列取回的行数
+const BULK_REPETITIONS: u32 = 16;
+
+/// 响应中每个变量的 OID、取值，以及是否为 endOfMibView 之类的结束标记
+fn bindings(response: Pdu<'_>) -> Vec<(Vec<u64>, Option<Reading>, bool)> {
+    response
+        .varbinds
+        .map(|(name, value)| {
+            let end = matches!(value, Value::EndOfMibView | Value::NoSuchObject | Value::NoSuchInstance);
+            (name.iter().map(|n| n.collect()).unwrap_or_default(), reading(&value), end)
+        })
+        .collect()
+}
+
+/// 同时遍历一张表的几列，读出每个 `root` 子树下的所有变量，键为 `root` 之后的部分 (即行索引)。
+/// v2c / v3 用 GETBULK，一个请求取回所有列的后续若干行；v1 不支持 GETBULK，每列逐个 GETNEXT。
+/// 第一个请求和 `get` 一样按候选凭据尝试；设备不响应时返回 None，中途超时返回已读到的部分
+pub async fn walk_columns(client: &Client, ip: IpAddr, roots: &[&[u64]]) -> Option<Vec<BTreeMap<Vec<u64>, Reading>>> {
+    for credential in credentials(client, ip) {
+        let Some(mut sess) = session(client, ip, &credential).await else { continue };
+        let mut columns = vec![BTreeMap::new(); roots.len()];
+        let mut next: Vec<Vec<u64>> = roots.iter().map(|root| root.to_vec()).collect();
+        let mut done = vec![false; roots.len()];
+        let mut answered = false;
+        for _ in 0..MAX_WALK {
+            let active: Vec<usize> = (0..roots.len()).filter(|&i| !done[i]).collect();
+            if active.is_empty() {
+                break;
+            }
+            let oids: Vec<Oid> = active.iter().map(|&i| Oid::from(&next[i])).collect::<Result<_, _>>().ok()?;
+            let received = if credential.version == SnmpVersion::V1 {
+                let mut received = Vec::new();
+                for oid in &oids {
+                    // v1 到达 MIB 末尾时返回 noSuchName 错误，与超时一样当作该列结束
+                    match timeout(client.timeout, sess.getnext(oid)).await {
+                        Ok(Ok(response)) => received.extend(bindings(response).into_iter().take(1)),
+                        _ => received.push((Vec::new(), None, true)),
+                    }
+                }
+                received
+            } else {
+                let refs: Vec<&Oid> = oids.iter().collect();
+                let Ok(Ok(response)) = timeout(client.timeout, sess.getbulk(&refs, 0, BULK_REPETITIONS)).await else { break };
+                bindings(response)
+            };
+            if received.iter().all(|(name, _, _)| name.is_empty()) {
+                break;
+            }
+            answered = true;
+            // GETBULK 的结果按行排列：第一行的各列、第二行的各列……
+            for (k, (name, value, end)) in received.into_iter().enumerate() {
+                let i = active[k % active.len()];
+                let root = roots[i];
+                // 走出子树、到达 MIB 末尾，或设备返回的 OID 没有前进时该列结束
+                if done[i] || end || !name.starts_with(root) || name <= next[i] {
+                    done[i] = true;
+                    continue;
+                }
+                if let Some(reading) = value {
+                    columns[i].insert(name[root.len()..].to_vec(), reading);
+                }
+                done[i] = columns[i].len() >= MAX_WALK;
+                next[i] = name;
+            }
+        }
+        client.record(ip, &credential, answered);
+        if answered {
+            return Some(columns);
+        }
+    }
+    None
+}
+
+/// 厂商私有 MIB 中的型号、序列号和计数器 OID
+pub struct VendorMib {
+    pub vendor: &'static str,
+    /// sysObjectID 中的企业号
+    pub enterprise: u64,
+    /// sysDescr 中出现这些词 (不区分大小写) 也认为是该厂商
+    pub brands: &'static [&'static str],
+    pub model: &'static [u64],
+    pub serial: &'static [u64],
+    /// 计数器名称和 OID
+    pub counters: &'static [(&'static str, &'static [u64])],
+}
+
+/// 从厂商 MIB 读出的信息
+pub struct VendorInfo {
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    pub counters: BTreeMap<String, u64>,
+}
+
+/// 有私有 MIB 补充查询的厂商
+const VENDOR_MIBS: &[&VendorMib] = &[&crate::kyocera::MIB, &crate::ricoh::MIB];
+
+impl VendorMib {
+    fn matches(&self, text: &str) -> bool {
+        let lower = text.to_lowercase();
+        self.brands.iter().any(|b| lower.contains(b))
+    }
+}
+
+/// 按 `scalars` 中 sysObjectID 的企业号 (或 sysDescr 中的品牌名) 认出厂商，再查询它的私有 MIB。
+/// 这些品牌的 sysDescr 常常只有 `KYOCERA Document Solutions Printing System` 之类的通用字符串
+pub async fn vendor_info(client: &Client, ip: IpAddr, scalars: &Scalars) -> Option<(&'static str, VendorInfo)> {
+    let descr = scalars.text(OID_SYS_DESCR).unwrap_or_default();
+    let enterprise = enterprise_of(scalars.get(OID_SYS_OBJECT_ID));
+    let mib = VENDOR_MIBS.iter().find(|mib| enterprise == Some(mib.enterprise) || mib.matches(&descr))?;
+    let mut oids = vec![mib.model, mib.serial];
+    oids.extend(mib.counters.iter().map(|(_, oid)| *oid));
+    let values = get(client, ip, &oids).await?;
+    let counters = mib
+        .counters
+        .iter()
+        .zip(&values[2..])
+        .filter_map(|((name, _), value)| Some((name.to_string(), value.as_ref()?.number()?)))
+        .collect();
+    Some((
+        mib.vendor,
+        VendorInfo {
+            model: values[0].as_ref().and_then(Reading::text),
+            serial: values[1].as_ref().and_then(Reading::text),
+            counters,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        [&[tag, content.len() as u8][..], content].concat()
+    }
+
+    /// 设备对发现请求回的 Report：只填 USM 参数里的 engine ID 和 boots
+    fn report(version: u8, engine_id: &[u8], boots: &[u8]) -> Vec<u8> {
+        let header = tlv(0x30, &[tlv(0x02, &[0x12, 0x34]), tlv(0x02, &[0x00, 0xff, 0xe3]), tlv(0x04, &[0x00]), tlv(0x02, &[0x03])].concat());
+        let usm = tlv(
+            0x30,
+            &[tlv(0x04, engine_id), tlv(0x02, boots), tlv(0x02, &[0x30, 0x39]), tlv(0x04, b""), tlv(0x04, b""), tlv(0x04, b"")].concat(),
+        );
+        let pdu = tlv(0xa8, &[tlv(0x02, &[0x12, 0x34]), tlv(0x02, &[0]), tlv(0x02, &[0]), tlv(0x30, b"")].concat());
+        let scoped = tlv(0x30, &[tlv(0x04, engine_id), tlv(0x04, b""), pdu].concat());
+        tlv(0x30, &[tlv(0x02, &[version]), header, tlv(0x04, &usm), scoped].concat())
+    }
+
+    #[test]
+    fn rfc3411_engine_id_names_vendor_and_format() {
+        let id = [0x80, 0x00, 0x00, 0x0b, 0x03, 0x00, 0x25, 0x07, 0xaa, 0xbb, 0xcc];
+        let engine = parse_engine(&report(3, &id, &[0x07])).unwrap();
+        assert_eq!(engine.id, "8000000b03002507aabbcc");
+        assert_eq!((engine.enterprise, engine.vendor.as_deref(), engine.format.as_deref()), (Some(11), Some("HP"), Some("mac")));
+        assert_eq!(engine.boots, 7);
+    }
+
+    #[test]
+    fn rfc1910_engine_id_has_no_format() {
+        // 最高位为 0：企业号后面是 8 个字节的厂商数据，第 5 个字节不是格式
+        let id = [0x00, 0x00, 0x02, 0x81, 0x03, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01];
+        let engine = parse_engine(&report(3, &id, &[0x01, 0x00])).unwrap();
+        assert_eq!((engine.enterprise, engine.vendor.as_deref(), engine.format), (Some(641), Some("Lexmark"), None));
+        assert_eq!(engine.boots, 256);
+    }
+
+    #[test]
+    fn unknown_format_and_short_ids() {
+        let engine = parse_engine(&report(3, &[0x80, 0x00, 0x1f, 0x88, 0x80, 0x01], &[0x00])).unwrap();
+        assert_eq!((engine.enterprise, engine.vendor, engine.format), (Some(8072), None, None));
+        let engine = parse_engine(&report(3, &[0x80, 0x01], &[0x00])).unwrap();
+        assert_eq!(engine.enterprise, None);
+    }
+
+    #[test]
+    fn only_v3_reports_with_an_engine_id() {
+        // 发现请求本身的 engine ID 是空的
+        assert_eq!(parse_engine(ENGINE_DISCOVERY), None);
+        assert_eq!(parse_engine(&report(3, b"", &[0x00])), None);
+        assert_eq!(parse_engine(&report(1, &[0x80, 0x00, 0x00, 0x0b, 0x04], &[0x00])), None);
+        let full = report(3, &[0x80, 0x00, 0x00, 0x0b, 0x04, b'h', b'p'], &[0x00]);
+        assert_eq!(parse_engine(&full[..20]), None);
+    }
+
+    #[test]
+    fn scalars_prefer_vendor_counters_and_entity_firmware() {
+        let mut values = Scalars(BTreeMap::from([
+            (OID_SYS_DESCR.to_vec(), Reading::Bytes(b"HP ETHERNET MULTI-ENVIRONMENT,ROM none,JETDIRECT,JD153,EEPROM JSI24030084".to_vec())),
+            (OID_MARKER_LIFE_COUNT.to_vec(), Reading::Number(1200)),
+            (VENDOR_PAGE_COUNTERS[0].1.to_vec(), Reading::Bytes(b"1234".to_vec())),
+        ]));
+        assert_eq!(page_count(&values, Some("HP")), Some(1234));
+        assert_eq!(page_count(&values, Some("Canon")), Some(1200));
+        assert_eq!(firmware(&values).as_deref(), Some("JSI24030084"));
+        values.0.insert(OID_ENT_SOFTWARE_REV.to_vec(), Reading::Bytes(b"2.63.1".to_vec()));
+        assert_eq!(firmware(&values).as_deref(), Some("2.63.1"));
+        assert_eq!(system_info(&values).name, None);
+    }
+
+    #[test]
+    fn answered_credential_goes_first_until_it_times_out() {
+        let client = Client::default();
+        let ip: IpAddr = Ipv4Addr::new(10, 0, 0, 5).into();
+        let (v2c, v1) = (
+            Credential { version: SnmpVersion::V2c, community: Some("public".to_string()) },
+            Credential { version: SnmpVersion::V1, community: Some("public".to_string()) },
+        );
+        assert_eq!(credentials(&client, ip), [v2c.clone(), v1.clone()]);
+        client.record(ip, &v1, true);
+        assert_eq!(credentials(&client, ip), [v1.clone(), v2c.clone()]);
+        // 另一个 Client 有自己的记录，克隆出的共用一份
+        assert_eq!(Client::default().answered(ip), None);
+        assert_eq!(client.clone().answered(ip), Some(v1.clone()));
+        // 其他组合超时不影响记下的那个
+        client.record(ip, &v2c, false);
+        assert_eq!(client.answered(ip), Some(v1.clone()));
+        client.record(ip, &v1, false);
+        assert_eq!(client.answered(ip), None);
+        assert_eq!(credentials(&client, ip), [v2c, v1]);
+    }
+}
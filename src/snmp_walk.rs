@@ -0,0 +1,335 @@
+use crate::cli::{SnmpWalkArgs, exit_error};
+use crate::snmp::{self, Reading};
+use crate::output;
+use colored::*;
+use serde::Serialize;
+use std::io::Write;
+use std::process::ExitCode;
+
+/// Printer MIB (RFC 3805) 和 Host Resources MIB (RFC 2790)，不指定起点时两棵子树都遍历
+const PRINTER_MIB: &[u64] = &[1, 3, 6, 1, 2, 1, 43];
+const HOST_RESOURCES_MIB: &[u64] = &[1, 3, 6, 1, 2, 1, 25];
+
+/// 一张表：表名、列名前缀、表项 (Entry) 的 OID，以及从第 1 列起的列名 (拼在前缀后面)。
+/// hrSystem 是标量组，没有表名
+struct Table {
+    table: Option<&'static str>,
+    prefix: &'static str,
+    entry: &'static [u64],
+    columns: &'static [&'static str],
+}
+
+/// 没有细分到列的子树
+const GROUPS: &[(&str, &[u64])] = &[
+    ("printmib", PRINTER_MIB),
+    ("host", HOST_RESOURCES_MIB),
+    ("hrSystem", &[1, 3, 6, 1, 2, 1, 25, 1]),
+    ("hrStorage", &[1, 3, 6, 1, 2, 1, 25, 2]),
+    ("hrMemorySize", &[1, 3, 6, 1, 2, 1, 25, 2, 2]),
+    ("hrDevice", &[1, 3, 6, 1, 2, 1, 25, 3]),
+    ("hrSWRun", &[1, 3, 6, 1, 2, 1, 25, 4]),
+    ("hrSWRunPerf", &[1, 3, 6, 1, 2, 1, 25, 5]),
+    ("hrSWInstalled", &[1, 3, 6, 1, 2, 1, 25, 6]),
+];
+
+const TABLES: &[Table] = &[
+    Table {
+        table: None,
+        prefix: "hrSystem",
+        entry: &[1, 3, 6, 1, 2, 1, 25, 1],
+        columns: &["Uptime", "Date", "InitialLoadDevice", "InitialLoadParameters", "NumUsers", "Processes", "MaxProcesses"],
+    },
+    Table {
+        table: Some("hrStorageTable"),
+        prefix: "hrStorage",
+        entry: &[1, 3, 6, 1, 2, 1, 25, 2, 3, 1],
+        columns: &["Index", "Type", "Descr", "AllocationUnits", "Size", "Used", "AllocationFailures"],
+    },
+    Table { table: Some("hrDeviceTable"), prefix: "hrDevice", entry: &[1, 3, 6, 1, 2, 1, 25, 3, 2, 1], columns: &["Index", "Type", "Descr", "ID", "Status", "Errors"] },
+    Table { table: Some("hrProcessorTable"), prefix: "hrProcessor", entry: &[1, 3, 6, 1, 2, 1, 25, 3, 3, 1], columns: &["FrwID", "Load"] },
+    Table { table: Some("hrNetworkTable"), prefix: "hrNetwork", entry: &[1, 3, 6, 1, 2, 1, 25, 3, 4, 1], columns: &["IfIndex"] },
+    Table { table: Some("hrPrinterTable"), prefix: "hrPrinter", entry: &[1, 3, 6, 1, 2, 1, 25, 3, 5, 1], columns: &["Status", "DetectedErrorState"] },
+    Table {
+        table: Some("prtGeneralTable"),
+        prefix: "prt",
+        entry: &[1, 3, 6, 1, 2, 1, 43, 5, 1, 1],
+        columns: &[
+            "GeneralConfigChanges",
+            "GeneralCurrentLocalization",
+            "GeneralReset",
+            "GeneralCurrentOperator",
+            "GeneralServicePerson",
+            "InputDefaultIndex",
+            "OutputDefaultIndex",
+            "MarkerDefaultIndex",
+            "MediaPathDefaultIndex",
+            "ConsoleLocalization",
+            "ConsoleNumberOfDisplayLines",
+            "ConsoleNumberOfDisplayChars",
+            "ConsoleDisable",
+            "AuxiliarySheetStartupPage",
+            "AuxiliarySheetBannerPage",
+            "GeneralPrinterName",
+            "GeneralSerialNumber",
+            "AlertCriticalEvents",
+            "AlertAllEvents",
+        ],
+    },
+    Table { table: Some("prtCoverTable"), prefix: "prtCover", entry: &[1, 3, 6, 1, 2, 1, 43, 6, 1, 1], columns: &["Index", "Description", "Status"] },
+    Table { table: Some("prtLocalizationTable"), prefix: "prtLocalization", entry: &[1, 3, 6, 1, 2, 1, 43, 7, 1, 1], columns: &["Index", "Language", "Country", "CharacterSet"] },
+    Table {
+        table: Some("prtInputTable"),
+        prefix: "prtInput",
+        entry: &[1, 3, 6, 1, 2, 1, 43, 8, 2, 1],
+        columns: &[
+            "Index",
+            "Type",
+            "DimUnit",
+            "MediaDimFeedDirDeclared",
+            "MediaDimXFeedDirDeclared",
+            "MediaDimFeedDirChosen",
+            "MediaDimXFeedDirChosen",
+            "CapacityUnit",
+            "MaxCapacity",
+            "CurrentLevel",
+            "Status",
+            "MediaName",
+            "Name",
+            "VendorName",
+            "Model",
+            "Version",
+            "SerialNumber",
+            "Description",
+            "Security",
+            "MediaWeight",
+            "MediaType",
+            "MediaColor",
+            "MediaFormParts",
+        ],
+    },
+    Table {
+        table: Some("prtOutputTable"),
+        prefix: "prtOutput",
+        entry: &[1, 3, 6, 1, 2, 1, 43, 9, 2, 1],
+        columns: &[
+            "Index",
+            "Type",
+            "CapacityUnit",
+            "MaxCapacity",
+            "RemainingCapacity",
+            "Status",
+            "Name",
+            "VendorName",
+            "Model",
+            "Version",
+            "SerialNumber",
+            "Description",
+            "Security",
+            "DimUnit",
+            "MaxDimFeedDir",
+            "MaxDimXFeedDir",
+            "MinDimFeedDir",
+            "MinDimXFeedDir",
+            "StackingOrder",
+            "PageDeliveryOrientation",
+            "Bursting",
+            "Decollating",
+            "PageCollated",
+            "OffsetStacking",
+        ],
+    },
+    Table {
+        table: Some("prtMarkerTable"),
+        prefix: "prtMarker",
+        entry: &[1, 3, 6, 1, 2, 1, 43, 10, 2, 1],
+        columns: &[
+            "Index",
+            "MarkTech",
+            "CounterUnit",
+            "LifeCount",
+            "PowerOnCount",
+            "ProcessColorants",
+            "SpotColorants",
+            "AddressabilityUnit",
+            "AddressabilityFeedDir",
+            "AddressabilityXFeedDir",
+            "NorthMargin",
+            "SouthMargin",
+            "WestMargin",
+            "EastMargin",
+            "Status",
+        ],
+    },
+    Table {
+        table: Some("prtMarkerSuppliesTable"),
+        prefix: "prtMarkerSupplies",
+        entry: &[1, 3, 6, 1, 2, 1, 43, 11, 1, 1],
+        columns: &["Index", "MarkerIndex", "ColorantIndex", "Class", "Type", "Description", "SupplyUnit", "MaxCapacity", "Level"],
+    },
+    Table {
+        table: Some("prtMarkerColorantTable"),
+        prefix: "prtMarkerColorant",
+        entry: &[1, 3, 6, 1, 2, 1, 43, 12, 1, 1],
+        columns: &["Index", "MarkerIndex", "Role", "Value", "Tonality"],
+    },
+    Table {
+        table: Some("prtMediaPathTable"),
+        prefix: "prtMediaPath",
+        entry: &[1, 3, 6, 1, 2, 1, 43, 13, 4, 1],
+        columns: &[
+            "Index",
+            "MaxSpeedPrintUnit",
+            "MediaSizeUnit",
+            "MaxSpeed",
+            "MaxMediaFeedDir",
+            "MaxMediaXFeedDir",
+            "MinMediaFeedDir",
+            "MinMediaXFeedDir",
+            "Type",
+            "Description",
+            "Status",
+        ],
+    },
+    Table {
+        table: Some("prtChannelTable"),
+        prefix: "prtChannel",
+        entry: &[1, 3, 6, 1, 2, 1, 43, 14, 1, 1],
+        columns: &[
+            "Index",
+            "Type",
+            "ProtocolVersion",
+            "CurrentJobCntlLangIndex",
+            "DefaultPageDescLangIndex",
+            "State",
+            "IfIndex",
+            "Status",
+            "Information",
+        ],
+    },
+    Table {
+        table: Some("prtInterpreterTable"),
+        prefix: "prtInterpreter",
+        entry: &[1, 3, 6, 1, 2, 1, 43, 15, 1, 1],
+        columns: &[
+            "Index",
+            "LangFamily",
+            "LangLevel",
+            "LangVersion",
+            "Description",
+            "Version",
+            "DefaultOrientation",
+            "FeedAddressability",
+            "XFeedAddressability",
+            "DefaultCharSetIn",
+            "DefaultCharSetOut",
+            "TwoWay",
+        ],
+    },
+    Table { table: Some("prtConsoleDisplayBufferTable"), prefix: "prtConsoleDisplayBuffer", entry: &[1, 3, 6, 1, 2, 1, 43, 16, 5, 1], columns: &["Index", "Text"] },
+    Table { table: Some("prtConsoleLightTable"), prefix: "prtConsole", entry: &[1, 3, 6, 1, 2, 1, 43, 17, 6, 1], columns: &["LightIndex", "OnTime", "OffTime", "Color", "Description"] },
+    Table {
+        table: Some("prtAlertTable"),
+        prefix: "prtAlert",
+        entry: &[1, 3, 6, 1, 2, 1, 43, 18, 1, 1],
+        columns: &["Index", "SeverityLevel", "TrainingLevel", "Group", "GroupIndex", "Location", "Code", "Description", "Time"],
+    },
+];
+
+/// 所有已知的名称和 OID：子树、表 (`prtInputTable`) 和列 (`prtInputMaxCapacity`)
+fn names() -> Vec<(String, Vec<u64>)> {
+    let mut names: Vec<(String, Vec<u64>)> = GROUPS.iter().map(|(name, oid)| (name.to_string(), oid.to_vec())).collect();
+    for table in TABLES {
+        if let Some(name) = table.table {
+            names.push((name.to_string(), table.entry[..table.entry.len() - 1].to_vec()));
+        }
+        for (i, column) in table.columns.iter().enumerate() {
+            names.push((format!("{}{}", table.prefix, column), [table.entry, &[i as u64 + 1]].concat()));
+        }
+    }
+    names
+}
+
+/// 按最长前缀把 OID 写成 `prtMarkerSuppliesLevel.1.1`，不认识的写成点分形式
+pub fn friendly_name(oid: &[u64]) -> String {
+    name_in(&names(), oid)
+}
+
+fn name_in(names: &[(String, Vec<u64>)], oid: &[u64]) -> String {
+    match names.iter().filter(|(_, prefix)| oid.starts_with(prefix)).max_by_key(|(_, prefix)| prefix.len()) {
+        Some((name, prefix)) if prefix.len() == oid.len() => name.clone(),
+        Some((name, prefix)) => format!("{}.{}", name, snmp::dotted(&oid[prefix.len()..])),
+        None => snmp::dotted(oid),
+    }
+}
+
+/// 文本原样输出，含控制字符的字节串 (错误状态位图、MAC 地址等) 用十六进制
+pub fn value_text(value: &Reading) -> String {
+    match value {
+        Reading::Bytes(bytes) => match std::str::from_utf8(bytes) {
+            Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => text.trim_end_matches('\0').to_string(),
+            _ => format!("0x{}", bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>()),
+        },
+        Reading::Number(n) => n.to_string(),
+        Reading::Oid(oid) => snmp::dotted(oid),
+    }
+}
+
+#[derive(Serialize)]
+struct WalkEntry {
+    oid: String,
+    name: String,
+    value: String,
+}
+
+/// 遍历 Printer MIB / Host Resources MIB 中的一棵子树，按友好名称列出每个变量。
+/// 起点可以是名称 (`prtMarkerSuppliesTable`) 或点分 OID，必须在这两个 MIB 之内
+pub async fn run_snmpwalk(args: SnmpWalkArgs) -> ExitCode {
+    let client = match snmp::Client::from_args(&args.snmp) {
+        Ok(client) => client,
+        Err(e) => { eprintln!("{}", e); return exit_error(); }
+    };
+    let names = names();
+    let roots: Vec<Vec<u64>> = match &args.base {
+        None => vec![PRINTER_MIB.to_vec(), HOST_RESOURCES_MIB.to_vec()],
+        Some(base) => {
+            let oid = names.iter().find(|(name, _)| name.eq_ignore_ascii_case(base)).map(|(_, oid)| oid.clone()).or_else(|| snmp::parse_oid(base));
+            match oid {
+                Some(oid) if oid.starts_with(PRINTER_MIB) || oid.starts_with(HOST_RESOURCES_MIB) => vec![oid],
+                Some(_) => { eprintln!("{}", tr!(SnmpWalkOutOfScope, base)); return exit_error(); }
+                None => { eprintln!("{}", tr!(InvalidOid, base)); return exit_error(); }
+            }
+        }
+    };
+    let mut out = match output::open_output(args.output.as_deref()) {
+        Ok(out) => out,
+        Err(e) => { eprintln!("{}", tr!(CreateOutputFailed, e)); return exit_error(); }
+    };
+
+    status!("{}", tr!(SnmpWalking, "🔍".green(), args.ip));
+    let refs: Vec<&[u64]> = roots.iter().map(Vec::as_slice).collect();
+    let Some(columns) = snmp::walk_columns(&client, args.ip, &refs).await else {
+        eprintln!("{}", tr!(SnmpNoAnswer, args.ip));
+        return exit_error();
+    };
+    let entries: Vec<WalkEntry> = roots
+        .iter()
+        .zip(columns)
+        .flat_map(|(root, rows)| {
+            rows.into_iter().map(|(suffix, value)| {
+                let oid = [root.as_slice(), &suffix].concat();
+                WalkEntry { name: name_in(&names, &oid), oid: snmp::dotted(&oid), value: value_text(&value) }
+            })
+        })
+        .collect();
+
+    let written = if args.json {
+        serde_json::to_writer_pretty(&mut out, &entries).map_err(std::io::Error::from).and_then(|_| writeln!(out))
+    } else {
+        entries.iter().try_for_each(|e| writeln!(out, "{} = {}", e.name.bold(), e.value))
+    };
+    if let Err(e) = written.and_then(|_| out.flush()) {
+        eprintln!("{}", tr!(WriteFailed, e));
+        return exit_error();
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,95 @@
+use crate::discovery::{Discovered, xml_text};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::{Instant, timeout_at};
+
+const SSDP_PORT: u16 = 1900;
+const SSDP_MULTICAST_V4: Ipv4Addr = Ipv4Addr::new(239, 255, 255, 250);
+const SEARCH_TARGET: &str = "urn:schemas-upnp-org:device:Printer:1";
+
+fn search_message(wait: Duration) -> String {
+    // MX 是设备随机延迟响应的上限 (秒)，不超过等待时间
+    let mx = wait.as_secs().clamp(1, 5);
+    format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: {}:{}\r\nMAN: \"ssdp:discover\"\r\nMX: {}\r\nST: {}\r\n\r\n",
+        SSDP_MULTICAST_V4, SSDP_PORT, mx, SEARCH_TARGET
+    )
+}
+
+/// 取 HTTP 风格响应中的头部值，名称不区分大小写
+fn header<'a>(response: &'a str, name: &str) -> Option<&'a str> {
+    response.lines().skip(1).find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+/// 下载 LOCATION 指向的设备描述 XML，从中取友好名称和型号
+async fn to_discovered(client: &reqwest::Client, ip: IpAddr, location: String, server: Option<String>) -> Discovered {
+    let url: Option<reqwest::Url> = location.parse().ok();
+    let description = match &url {
+        Some(url) => match client.get(url.clone()).send().await {
+            Ok(resp) => resp.text().await.ok(),
+            Err(_) => None,
+        },
+        None => None,
+    };
+    let description = description.as_deref().unwrap_or("");
+    let model = xml_text(description, "modelName").map(|model| match xml_text(description, "manufacturer") {
+        Some(mfg) if !model.starts_with(&mfg) => format!("{} {}", mfg, model),
+        _ => model,
+    });
+
+    let mut txt = BTreeMap::new();
+    txt.insert("location".to_string(), location);
+    if let Some(server) = server {
+        txt.insert("server".to_string(), server);
+    }
+    Discovered {
+        ip,
+        port: url.and_then(|u| u.port_or_known_default()).unwrap_or(80),
+        source: "SSDP",
+        name: xml_text(description, "friendlyName"),
+        model,
+        txt,
+    }
+}
+
+/// 向 239.255.255.250:1900 组播 M-SEARCH 查找 UPnP 打印机，在 `wait` 时间内收集响应，
+/// 再逐台下载设备描述
+pub async fn search(wait: Duration) -> Vec<Discovered> {
+    let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await {
+        Ok(s) => s,
+        Err(e) => { eprintln!("{}", tr!(SsdpFailed, e)); return Vec::new(); }
+    };
+    let target = SocketAddr::new(IpAddr::V4(SSDP_MULTICAST_V4), SSDP_PORT);
+    // UDP 可能丢包，UPnP 规范建议重复发送
+    for _ in 0..2 {
+        if let Err(e) = socket.send_to(search_message(wait).as_bytes(), target).await {
+            eprintln!("{}", tr!(SsdpFailed, e));
+            return Vec::new();
+        }
+    }
+
+    let deadline = Instant::now() + wait;
+    let mut responses: BTreeMap<IpAddr, (String, Option<String>)> = BTreeMap::new();
+    let mut buf = vec![0u8; 8192];
+    while let Ok(Ok((n, from))) = timeout_at(deadline, socket.recv_from(&mut buf)).await {
+        let response = String::from_utf8_lossy(&buf[..n]);
+        if !response.starts_with("HTTP/1.1 200") {
+            continue;
+        }
+        if let Some(location) = header(&response, "LOCATION") {
+            let server = header(&response, "SERVER").map(str::to_string);
+            responses.entry(from.ip()).or_insert((location.to_string(), server));
+        }
+    }
+
+    let Ok(client) = reqwest::Client::builder().timeout(wait.max(Duration::from_secs(2))).build() else {
+        return Vec::new();
+    };
+    let lookups = responses.into_iter().map(|(ip, (location, server))| to_discovered(&client, ip, location, server));
+    futures::future::join_all(lookups).await
+}
@@ -0,0 +1,191 @@
+use crate::targets::Host;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::time::{Duration, Instant};
+
+const TCP_SYN: u8 = 0x02;
+const TCP_RST: u8 = 0x04;
+const TCP_ACK: u8 = 0x10;
+
+/// 每发送这么多个 SYN 就收一次包，避免响应堆满接收缓冲区
+const BATCH: usize = 64;
+
+/// 用来区分本次扫描的响应：序号由目标地址、端口和每次运行不同的密钥算出，
+/// SYN-ACK 的确认号必须等于它加一
+fn cookie(secret: u32, ip: Ipv4Addr, port: u16) -> u32 {
+    let mut h = secret ^ 0x811c_9dc5;
+    for b in ip.octets().into_iter().chain(port.to_be_bytes()) {
+        h = (h ^ b as u32).wrapping_mul(0x0100_0193);
+    }
+    h
+}
+
+fn checksum(data: &[u8], mut sum: u32) -> u16 {
+    for chunk in data.chunks(2) {
+        sum += u16::from_be_bytes([chunk[0], *chunk.get(1).unwrap_or(&0)]) as u32;
+    }
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// 带 MSS 选项的 SYN 报文段 (24 字节)，IP 头由内核填写
+fn syn_segment(src: Ipv4Addr, dst: Ipv4Addr, sport: u16, dport: u16, seq: u32) -> [u8; 24] {
+    let mut seg = [0u8; 24];
+    seg[0..2].copy_from_slice(&sport.to_be_bytes());
+    seg[2..4].copy_from_slice(&dport.to_be_bytes());
+    seg[4..8].copy_from_slice(&seq.to_be_bytes());
+    seg[12] = 6 << 4;
+    seg[13] = TCP_SYN;
+    seg[14..16].copy_from_slice(&1024u16.to_be_bytes());
+    seg[20..24].copy_from_slice(&[0x02, 0x04, 0x05, 0xb4]);
+    // 校验和覆盖伪首部：源地址、目的地址、协议号、TCP 长度
+    let mut pseudo = 0u32;
+    for word in [src.octets(), dst.octets()].concat().chunks(2) {
+        pseudo += u16::from_be_bytes([word[0], word[1]]) as u32;
+    }
+    pseudo += 6 + seg.len() as u32;
+    let sum = checksum(&seg, pseudo);
+    seg[16..18].copy_from_slice(&sum.to_be_bytes());
+    seg
+}
+
+/// 系统发往 `dst` 时会用的源地址，用于计算校验和
+fn source_for(dst: Ipv4Addr) -> Option<Ipv4Addr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((dst, 9)).ok()?;
+    match socket.local_addr().ok()?.ip() {
+        IpAddr::V4(ip) => Some(ip),
+        IpAddr::V6(_) => None,
+    }
+}
+
+/// 解析收到的 IPv4 + TCP 包，返回 (来源地址, 来源端口, 目的端口, 确认号, 标志)
+fn parse_reply(packet: &[u8]) -> Option<(Ipv4Addr, u16, u16, u32, u8)> {
+    let ihl = (*packet.first()? & 0x0f) as usize * 4;
+    if packet.get(9) != Some(&6) {
+        return None;
+    }
+    let src = Ipv4Addr::from(<[u8; 4]>::try_from(packet.get(12..16)?).ok()?);
+    let tcp = packet.get(ihl..ihl + 20)?;
+    let sport = u16::from_be_bytes([tcp[0], tcp[1]]);
+    let dport = u16::from_be_bytes([tcp[2], tcp[3]]);
+    let ack = u32::from_be_bytes([tcp[8], tcp[9], tcp[10], tcp[11]]);
+    Some((src, sport, dport, ack, tcp[13]))
+}
+
+struct SynScan {
+    socket: Socket,
+    sport: u16,
+    secret: u32,
+    sent: HashMap<(Ipv4Addr, u16), Instant>,
+    /// 各 /24 网段的源地址，同一网段走同一条路由，不必每个 SYN 都问一次系统
+    sources: HashMap<[u8; 3], Option<Ipv4Addr>>,
+    /// `--interface` 网卡的地址，指定时所有 SYN 都从它发出
+    interface: Option<Ipv4Addr>,
+    /// 每台主机开放的端口和最早的 SYN-ACK 往返时间
+    open: BTreeMap<Ipv4Addr, (BTreeSet<u16>, u64)>,
+}
+
+impl SynScan {
+    fn send(&mut self, dst: Ipv4Addr, port: u16) {
+        let [a, b, c, _] = dst.octets();
+        let Some(src) = *self.sources.entry([a, b, c]).or_insert_with(|| self.interface.or_else(|| source_for(dst))) else { return };
+        let segment = syn_segment(src, dst, self.sport, port, cookie(self.secret, dst, port));
+        let addr = SocketAddr::V4(SocketAddrV4::new(dst, 0)).into();
+        // 发送太快时内核可能返回 ENOBUFS，稍等再试一次
+        if self.socket.send_to(&segment, &addr).is_err() {
+            std::thread::sleep(Duration::from_millis(1));
+            let _ = self.socket.send_to(&segment, &addr);
+        }
+        self.sent.insert((dst, port), Instant::now());
+    }
+
+    /// 接收到 `until` 为止；`until` 为 None 时只取走已经到达的包
+    fn receive(&mut self, until: Option<Instant>) {
+        let mut buf = [0u8; 1500];
+        loop {
+            let wait = match until {
+                Some(t) => match t.checked_duration_since(Instant::now()) {
+                    Some(d) if !d.is_zero() => d,
+                    _ => return,
+                },
+                None => Duration::from_millis(1),
+            };
+            let _ = self.socket.set_read_timeout(Some(wait));
+            let Ok(n) = (&self.socket).read(&mut buf) else {
+                if until.is_none() {
+                    return;
+                }
+                continue;
+            };
+            let Some((src, port, dport, ack, flags)) = parse_reply(&buf[..n]) else { continue };
+            if dport != self.sport || flags & TCP_RST != 0 || flags & (TCP_SYN | TCP_ACK) != TCP_SYN | TCP_ACK {
+                continue;
+            }
+            if ack != cookie(self.secret, src, port).wrapping_add(1) {
+                continue;
+            }
+            let Some(sent) = self.sent.get(&(src, port)) else { continue };
+            let rtt = sent.elapsed().as_millis() as u64;
+            let entry = self.open.entry(src).or_insert((BTreeSet::new(), rtt));
+            entry.0.insert(port);
+            entry.1 = entry.1.min(rtt);
+        }
+    }
+}
+
+/// 原始套接字半开扫描：只发 SYN，收到 SYN-ACK 即认为端口开放，
+/// 内核会自动对 SYN-ACK 回 RST，不建立完整连接
+fn scan_blocking(targets: Vec<(Ipv4Addr, Vec<u16>)>, interface: Option<Ipv4Addr>, timeout: Duration) -> std::io::Result<BTreeMap<Ipv4Addr, (BTreeSet<u16>, u64)>> {
+    let socket = Socket::new(Domain::IPV4, Type::RAW, Some(Protocol::TCP))?;
+    let secret = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0)
+        ^ std::process::id();
+    let mut scan = SynScan { socket, sport: 40000 + (secret % 20000) as u16, secret, sent: HashMap::new(), sources: HashMap::new(), interface, open: BTreeMap::new() };
+    for (i, (ip, port)) in targets.iter().flat_map(|(ip, ports)| ports.iter().map(move |p| (*ip, *p))).enumerate() {
+        scan.send(ip, port);
+        if (i + 1) % BATCH == 0 {
+            scan.receive(None);
+        }
+    }
+    scan.receive(Some(Instant::now() + timeout));
+    Ok(scan.open)
+}
+
+/// `--syn`：用 SYN 扫描代替逐个 TCP 连接。IPv4 主机的 `ports` 只留下实际开放的端口
+/// (可能为空)，并标记为已确认开放；IPv6 主机保持原样，仍走连接探测。
+/// 没有权限创建原始套接字时返回 None，调用方退回普通扫描
+pub async fn scan(hosts: Vec<Host>, interface: Option<Ipv4Addr>, timeout_ms: u64) -> Option<Vec<Host>> {
+    let targets: Vec<(Ipv4Addr, Vec<u16>)> = hosts
+        .iter()
+        .filter(|h| h.known_rtt_ms.is_none())
+        .filter_map(|h| match h.ip {
+            IpAddr::V4(ip) => Some((ip, h.ports.clone())),
+            IpAddr::V6(_) => None,
+        })
+        .collect();
+    let timeout = Duration::from_millis(timeout_ms);
+    let open = match tokio::task::spawn_blocking(move || scan_blocking(targets, interface, timeout)).await.ok()? {
+        Ok(open) => open,
+        Err(e) => { eprintln!("{}", tr!(SynUnavailable, e)); return None; }
+    };
+    Some(
+        hosts
+            .into_iter()
+            .map(|mut host| {
+                if let IpAddr::V4(ip) = host.ip
+                    && host.known_rtt_ms.is_none()
+                {
+                    // 按用户给出的端口顺序保留开放的端口，识别时仍先试排在前面的端口
+                    let (ports, rtt) = open.get(&ip).cloned().unwrap_or_default();
+                    host.ports.retain(|p| ports.contains(p));
+                    host.known_rtt_ms = Some(rtt);
+                }
+                host
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,106 @@
+use crate::PrinterInfo;
+use crate::diff::Change;
+use crate::printer_mib::Alert;
+use std::net::IpAddr;
+use syslog::{Facility, Formatter3164, Logger, LoggerBackend};
+
+/// logfmt 风格的值：含空格、引号或等号时加双引号
+fn value(s: &str) -> String {
+    if s.is_empty() || s.contains([' ', '"', '=', '\\']) {
+        format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn printer_fields(printer: &PrinterInfo) -> String {
+    let mut fields = format!(
+        "ip={} model={} source={} response_ms={}",
+        printer.ip,
+        value(&printer.model),
+        value(&printer.source),
+        printer.response_ms
+    );
+    if let Some(name) = &printer.hostname {
+        fields.push_str(&format!(" hostname={}", value(name)));
+    }
+    if let Some(name) = &printer.netbios_name {
+        fields.push_str(&format!(" netbios_name={}", value(name)));
+    }
+    if let Some(mac) = &printer.mac {
+        fields.push_str(&format!(" mac={}", mac));
+    }
+    if let Some(vendor) = &printer.vendor {
+        fields.push_str(&format!(" vendor={}", value(vendor)));
+    }
+    fields
+}
+
+/// 写入本机 syslog (/dev/log，journald 同样监听)。
+/// 消息体使用 `event=... key=value` 形式，方便日志平台按字段提取
+pub struct SyslogSink {
+    logger: Logger<LoggerBackend, Formatter3164>,
+}
+
+impl SyslogSink {
+    pub fn connect() -> Option<Self> {
+        let formatter = Formatter3164 {
+            facility: Facility::LOG_DAEMON,
+            hostname: None,
+            process: "printer_scanner".into(),
+            pid: std::process::id(),
+        };
+        match syslog::unix(formatter) {
+            Ok(logger) => Some(SyslogSink { logger }),
+            Err(e) => { eprintln!("{}", tr!(SyslogConnectFailed, e)); None }
+        }
+    }
+
+    fn send(&mut self, msg: String) {
+        if let Err(e) = self.logger.info(msg) {
+            eprintln!("{}", tr!(SyslogWriteFailed, e));
+        }
+    }
+
+    pub fn discovered(&mut self, printer: &PrinterInfo) {
+        self.send(format!("event=printer_found {}", printer_fields(printer)));
+    }
+
+    pub fn change(&mut self, change: &Change) {
+        let msg = match change {
+            Change::Added { printer } => format!("event=printer_added {}", printer_fields(printer)),
+            Change::Removed { printer } => format!("event=printer_removed {}", printer_fields(printer)),
+            Change::Changed { before, after } => format!(
+                "event=printer_changed {} previous_model={}",
+                printer_fields(after),
+                value(&before.model)
+            ),
+            Change::Alerts { printer, raised, cleared } => format!(
+                "event=printer_alerts {} raised={} cleared={}",
+                printer_fields(printer),
+                value(&raised.join(", ")),
+                value(&cleared.join(", "))
+            ),
+        };
+        self.send(msg);
+    }
+
+    pub fn trap(&mut self, ip: IpAddr, model: Option<&str>, trap: &str, alert: Option<&Alert>) {
+        let mut msg = format!("event=snmp_trap ip={} trap={}", ip, value(trap));
+        if let Some(model) = model {
+            msg.push_str(&format!(" model={}", value(model)));
+        }
+        if let Some(alert) = alert {
+            msg.push_str(&format!(" severity={} alert={}", alert.severity, value(&alert.description)));
+        }
+        self.send(msg);
+    }
+
+    pub fn summary(&mut self, network: &str, printers: usize, duration_ms: u64) {
+        self.send(format!(
+            "event=scan_complete network={} printers={} duration_ms={}",
+            value(network), printers, duration_ms
+        ));
+    }
+}
+
@@ -0,0 +1,516 @@
+use crate::cli::TargetArgs;
+use crate::nmap;
+use crate::probes::Source;
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs};
+use std::path::Path;
+
+/// 自动探测本机网段时，比这更大的网段 (如 VPN 的 /8) 只扫描本机所在的 /24
+const AUTO_MIN_PREFIX: u8 = 16;
+
+/// 不短于该长度的 IPv6 前缀 (最多 65536 个地址) 才逐个扫描，
+/// 更大的前缀 (如 /64) 只扫描系统邻居表中已知的主机
+const IPV6_SWEEP_PREFIX: u8 = 112;
+
+/// 一台待扫描的主机及要尝试的打印端口
+#[derive(Debug, Clone)]
+pub struct Host {
+    pub ip: IpAddr,
+    /// 按顺序尝试 (`-n` / `--ports` 给出的顺序，nmap 导入的从小到大)，第一个开放的端口用于识别
+    pub ports: Vec<u16>,
+    /// `ports` 已确认开放 (来自 `--import-nmap` 或 `--syn`)，跳过连接探测；值为测得的往返时间 (ms)
+    pub known_rtt_ms: Option<u64>,
+    /// 所属目标在 `Targets::groups` 中的下标，多个目标重叠时算作最先给出的那个
+    pub group: usize,
+}
+
+/// `--randomize`、`--ping-first`、`--syn` 要先列出全部主机，超过这个数 (一个 /12) 时报错，
+/// 普通扫描边生成地址边探测，不受限制
+const MAX_LISTED_HOSTS: u64 = 1 << 20;
+
+/// 一个目标包含的地址，扫描时才逐个生成
+#[derive(Debug, Clone)]
+enum Addrs {
+    /// IPv4 网段 (不含网络地址和广播地址) 或 `a.b.c.d-w.x.y.z` 范围，首尾都包含
+    Range(u32, u32),
+    V6(Ipv6Net),
+    /// 单个 IP、主机名解析出的地址、IPv6 大前缀中邻居表里的主机
+    List(Vec<IpAddr>),
+}
+
+impl Addrs {
+    fn iter(&self) -> Box<dyn Iterator<Item = IpAddr> + Send + '_> {
+        match self {
+            Addrs::Range(start, end) => Box::new((*start..=*end).map(|n| IpAddr::V4(Ipv4Addr::from(n)))),
+            Addrs::V6(net) => Box::new(net.hosts().map(IpAddr::V6)),
+            Addrs::List(ips) => Box::new(ips.iter().copied()),
+        }
+    }
+
+    fn contains(&self, ip: &IpAddr) -> bool {
+        match (self, ip) {
+            (Addrs::Range(start, end), IpAddr::V4(v4)) => (*start..=*end).contains(&u32::from(*v4)),
+            (Addrs::V6(net), IpAddr::V6(v6)) => net.contains(v6),
+            (Addrs::List(ips), _) => ips.contains(ip),
+            _ => false,
+        }
+    }
+
+    fn len(&self) -> u64 {
+        match self {
+            Addrs::Range(start, end) => (end - start) as u64 + 1,
+            Addrs::V6(net) => 1 << (128 - net.prefix_len()),
+            Addrs::List(ips) => ips.len() as u64,
+        }
+    }
+}
+
+/// `-n` 或文件中的一个目标及其端口
+#[derive(Debug, Clone)]
+struct Spec {
+    addrs: Addrs,
+    ports: Vec<u16>,
+    group: usize,
+}
+
+/// 解析后的扫描目标。主机由 `hosts()` 按需生成，大网段不会预先占用内存
+#[derive(Debug, Clone)]
+pub struct Targets {
+    specs: Vec<Spec>,
+    /// `--import-nmap` 的主机，端口已确认开放；同一主机以导入结果为准
+    imported: BTreeMap<IpAddr, Host>,
+    /// 横幅、报告和日志中显示的目标，即用户给出的原始写法
+    pub label: String,
+    /// 每个目标单独统计进度：`-n` 的每一项、`--targets-file` 整个文件、`--import-nmap` 整个文件
+    pub groups: Vec<String>,
+    /// 目标中的网段，用于判断组播发现的设备是否在扫描范围内
+    nets: Vec<IpNet>,
+    excluded: Vec<Addrs>,
+}
+
+/// 读取 `--targets-file` / `--exclude-file`，`-` 表示 stdin
+fn read_targets_file(path: &Path) -> Result<Vec<String>, String> {
+    let text = if path == Path::new("-") {
+        io::read_to_string(io::stdin()).map_err(|e| tr!(ReadFailed, "stdin", e))?
+    } else {
+        std::fs::read_to_string(path).map_err(|e| tr!(ReadFailed, path.display(), e))?
+    };
+    Ok(text
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// 把目标拆成地址部分和端口部分。IPv6 地址本身带冒号，需要端口时写成 `[2001:db8::/120]:9100`
+fn split_ports(spec: &str) -> Result<(&str, Option<&str>), String> {
+    if let Some(rest) = spec.strip_prefix('[') {
+        return match rest.split_once(']') {
+            Some((addrs, "")) => Ok((addrs, None)),
+            Some((addrs, after)) => match after.strip_prefix(':') {
+                Some(ports) => Ok((addrs, Some(ports))),
+                None => Err(tr!(InvalidAddress, spec)),
+            },
+            None => Err(tr!(InvalidAddress, spec)),
+        };
+    }
+    match spec.split_once(':') {
+        // 不带方括号又有多个冒号的是 IPv6 地址
+        Some((addrs, ports)) if !ports.contains(':') => Ok((addrs, Some(ports))),
+        _ => Ok((spec, None)),
+    }
+}
+
+/// 把 `-n` 的参数按逗号拆开。端口列表里也有逗号，
+/// 所以紧跟在 `...:9100` 之后的纯数字段归入前一个目标的端口
+fn split_specs(args: &[String]) -> Vec<String> {
+    let mut specs: Vec<String> = Vec::new();
+    for piece in args.iter().flat_map(|a| a.split(',')).map(str::trim).filter(|p| !p.is_empty()) {
+        let has_ports = |s: &str| matches!(split_ports(s), Ok((_, Some(_))));
+        match specs.last_mut() {
+            Some(last) if has_ports(last) && piece.parse::<u16>().is_ok() => {
+                last.push(',');
+                last.push_str(piece);
+            }
+            _ => specs.push(piece.to_string()),
+        }
+    }
+    specs
+}
+
+fn parse_ports(s: &str) -> Result<Vec<u16>, String> {
+    s.split(',').map(|p| p.parse().map_err(|_| tr!(InvalidPort, p))).collect()
+}
+
+fn parse_ipv4(s: &str) -> Result<Ipv4Addr, String> {
+    s.parse().map_err(|_| tr!(InvalidAddress, s))
+}
+
+/// 解析主机名。同时有 IPv4 和 IPv6 地址时只用 IPv4，避免同一台打印机扫出两条结果
+fn resolve(host: &str) -> Result<Vec<IpAddr>, String> {
+    let addrs: Vec<IpAddr> = (host, 0)
+        .to_socket_addrs()
+        .map_err(|e| tr!(ResolveFailed, host, e))?
+        .map(|a| a.ip())
+        .collect();
+    if addrs.is_empty() {
+        return Err(tr!(NoAddress, host));
+    }
+    let v4: Vec<IpAddr> = addrs.iter().copied().filter(IpAddr::is_ipv4).collect();
+    Ok(if v4.is_empty() { addrs } else { v4 })
+}
+
+/// 从系统邻居表 (`ip -6 neigh`) 中取出落在前缀内的主机。
+/// 邻居表只包含最近通信过的设备，配合 mDNS 等发现手段效果更好
+fn ipv6_neighbors(net: Ipv6Net) -> Vec<IpAddr> {
+    let stdout = match std::process::Command::new("ip").args(["-6", "neigh", "show"]).output() {
+        Ok(output) if output.status.success() => output.stdout,
+        Ok(output) => {
+            eprintln!("{}", tr!(Ipv6NeighborsFailed, net, String::from_utf8_lossy(&output.stderr).trim()));
+            return Vec::new();
+        }
+        Err(e) => { eprintln!("{}", tr!(Ipv6NeighborsFailed, net, e)); return Vec::new(); }
+    };
+    let hosts: BTreeSet<IpAddr> = String::from_utf8_lossy(&stdout)
+        .lines()
+        .filter(|line| !line.contains("FAILED"))
+        .filter_map(|line| line.split_whitespace().next()?.parse::<Ipv6Addr>().ok())
+        .filter(|ip| net.contains(ip))
+        .map(IpAddr::V6)
+        .collect();
+    status!("{}", tr!(Ipv6Neighbors, net, hosts.len()));
+    hosts.into_iter().collect()
+}
+
+/// 解析地址部分：CIDR、`a.b.c.d-e` (只改最后一段)、`a.b.c.d-w.x.y.z`、单个 IP、
+/// IPv6 地址或前缀、主机名
+fn parse_addrs(s: &str) -> Result<Addrs, String> {
+    if s.contains('/') {
+        if let Ok(net) = s.parse::<Ipv6Net>() {
+            if net.prefix_len() < IPV6_SWEEP_PREFIX {
+                return Ok(Addrs::List(ipv6_neighbors(net)));
+            }
+            return Ok(Addrs::V6(net));
+        }
+        let net: Ipv4Net = s.parse().map_err(|e| format!("{}: {}", s, e))?;
+        let mut hosts = net.hosts();
+        return Ok(match (hosts.next(), hosts.next_back()) {
+            (Some(start), Some(end)) => Addrs::Range(start.into(), end.into()),
+            (Some(ip), None) | (None, Some(ip)) => Addrs::Range(ip.into(), ip.into()),
+            (None, None) => Addrs::List(Vec::new()),
+        });
+    }
+    if let Ok(ip) = s.parse::<IpAddr>() {
+        return Ok(Addrs::List(vec![ip]));
+    }
+    // 主机名里也可能有 `-`，起点是 IP 时才当作范围
+    if let Some((start, end)) = s.split_once('-')
+        && let Ok(start) = start.parse::<Ipv4Addr>()
+    {
+        let end = if end.contains('.') {
+            parse_ipv4(end)?
+        } else {
+            let last: u8 = end.parse().map_err(|_| tr!(InvalidRange, s))?;
+            let [a, b, c, _] = start.octets();
+            Ipv4Addr::new(a, b, c, last)
+        };
+        if start > end {
+            return Err(tr!(InvalidRange, s));
+        }
+        return Ok(Addrs::Range(start.into(), end.into()));
+    }
+    resolve(s).map(Addrs::List)
+}
+
+/// 未指定目标时，从本机已启用的非回环 IPv4 接口推导要扫描的网段，
+/// 指定了 `--interface` 时只看该网卡
+fn local_networks(interface: Option<&str>) -> Result<Vec<String>, String> {
+    let interfaces = if_addrs::get_if_addrs().map_err(|e| tr!(InterfacesFailed, e))?;
+    let mut nets = BTreeSet::new();
+    for iface in interfaces
+        .iter()
+        .filter(|i| !i.is_loopback() && i.is_oper_up())
+        .filter(|i| interface.is_none_or(|name| i.name == name))
+    {
+        let if_addrs::IfAddr::V4(v4) = &iface.addr else { continue };
+        if v4.is_link_local() {
+            continue;
+        }
+        let prefix = if v4.prefixlen < AUTO_MIN_PREFIX { 24 } else { v4.prefixlen };
+        if let Ok(net) = Ipv4Net::new(v4.ip, prefix) {
+            nets.insert(net.trunc());
+        }
+    }
+    if nets.is_empty() {
+        return Err(tr!(NoLocalNetworks));
+    }
+    Ok(nets.iter().map(Ipv4Net::to_string).collect())
+}
+
+/// `--interface` 指定网卡的地址，探测连接从这些地址发出
+pub fn interface_source(name: &str) -> Result<Source, String> {
+    let interfaces = if_addrs::get_if_addrs().map_err(|e| tr!(InterfacesFailed, e))?;
+    let addrs: Vec<&if_addrs::IfAddr> = interfaces.iter().filter(|i| i.name == name).map(|i| &i.addr).collect();
+    if addrs.is_empty() {
+        return Err(tr!(NoSuchInterface, name));
+    }
+    let v4 = addrs.iter().find_map(|a| match a {
+        if_addrs::IfAddr::V4(v4) => Some(v4.ip),
+        _ => None,
+    });
+    // 链路本地地址需要 scope id，绑定后也连不上其他前缀的目标
+    let v6 = addrs.iter().find_map(|a| match a {
+        if_addrs::IfAddr::V6(v6) if !v6.is_link_local() => Some(v6.ip),
+        _ => None,
+    });
+    Ok(Source { v4, v6 })
+}
+
+impl Targets {
+    /// 解析 `--network`，可重复或逗号分隔，多个目标合并成一次扫描。
+    /// 每个目标可以是 CIDR、地址范围、单个 IP 或主机名，后面可跟 `:端口,端口` 指定要尝试的打印端口
+    pub fn from_args(args: &TargetArgs) -> Result<Self, String> {
+        // 每个目标带上所属分组的下标
+        let mut specs: Vec<(String, usize)> = split_specs(&args.network).into_iter().enumerate().map(|(i, s)| (s, i)).collect();
+        let mut groups: Vec<String> = specs.iter().map(|(s, _)| s.clone()).collect();
+        // 文件里可能有成百上千个目标，显示时只写文件名
+        if let Some(path) = &args.targets_file {
+            let group = groups.len();
+            specs.extend(split_specs(&read_targets_file(path)?).into_iter().map(|s| (s, group)));
+            groups.push(if path == Path::new("-") { "stdin".to_string() } else { path.display().to_string() });
+        }
+        let imported = match &args.import_nmap {
+            Some(path) => {
+                groups.push(path.display().to_string());
+                nmap::read_hosts(path)?
+            }
+            None => Vec::new(),
+        };
+        if specs.is_empty() && args.import_nmap.is_none() {
+            groups = local_networks(args.interface.as_deref())?;
+            specs = groups.iter().cloned().enumerate().map(|(i, s)| (s, i)).collect();
+            status!("{}", tr!(AutoNetworks, groups.join(" ")));
+        }
+
+        let mut excludes = args.exclude.clone();
+        if let Some(path) = &args.exclude_file {
+            excludes.extend(read_targets_file(path)?);
+        }
+        let excluded = excludes
+            .iter()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|spec| parse_addrs(split_ports(spec)?.0))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut parsed = Vec::new();
+        let mut nets = Vec::new();
+        for (spec, group) in &specs {
+            let (addrs, ports) = match split_ports(spec)? {
+                (addrs, Some(ports)) => (addrs, parse_ports(ports)?),
+                (addrs, None) => (addrs, args.ports.clone()),
+            };
+            if let Ok(net) = addrs.parse::<IpNet>() {
+                nets.push(net);
+            }
+            parsed.push(Spec { addrs: parse_addrs(addrs)?, ports, group: *group });
+        }
+        // nmap 已经确认过端口，同一主机以导入结果为准
+        let group = groups.len().saturating_sub(1);
+        let imported = imported
+            .into_iter()
+            .filter(|h| !excluded.iter().any(|e| e.contains(&h.ip)))
+            .map(|h| (h.ip, Host { ip: h.ip, ports: h.ports.into_iter().collect(), known_rtt_ms: Some(h.rtt_ms.unwrap_or(0)), group }))
+            .collect();
+        let targets = Targets { specs: parsed, imported, label: groups.join(" "), groups, nets, excluded };
+        let listed = targets.specs.iter().map(|s| s.addrs.len()).sum::<u64>();
+        if (args.randomize || args.ping_first || args.syn) && listed > MAX_LISTED_HOSTS {
+            return Err(tr!(TooManyHosts, listed, MAX_LISTED_HOSTS));
+        }
+        Ok(targets)
+    }
+
+    fn excludes(&self, ip: &IpAddr) -> bool {
+        self.excluded.iter().any(|e| e.contains(ip))
+    }
+
+    /// 逐个生成要扫描的主机：按目标给出的顺序展开，最后是 nmap 导入的主机。
+    /// 多个目标重叠的地址只在最先给出它的目标中出现，端口合并所有包含它的目标的端口
+    pub fn hosts(&self) -> impl Iterator<Item = Host> + Send + '_ {
+        let listed = self.specs.iter().enumerate().flat_map(move |(i, spec)| {
+            spec.addrs
+                .iter()
+                .filter(move |ip| !self.excludes(ip) && !self.imported.contains_key(ip) && !self.specs[..i].iter().any(|s| s.addrs.contains(ip)))
+                .map(move |ip| Host { ip, ports: self.ports_of(ip, i), known_rtt_ms: None, group: spec.group })
+        });
+        listed.chain(self.imported.values().cloned())
+    }
+
+    /// 从第 `from` 个目标起，所有包含该地址的目标的端口，按给出的顺序去重
+    fn ports_of(&self, ip: IpAddr, from: usize) -> Vec<u16> {
+        let mut ports: Vec<u16> = Vec::new();
+        for spec in self.specs[from..].iter().filter(|s| s.addrs.contains(&ip)) {
+            for port in &spec.ports {
+                if !ports.contains(port) {
+                    ports.push(*port);
+                }
+            }
+        }
+        ports
+    }
+
+    /// 地址所属的目标分组，只对逐个列出的主机有效
+    pub fn group_of(&self, ip: IpAddr) -> Option<usize> {
+        if self.excludes(&ip) {
+            return None;
+        }
+        match self.imported.get(&ip) {
+            Some(host) => Some(host.group),
+            None => self.specs.iter().find(|s| s.addrs.contains(&ip)).map(|s| s.group),
+        }
+    }
+
+    /// 地址是否在扫描范围内 (包括 IPv6 大前缀中没能逐个列出的地址)
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.group_of(ip).is_some() || (!self.excludes(&ip) && self.nets.iter().any(|n| n.contains(&ip)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        target: TargetArgs,
+    }
+
+    fn targets(args: &[&str]) -> Result<Targets, String> {
+        Targets::from_args(&Cli::parse_from(["printer_scanner"].iter().chain(args)).target)
+    }
+
+    fn specs(args: &[&str]) -> Vec<String> {
+        split_specs(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+    }
+
+    fn ips(s: &str) -> Vec<String> {
+        parse_addrs(s).unwrap().iter().map(|ip| ip.to_string()).collect()
+    }
+
+    #[test]
+    fn overlapping_networks_are_merged() {
+        let t = targets(&["-n", "10.0.0.0/30,10.0.0.0/29", "-n", "10.0.0.8/31"]).unwrap();
+        // /30 是 /29 的一部分，只扫一次；/31 的两个地址都算主机
+        let hosts: Vec<String> = t.hosts().map(|h| h.ip.to_string()).collect();
+        assert_eq!(hosts, ["10.0.0.1", "10.0.0.2", "10.0.0.3", "10.0.0.4", "10.0.0.5", "10.0.0.6", "10.0.0.8", "10.0.0.9"]);
+        assert_eq!(t.label, "10.0.0.0/30 10.0.0.0/29 10.0.0.8/31");
+    }
+
+    #[test]
+    fn invalid_network_names_the_spec() {
+        let err = targets(&["-n", "10.0.0.0/24,10.0.1.0/33"]).unwrap_err();
+        assert!(err.starts_with("10.0.1.0/33: "), "{err}");
+    }
+
+    #[test]
+    fn port_lists_follow_their_target() {
+        // `-n` 本身按逗号拆开，端口列表里的数字要归回前一个目标
+        assert_eq!(specs(&["10.0.0.0/24:9100,631,10.0.1.5-9"]), ["10.0.0.0/24:9100,631", "10.0.1.5-9"]);
+        assert_eq!(specs(&["10.0.0.1-3:515", "9100"]), ["10.0.0.1-3:515,9100"]);
+        assert_eq!(specs(&[" 10.0.0.0/30 ,, 10.0.1.0/30"]), ["10.0.0.0/30", "10.0.1.0/30"]);
+    }
+
+    #[test]
+    fn same_host_in_two_targets_tries_both_ports() {
+        let t = targets(&["-n", "10.0.0.1-2:631,10.0.0.2-2:515,9100"]).unwrap();
+        let ports: Vec<(String, Vec<u16>)> = t.hosts().map(|h| (h.ip.to_string(), h.ports.clone())).collect();
+        // 端口按给出的顺序尝试，重复的只保留第一次
+        assert_eq!(ports, [("10.0.0.1".into(), vec![631]), ("10.0.0.2".into(), vec![631, 515, 9100])]);
+        assert!(targets(&["-n", "10.0.0.1-2:96xx"]).is_err());
+    }
+
+    #[test]
+    fn default_ports_apply_to_bare_targets() {
+        let t = targets(&["-n", "10.0.0.1-1,10.0.0.2-2:515", "--ports", "631,9100"]).unwrap();
+        let ports: Vec<Vec<u16>> = t.hosts().map(|h| h.ports.clone()).collect();
+        assert_eq!(ports, [vec![631, 9100], vec![515]]);
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(ips("10.0.0.254-10.0.1.1"), ["10.0.0.254", "10.0.0.255", "10.0.1.0", "10.0.1.1"]);
+        // 只写最后一段时沿用起点的前三段
+        assert_eq!(ips("192.168.1.9-11"), ["192.168.1.9", "192.168.1.10", "192.168.1.11"]);
+        assert_eq!(ips("192.168.1.9-9"), ["192.168.1.9"]);
+        assert!(parse_addrs("192.168.1.9-3").is_err());
+        assert!(parse_addrs("192.168.1.9-256").is_err());
+        assert!(parse_addrs("192.168.1.9-192.168.1").is_err());
+    }
+
+    #[test]
+    fn ipv6_ports_need_brackets() {
+        assert_eq!(split_ports("2001:db8::10"), Ok(("2001:db8::10", None)));
+        assert_eq!(split_ports("[2001:db8::/120]:9100,631"), Ok(("2001:db8::/120", Some("9100,631"))));
+        assert_eq!(split_ports("[2001:db8::10]"), Ok(("2001:db8::10", None)));
+        assert_eq!(split_ports("10.0.0.0/24:515"), Ok(("10.0.0.0/24", Some("515"))));
+        assert!(split_ports("[2001:db8::10").is_err());
+        assert!(split_ports("[2001:db8::10]9100").is_err());
+        // 不带方括号的 IPv6 地址后面的数字不是端口
+        assert_eq!(specs(&["2001:db8::10,9100"]), ["2001:db8::10", "9100"]);
+        assert_eq!(specs(&["[2001:db8::10]:9100,515,2001:db8::11"]), ["[2001:db8::10]:9100,515", "2001:db8::11"]);
+    }
+
+    #[test]
+    fn small_ipv6_prefixes_are_swept() {
+        assert_eq!(parse_addrs("2001:db8::/120").unwrap().len(), 256);
+        assert_eq!(ips("2001:db8::/127"), ["2001:db8::", "2001:db8::1"]);
+    }
+
+    #[test]
+    fn addrs_know_their_size_without_listing() {
+        let range = parse_addrs("10.0.0.250-10.0.1.5").unwrap();
+        assert_eq!(range.len(), 12);
+        assert!(range.contains(&"10.0.1.0".parse().unwrap()));
+        assert!(!range.contains(&"10.0.1.6".parse().unwrap()));
+        assert!(!range.contains(&"2001:db8::1".parse().unwrap()));
+        // /16 不含网络地址和广播地址
+        assert_eq!(parse_addrs("172.16.0.0/16").unwrap().len(), 65534);
+        let v6 = parse_addrs("2001:db8::/112").unwrap();
+        assert_eq!(v6.len(), 65536);
+        assert!(v6.contains(&"2001:db8::ffff".parse().unwrap()));
+        assert!(!v6.contains(&"2001:db8::1:0".parse().unwrap()));
+    }
+
+    #[test]
+    fn hosts_are_generated_lazily() {
+        // /8 有一千六百多万个地址，只取前几个不应该把它们都列出来
+        let t = targets(&["-n", "10.0.0.0/8", "--exclude", "10.0.0.2"]).unwrap();
+        let first: Vec<String> = t.hosts().take(3).map(|h| h.ip.to_string()).collect();
+        assert_eq!(first, ["10.0.0.1", "10.0.0.3", "10.0.0.4"]);
+        assert!(t.contains("10.255.255.254".parse().unwrap()));
+        assert!(!t.contains("10.0.0.2".parse().unwrap()));
+    }
+
+    #[test]
+    fn listing_options_cap_the_target_size() {
+        assert!(targets(&["-n", "10.0.0.0/12", "--randomize"]).is_ok());
+        assert!(targets(&["-n", "10.0.0.0/11", "--randomize"]).is_err());
+        assert!(targets(&["-n", "10.0.0.0/8"]).is_ok());
+    }
+
+    #[test]
+    fn only_targets_are_parsed() {
+        // 网卡、SNMP 和 OUI 设置由 `probes::Context` 处理，解析目标时不检查也不生效
+        let args = ["-n", "10.0.0.1", "-i", "no-such-if0", "--oid", "x=bad", "--oui-file", "/nonexistent/oui.txt"];
+        assert_eq!(targets(&args).unwrap().hosts().count(), 1);
+        let cli = Cli::parse_from(["printer_scanner"].iter().chain(&args));
+        assert!(matches!(crate::probes::Context::from_args(&cli.target), Err(crate::ScanError::Snmp(_))));
+        let cli = Cli::parse_from(["printer_scanner", "-n", "10.0.0.1", "-i", "no-such-if0"]);
+        assert!(matches!(crate::probes::Context::from_args(&cli.target), Err(crate::ScanError::Targets(_))));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::probes::{self, Source};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{Instant, timeout};
+
+pub const TELNET_PORT: u16 = 23;
+
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+
+/// JetDirect telnet 的 `/` (当前设置) 输出
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JetDirectConfig {
+    /// 固件版本 (`Firmware Rev.`)，如 `H.08.49`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub firmware: Option<String>,
+    /// 打印服务器网卡的 MAC 地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac: Option<String>,
+    /// 其余的 `名称 : 值` 设置 (IP、子网掩码、网关、Syslog 服务器、空闲超时等)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub settings: BTreeMap<String, String>,
+}
+
+/// 去掉 telnet 协商序列，对服务器的 DO / WILL 一律拒绝，返回需要回复的字节
+fn strip_negotiation(raw: &[u8], text: &mut Vec<u8>) -> Vec<u8> {
+    let mut reply = Vec::new();
+    let mut i = 0;
+    while i < raw.len() {
+        match (raw[i], raw.get(i + 1), raw.get(i + 2)) {
+            (IAC, Some(&DO), Some(&opt)) => {
+                reply.extend([IAC, WONT, opt]);
+                i += 3;
+            }
+            (IAC, Some(&WILL), Some(&opt)) => {
+                reply.extend([IAC, DONT, opt]);
+                i += 3;
+            }
+            (IAC, Some(&(DONT | WONT)), Some(_)) => i += 3,
+            (IAC, Some(&SB), _) => {
+                // 子协商一直到 IAC SE
+                let end = raw[i..].windows(2).position(|w| w == [IAC, SE]).map_or(raw.len(), |p| i + p + 2);
+                i = end;
+            }
+            (IAC, Some(&IAC), _) => {
+                text.push(IAC);
+                i += 2;
+            }
+            (IAC, Some(_), _) => i += 2,
+            (b, _, _) => {
+                text.push(b);
+                i += 1;
+            }
+        }
+    }
+    reply
+}
+
+/// 一直读到出现 `until` 中的任一提示符，或等待超时
+async fn read_until(stream: &mut TcpStream, until: &[&str], wait: Duration) -> Option<String> {
+    let deadline = Instant::now() + wait;
+    let mut text = Vec::new();
+    let mut buffer = [0u8; 2048];
+    loop {
+        let left = deadline.checked_duration_since(Instant::now())?;
+        let n = match timeout(left, stream.read(&mut buffer)).await {
+            Ok(Ok(n)) if n > 0 => n,
+            _ => break,
+        };
+        let reply = strip_negotiation(&buffer[..n], &mut text);
+        if !reply.is_empty() {
+            stream.write_all(&reply).await.ok()?;
+        }
+        let seen = String::from_utf8_lossy(&text);
+        if until.iter().any(|p| seen.trim_end().ends_with(p)) {
+            break;
+        }
+    }
+    (!text.is_empty()).then(|| String::from_utf8_lossy(&text).to_string())
+}
+
+fn parse(text: &str) -> Option<JetDirectConfig> {
+    let mut settings: BTreeMap<String, String> = text
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let (key, value) = (key.trim(), value.trim());
+            // MAC 地址本身带冒号，只按第一个冒号拆分；没有名称的行不是设置项
+            (!key.is_empty() && !value.is_empty() && key.len() <= 40).then(|| (key.to_string(), value.to_string()))
+        })
+        .collect();
+    if settings.is_empty() {
+        return None;
+    }
+    let mut take = |names: &[&str]| names.iter().find_map(|n| settings.remove(*n));
+    let firmware = take(&["Firmware Rev.", "Firmware Rev", "Firmware Version"]);
+    let mac = take(&["MAC Address", "LAN HW Address", "Hardware Address"]);
+    Some(JetDirectConfig { firmware, mac, settings })
+}
+
+/// 连接 23 端口，发送 `/` 读取 JetDirect 的当前设置后退出。
+/// 设置了 telnet 密码的卡会要求登录，这种情况以及端口不通时返回 None
+pub async fn jetdirect_config(source: Source, ip: IpAddr, timeout_ms: u64) -> Option<JetDirectConfig> {
+    let addr = SocketAddr::new(ip, TELNET_PORT);
+    let mut stream = timeout(Duration::from_millis(timeout_ms), probes::connect(source, addr)).await.ok()?.ok()?;
+    let wait = Duration::from_millis(timeout_ms.max(1000));
+    let banner = read_until(&mut stream, &[">", "password:", "Password:"], wait).await?;
+    if !(banner.contains("JetDirect") || banner.contains("JETDIRECT")) || banner.trim_end().ends_with(':') {
+        return None;
+    }
+    stream.write_all(b"/\r\n").await.ok()?;
+    let settings = read_until(&mut stream, &[">"], wait).await;
+    let _ = stream.write_all(b"quit\r\n").await;
+    parse(&settings?)
+}
@@ -0,0 +1,136 @@
+use crate::cli::{TrapArgs, exit_error};
+use crate::printer_mib::{self, Alert};
+use crate::snmp::{self, SnmpVersion};
+use crate::syslog_sink::SyslogSink;
+use crate::{diff, output, snmp_walk};
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::process::ExitCode;
+use std::time::SystemTime;
+use tokio::net::UdpSocket;
+
+/// 常见 trap 的名称
+const TRAP_NAMES: &[(&str, &[u64])] = &[
+    ("coldStart", &[1, 3, 6, 1, 6, 3, 1, 1, 5, 1]),
+    ("warmStart", &[1, 3, 6, 1, 6, 3, 1, 1, 5, 2]),
+    ("linkDown", &[1, 3, 6, 1, 6, 3, 1, 1, 5, 3]),
+    ("linkUp", &[1, 3, 6, 1, 6, 3, 1, 1, 5, 4]),
+    ("authenticationFailure", &[1, 3, 6, 1, 6, 3, 1, 1, 5, 5]),
+    ("printerV2AlertTrap", printer_mib::OID_ALERT_TRAP),
+];
+
+/// 一个收到的 trap
+#[derive(Serialize, Debug)]
+struct TrapEvent {
+    timestamp: String,
+    source: IpAddr,
+    /// 上次扫描中该设备的型号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    version: SnmpVersion,
+    /// trap 的名称 (`printerV2AlertTrap`、`coldStart` 等)，不认识的写成点分 OID
+    trap: String,
+    /// 告警 trap 解码出的告警
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alert: Option<Alert>,
+    /// 携带的变量，键为 Printer / Host Resources MIB 中的名称或点分 OID
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    variables: BTreeMap<String, String>,
+}
+
+fn trap_name(oid: &[u64]) -> String {
+    TRAP_NAMES.iter().find(|(_, trap)| *trap == oid).map(|(name, _)| name.to_string()).unwrap_or_else(|| snmp::dotted(oid))
+}
+
+fn write_event(out: &mut dyn Write, json: bool, event: &TrapEvent) -> io::Result<()> {
+    if json {
+        serde_json::to_writer(&mut *out, event)?;
+        writeln!(out)?;
+    } else {
+        let model = event.model.as_deref().unwrap_or("-");
+        match &event.alert {
+            Some(alert) => {
+                let description = if alert.severity == "critical" { alert.description.red() } else { alert.description.yellow() };
+                writeln!(out, "[{}] {} {} {} {}", event.timestamp, event.source, model, alert.severity.bold(), description)?
+            }
+            None => {
+                let variables = event.variables.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join(" ");
+                writeln!(out, "[{}] {} {} {} {}", event.timestamp, event.source, model, event.trap.bold(), variables)?
+            }
+        }
+    }
+    out.flush()
+}
+
+/// 在 UDP 162 上接收 trap，解码 Printer MIB 的告警 trap (卡纸、缺纸、缺粉等) 并输出，
+/// 可同时原样转发给其他接收端和写入 syslog。给出 `--scan` 时只接收其中打印机发来的 trap
+pub async fn run_traps(args: TrapArgs) -> ExitCode {
+    let known: Option<BTreeMap<IpAddr, String>> = match &args.scan {
+        Some(path) => match diff::load_scan(path) {
+            Ok(printers) => Some(printers.into_iter().map(|p| (p.ip, p.model)).collect()),
+            Err(e) => { eprintln!("{}", e); return exit_error(); }
+        },
+        None => None,
+    };
+    let mut out = match output::open_output(args.output.as_deref()) {
+        Ok(out) => out,
+        Err(e) => { eprintln!("{}", tr!(CreateOutputFailed, e)); return exit_error(); }
+    };
+    let socket = match UdpSocket::bind(args.listen).await {
+        Ok(socket) => socket,
+        Err(e) => { eprintln!("{}", tr!(ListenFailed, args.listen, e)); return exit_error(); }
+    };
+    let mut syslog = if args.syslog { SyslogSink::connect() } else { None };
+
+    match &known {
+        Some(known) => status!("{}", tr!(TrapsStartedFiltered, "👂".green(), args.listen, known.len())),
+        None => status!("{}", tr!(TrapsStarted, "👂".green(), args.listen)),
+    }
+    let mut buffer = vec![0u8; 65535];
+    loop {
+        let (n, from) = match socket.recv_from(&mut buffer).await {
+            Ok(received) => received,
+            Err(e) => { eprintln!("{}", tr!(TrapReceiveFailed, e)); continue; }
+        };
+        let source = from.ip().to_canonical();
+        let model = match &known {
+            Some(known) => match known.get(&source) {
+                Some(model) => Some(model.clone()),
+                None => continue,
+            },
+            None => None,
+        };
+        let Some(trap) = snmp::parse_trap(&buffer[..n]) else { continue };
+
+        for target in &args.forward {
+            if let Err(e) = socket.send_to(&buffer[..n], target).await {
+                eprintln!("{}", tr!(TrapForwardFailed, target, e));
+            }
+        }
+
+        let alert = (trap.oid == printer_mib::OID_ALERT_TRAP).then(|| printer_mib::trap_alert(&trap.variables)).flatten();
+        let variables = if alert.is_some() {
+            BTreeMap::new()
+        } else {
+            trap.variables.iter().map(|(oid, value)| (snmp_walk::friendly_name(oid), snmp_walk::value_text(value))).collect()
+        };
+        let event = TrapEvent {
+            timestamp: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            source,
+            model,
+            version: trap.version,
+            trap: trap_name(&trap.oid),
+            alert,
+            variables,
+        };
+        if let Some(syslog) = &mut syslog {
+            syslog.trap(event.source, event.model.as_deref(), &event.trap, event.alert.as_ref());
+        }
+        if let Err(e) = write_event(&mut out, args.json, &event) {
+            eprintln!("{}", tr!(EventWriteFailed, e));
+        }
+    }
+}
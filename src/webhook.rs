@@ -0,0 +1,76 @@
+use crate::PrinterInfo;
+use crate::cli::WebhookArgs;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+#[derive(Serialize)]
+struct DiscoveredPayload<'a> {
+    event: &'static str,
+    timestamp: String,
+    printer: &'a PrinterInfo,
+}
+
+/// 发现上一轮没有的打印机时 POST 一条 JSON 到 `--webhook-url`
+pub struct Webhook {
+    url: String,
+    client: reqwest::Client,
+    state_path: Option<PathBuf>,
+    /// 上一轮看到的打印机；None 表示还没有基线，这一轮只记录不通知
+    seen: Option<BTreeSet<IpAddr>>,
+}
+
+impl Webhook {
+    /// 未指定 `--webhook-url` 时返回 None
+    pub fn new(args: &WebhookArgs) -> Option<Self> {
+        let url = args.webhook_url.clone()?;
+        let seen = args.webhook_state.as_ref().and_then(|path| {
+            let text = std::fs::read_to_string(path).ok()?;
+            match serde_json::from_str(&text) {
+                Ok(seen) => Some(seen),
+                Err(e) => { eprintln!("{}", tr!(StateInvalid, path.display(), e)); None }
+            }
+        });
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .ok()?;
+        Some(Webhook { url, client, state_path: args.webhook_state.clone(), seen })
+    }
+
+    /// 与上一轮比较，逐台通知新出现的打印机，并把本轮结果作为下一轮的基线
+    pub async fn notify_new(&mut self, printers: &[PrinterInfo]) {
+        let current: BTreeSet<IpAddr> = printers.iter().map(|p| p.ip).collect();
+
+        match &self.seen {
+            Some(seen) => {
+                for printer in printers.iter().filter(|p| !seen.contains(&p.ip)) {
+                    self.post(printer).await;
+                }
+            }
+            None => status!("{}", tr!(WebhookBaseline, current.len())),
+        }
+
+        if let Some(path) = &self.state_path
+            && let Err(e) = serde_json::to_string(&current).map_err(std::io::Error::other).and_then(|s| std::fs::write(path, s))
+        {
+            eprintln!("{}", tr!(StateWriteFailed, path.display(), e));
+        }
+        self.seen = Some(current);
+    }
+
+    async fn post(&self, printer: &PrinterInfo) {
+        let payload = DiscoveredPayload {
+            event: "printer_discovered",
+            timestamp: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            printer,
+        };
+        match self.client.post(&self.url).json(&payload).send().await {
+            Ok(resp) if resp.status().is_success() => {}
+            Ok(resp) => eprintln!("{}", tr!(WebhookStatus, resp.status(), printer.ip)),
+            Err(e) => eprintln!("{}", tr!(WebhookFailed, printer.ip, e)),
+        }
+    }
+}
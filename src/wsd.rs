@@ -0,0 +1,140 @@
+use crate::discovery::{Discovered, message_uuid, xml_text};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::{Instant, timeout_at};
+
+const WSD_PORT: u16 = 3702;
+const WSD_MULTICAST_V4: Ipv4Addr = Ipv4Addr::new(239, 255, 255, 250);
+
+/// 只查询打印设备 (wprt:PrintDeviceType)，避免整个网络的 WSD 设备都来响应
+fn probe_message() -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://www.w3.org/2003/05/soap-envelope" xmlns:wsa="http://schemas.xmlsoap.org/ws/2004/08/addressing" xmlns:wsd="http://schemas.xmlsoap.org/ws/2005/04/discovery" xmlns:wprt="http://schemas.microsoft.com/windows/2006/08/wdp/print">
+<soap:Header>
+<wsa:To>urn:schemas-xmlsoap-org:ws:2005:04:discovery</wsa:To>
+<wsa:Action>http://schemas.xmlsoap.org/ws/2005/04/discovery/Probe</wsa:Action>
+<wsa:MessageID>urn:uuid:{}</wsa:MessageID>
+</soap:Header>
+<soap:Body><wsd:Probe><wsd:Types>wprt:PrintDeviceType</wsd:Types></wsd:Probe></soap:Body>
+</soap:Envelope>"#,
+        message_uuid()
+    )
+}
+
+/// WS-Transfer Get，向设备索取元数据 (厂商、型号、友好名称)
+fn get_message(endpoint: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://www.w3.org/2003/05/soap-envelope" xmlns:wsa="http://schemas.xmlsoap.org/ws/2004/08/addressing">
+<soap:Header>
+<wsa:To>{}</wsa:To>
+<wsa:Action>http://schemas.xmlsoap.org/ws/2004/09/transfer/Get</wsa:Action>
+<wsa:MessageID>urn:uuid:{}</wsa:MessageID>
+<wsa:ReplyTo><wsa:Address>http://schemas.xmlsoap.org/ws/2004/08/addressing/role/anonymous</wsa:Address></wsa:ReplyTo>
+</soap:Header>
+<soap:Body/>
+</soap:Envelope>"#,
+        endpoint,
+        message_uuid()
+    )
+}
+
+/// 一条 ProbeMatch：设备的端点地址和服务地址 (XAddrs，空格分隔的 URL)
+struct ProbeMatch {
+    ip: IpAddr,
+    endpoint: Option<String>,
+    xaddrs: Vec<String>,
+    types: Option<String>,
+}
+
+fn parse_probe_match(ip: IpAddr, body: &str) -> Option<ProbeMatch> {
+    if !body.contains("ProbeMatch") {
+        return None;
+    }
+    Some(ProbeMatch {
+        ip,
+        endpoint: xml_text(body, "Address"),
+        xaddrs: xml_text(body, "XAddrs").map(|x| x.split_whitespace().map(str::to_string).collect()).unwrap_or_default(),
+        types: xml_text(body, "Types"),
+    })
+}
+
+/// 选一个与响应来源同一地址的 XAddr，设备常常同时通告 IPv4 和 IPv6 地址
+fn pick_xaddr(m: &ProbeMatch) -> Option<reqwest::Url> {
+    let urls: Vec<reqwest::Url> = m.xaddrs.iter().filter_map(|x| x.parse().ok()).collect();
+    let same_host = urls.iter().find(|u| u.host_str().map(|h| h.trim_matches(['[', ']'])) == Some(&m.ip.to_string()));
+    same_host.or(urls.first()).cloned()
+}
+
+async fn fetch_metadata(client: &reqwest::Client, url: reqwest::Url, endpoint: &str) -> Option<String> {
+    let resp = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/soap+xml; charset=utf-8")
+        .body(get_message(endpoint))
+        .send()
+        .await
+        .ok()?;
+    resp.text().await.ok()
+}
+
+async fn to_discovered(client: &reqwest::Client, m: ProbeMatch) -> Discovered {
+    let url = pick_xaddr(&m);
+    let metadata = match (&url, &m.endpoint) {
+        (Some(url), Some(endpoint)) => fetch_metadata(client, url.clone(), endpoint).await,
+        _ => None,
+    };
+    let metadata = metadata.as_deref().unwrap_or("");
+    let model = xml_text(metadata, "ModelName").map(|model| match xml_text(metadata, "Manufacturer") {
+        Some(mfg) if !model.starts_with(&mfg) => format!("{} {}", mfg, model),
+        _ => model,
+    });
+
+    let mut txt = BTreeMap::new();
+    txt.insert("xaddrs".to_string(), m.xaddrs.join(" "));
+    if let Some(types) = m.types {
+        txt.insert("types".to_string(), types);
+    }
+    Discovered {
+        ip: m.ip,
+        port: url.and_then(|u| u.port_or_known_default()).unwrap_or(80),
+        source: "WSD",
+        name: xml_text(metadata, "FriendlyName"),
+        model,
+        txt,
+    }
+}
+
+/// 向 239.255.255.250:3702 组播 WS-Discovery Probe，在 `wait` 时间内收集 ProbeMatches，
+/// 再逐台用 WS-Transfer Get 读取型号
+pub async fn probe(wait: Duration) -> Vec<Discovered> {
+    let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await {
+        Ok(s) => s,
+        Err(e) => { eprintln!("{}", tr!(WsdFailed, e)); return Vec::new(); }
+    };
+    let target = SocketAddr::new(IpAddr::V4(WSD_MULTICAST_V4), WSD_PORT);
+    // UDP 可能丢包，按规范建议重复发送
+    for _ in 0..2 {
+        if let Err(e) = socket.send_to(probe_message().as_bytes(), target).await {
+            eprintln!("{}", tr!(WsdFailed, e));
+            return Vec::new();
+        }
+    }
+
+    let deadline = Instant::now() + wait;
+    let mut matches: BTreeMap<IpAddr, ProbeMatch> = BTreeMap::new();
+    let mut buf = vec![0u8; 65535];
+    while let Ok(Ok((n, from))) = timeout_at(deadline, socket.recv_from(&mut buf)).await {
+        let body = String::from_utf8_lossy(&buf[..n]);
+        if let Some(m) = parse_probe_match(from.ip(), &body) {
+            matches.entry(m.ip).or_insert(m);
+        }
+    }
+
+    let Ok(client) = reqwest::Client::builder().timeout(wait.max(Duration::from_secs(2))).build() else {
+        return Vec::new();
+    };
+    futures::future::join_all(matches.into_values().map(|m| to_discovered(&client, m))).await
+}
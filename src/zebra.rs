@@ -0,0 +1,421 @@
+use crate::probes::{self, Source};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::timeout;
+
+/// Link-OS 的 JSON 配置通道，和 9100 上的 SGD 用同一套变量，但一次能读多个
+pub const JSON_PORT: u16 = 9200;
+
+const PRODUCT_NAME: &str = "device.product_name";
+/// 固件版本，如 `V75.20.01Z`
+pub const FIRMWARE: &str = "appl.name";
+pub const SERIAL: &str = "device.unique_id";
+/// 网络连接状态，结果中的名称和 SGD 变量
+const LINK_VARS: &[(&str, &str)] = &[
+    ("interface", "interface.network.active.protocol"),
+    ("speed", "interface.network.active.speed"),
+    ("wlan_associated", "wlan.associated"),
+    ("wlan_signal", "wlan.signal_strength"),
+];
+/// 里程计，结果中的计数器名称和 SGD 变量
+const ODOMETER_VARS: &[(&str, &str)] = &[
+    ("labels", "odometer.total_label_count"),
+    ("print_length_cm", "odometer.total_print_length"),
+    ("head_cleaned", "odometer.headclean"),
+];
+
+const STX: u8 = 0x02;
+const ETX: u8 = 0x03;
+
+/// JSON 通道返回的设备信息
+pub struct LinkOs {
+    pub model: Option<String>,
+    pub firmware: Option<String>,
+    pub serial: Option<String>,
+    pub link: BTreeMap<String, String>,
+    pub odometer: BTreeMap<String, u64>,
+}
+
+/// `{}{"var":null,...}` 是 JSON 形式的 getvar，返回 `{"var":"value",...}`
+fn request() -> String {
+    let vars = [PRODUCT_NAME, FIRMWARE, SERIAL].into_iter().chain(LINK_VARS.iter().chain(ODOMETER_VARS).map(|(_, var)| *var));
+    let map: Map<String, Value> = vars.map(|var| (var.to_string(), Value::Null)).collect();
+    format!("{{}}{}", Value::Object(map))
+}
+
+fn parse(raw: &[u8]) -> Option<LinkOs> {
+    let text = String::from_utf8_lossy(raw);
+    let start = text.find('{')?;
+    let values: Map<String, Value> = serde_json::from_str(&text[start..]).ok()?;
+    let get = |var: &str| {
+        let value = values.get(var)?.as_str()?.trim();
+        // 不支持的变量回 `?`
+        (!value.is_empty() && value != "?").then(|| value.to_string())
+    };
+    let link = LINK_VARS.iter().filter_map(|(name, var)| Some((name.to_string(), get(var)?))).collect();
+    let odometer = ODOMETER_VARS
+        .iter()
+        .filter_map(|(name, var)| {
+            // 长度带单位，如 `3 cm`、`1,234 LABELS`
+            let digits: String = get(var)?.split_whitespace().next()?.chars().filter(char::is_ascii_digit).collect();
+            Some((name.to_string(), digits.parse().ok()?))
+        })
+        .collect();
+    let info = LinkOs { model: get(PRODUCT_NAME), firmware: get(FIRMWARE), serial: get(SERIAL), link, odometer };
+    (info.model.is_some() || info.firmware.is_some()).then_some(info)
+}
+
+/// 连接 9200 发送一次 JSON 查询，对象收完整 (括号配平) 或等待超时后解析。
+/// 不是 Link-OS 的老机型没有这个端口，返回 None
+pub async fn link_os(source: Source, ip: IpAddr, timeout_ms: u64) -> Option<LinkOs> {
+    let addr = SocketAddr::new(ip, JSON_PORT);
+    let mut stream = timeout(Duration::from_millis(timeout_ms), probes::connect(source, addr)).await.ok()?.ok()?;
+    stream.write_all(request().as_bytes()).await.ok()?;
+    let mut raw = Vec::new();
+    let mut buffer = [0u8; 2048];
+    while let Ok(Ok(n)) = timeout(Duration::from_millis(1500), stream.read(&mut buffer)).await {
+        if n == 0 {
+            break;
+        }
+        raw.extend(&buffer[..n]);
+        let open = raw.iter().filter(|&&b| b == b'{').count();
+        if open > 0 && open == raw.iter().filter(|&&b| b == b'}').count() {
+            break;
+        }
+    }
+    parse(&raw)
+}
+
+/// 在打印端口上发送一条 ZPL 状态指令，读到 `frames` 个 `STX ... ETX` 帧或等待超时为止
+async fn status_command(source: Source, addr: SocketAddr, timeout_ms: u64, cmd: &[u8], frames: usize) -> Option<Vec<u8>> {
+    let mut stream = timeout(Duration::from_millis(timeout_ms), probes::connect(source, addr)).await.ok()?.ok()?;
+    stream.write_all(cmd).await.ok()?;
+    let mut raw = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while let Ok(Ok(n)) = timeout(Duration::from_millis(1000), stream.read(&mut buffer)).await {
+        if n == 0 {
+            break;
+        }
+        raw.extend(&buffer[..n]);
+        if raw.iter().filter(|&&b| b == ETX).count() >= frames {
+            break;
+        }
+    }
+    (!raw.is_empty()).then_some(raw)
+}
+
+/// `~HS` 主机状态：三个 `STX ... ETX` 字符串中的各个标志
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HostStatus {
+    pub paper_out: bool,
+    pub paused: bool,
+    pub head_open: bool,
+    pub ribbon_out: bool,
+    pub under_temperature: bool,
+    pub over_temperature: bool,
+    /// 标签长度 (点)
+    pub label_length_dots: u32,
+    /// 当前批次剩余的标签数
+    pub labels_remaining: u32,
+    /// 接收缓冲区中待打印的格式数
+    pub formats_in_buffer: u32,
+    /// `~HQES` 报告的错误，只在指定 `--with-status` 时查询
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+    /// `~HQES` 报告的警告
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl HostStatus {
+    /// 写入 `status` 字段的简要状态，按影响打印的严重程度取第一个
+    pub fn summary(&self) -> &str {
+        if let Some(error) = self.errors.first() {
+            error
+        } else if self.head_open {
+            "head open"
+        } else if self.paper_out {
+            "paper out"
+        } else if self.ribbon_out {
+            "ribbon out"
+        } else if self.paused {
+            "paused"
+        } else if self.under_temperature || self.over_temperature {
+            "head temperature"
+        } else {
+            "idle"
+        }
+    }
+}
+
+/// 字符串 1：`aaa,b,c,dddd,eee,f,g,h,iii,j,k,l`，b 缺纸、c 暂停、dddd 标签长度、eee 缓冲区格式数、
+/// k / l 欠温 / 过温；字符串 2：`mmm,n,o,p,q,r,s,t,uuuuuuuu,v,www`，o 打印头打开、p 碳带用完、
+/// uuuuuuuu 批次剩余标签数。字符串 3 是密码和内存，不需要
+fn parse_host_status(raw: &[u8]) -> Option<HostStatus> {
+    let text = String::from_utf8_lossy(raw);
+    let mut frames = text.split(STX as char).skip(1).map(|f| f.split(ETX as char).next().unwrap_or_default().trim().split(',').collect::<Vec<_>>());
+    let first = frames.next().filter(|f| f.len() >= 12)?;
+    let second = frames.next().filter(|f| f.len() >= 9)?;
+    let flag = |v: &str| v.trim() == "1";
+    let number = |v: &str| v.trim().parse().ok();
+    Some(HostStatus {
+        paper_out: flag(first[1]),
+        paused: flag(first[2]),
+        head_open: flag(second[2]),
+        ribbon_out: flag(second[3]),
+        under_temperature: flag(first[10]),
+        over_temperature: flag(first[11]),
+        label_length_dots: number(first[3])?,
+        labels_remaining: number(second[8])?,
+        formats_in_buffer: number(first[4])?,
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
+
+/// 在打印端口上发送 `~HS` 读取主机状态
+pub async fn host_status(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<HostStatus> {
+    parse_host_status(&status_command(source, addr, timeout_ms, b"~HS", 3).await?)
+}
+
+/// `~HQES` 错误标志 (第 1 组) 的位和含义
+const ERROR_FLAGS: &[(u32, &str)] = &[
+    (0x0000_0001, "media out"),
+    (0x0000_0002, "ribbon out"),
+    (0x0000_0004, "head open"),
+    (0x0000_0008, "cutter fault"),
+    (0x0000_0010, "head over temperature"),
+    (0x0000_0020, "motor over temperature"),
+    (0x0000_0040, "bad head element"),
+    (0x0000_0080, "head detection error"),
+    (0x0000_0100, "invalid firmware config"),
+    (0x0000_0200, "head thermistor open"),
+    (0x0000_0400, "clear paper path failed"),
+    (0x0000_0800, "paper feed error"),
+    (0x0000_1000, "presenter not running"),
+    (0x0000_2000, "paper jam during retract"),
+    (0x0000_4000, "black mark not found"),
+    (0x0000_8000, "black mark calibrate error"),
+    (0x0001_0000, "retract timed out"),
+    (0x0002_0000, "paused"),
+];
+/// `~HQES` 警告标志 (第 1 组)
+const WARNING_FLAGS: &[(u32, &str)] = &[
+    (0x0000_0001, "needs media calibration"),
+    (0x0000_0002, "clean head"),
+    (0x0000_0004, "replace head"),
+    (0x0000_0008, "paper near end"),
+    (0x0000_0010, "sensor 1 (paper before head)"),
+    (0x0000_0020, "sensor 2 (black mark)"),
+    (0x0000_0040, "sensor 3 (paper after head)"),
+    (0x0000_0080, "sensor 4 (loop ready)"),
+    (0x0000_0100, "sensor 5 (presenter)"),
+    (0x0000_0200, "sensor 6 (retract ready)"),
+    (0x0000_0400, "sensor 7 (in retract)"),
+    (0x0000_0800, "sensor 8 (at bin)"),
+];
+
+/// `ERRORS:   1 00000000 00000005` 这样的一行：第一个数为 1 表示有标志，后面是第 2 组和第 1 组的十六进制位。
+/// 第 2 组目前没有定义，只解码第 1 组；没有对应含义的位按十六进制显示
+fn decode_flags(text: &str, label: &str, table: &[(u32, &str)]) -> Option<Vec<String>> {
+    let line = text.lines().find(|l| l.trim_start().starts_with(label))?;
+    let fields: Vec<&str> = line.split_once(':')?.1.split_whitespace().collect();
+    let bits = u32::from_str_radix(fields.get(2)?, 16).ok()?;
+    let known = table.iter().fold(0, |mask, (bit, _)| mask | bit);
+    let mut names: Vec<String> = table.iter().filter(|(bit, _)| bits & bit != 0).map(|(_, name)| name.to_string()).collect();
+    if bits & !known != 0 {
+        names.push(format!("0x{:08x}", bits & !known));
+    }
+    Some(names)
+}
+
+/// 在打印端口上发送 `~HQES`，返回错误和警告
+pub async fn error_status(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<(Vec<String>, Vec<String>)> {
+    let raw = status_command(source, addr, timeout_ms, b"~HQES", 1).await?;
+    let text = String::from_utf8_lossy(&raw);
+    Some((decode_flags(&text, "ERRORS", ERROR_FLAGS)?, decode_flags(&text, "WARNINGS", WARNING_FLAGS).unwrap_or_default()))
+}
+
+/// `^HH` 配置标签中的主要设置，其余的按名称放在 `settings`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ZebraConfig {
+    /// 打印浓度，如 `+10.0`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub darkness: Option<String>,
+    /// 打印速度，如 `4 IPS`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub print_speed: Option<String>,
+    /// 介质类型，如 `GAP/NOTCH`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    /// 打印方式，如 `THERMAL-TRANS.`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub print_method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub settings: BTreeMap<String, String>,
+}
+
+/// 配置标签每行是 `值....... 名称` (网络返回时点线可能是空格)，名称转成小写加下划线作为键。
+/// 值和名称之间至少隔两个点或空格，值本身可以带单个空格 (`4 IPS`)
+fn config_line(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    let bytes = line.as_bytes();
+    let start = (1..bytes.len()).find(|&i| matches!(bytes[i], b'.' | b' ') && matches!(bytes.get(i + 1), Some(b'.' | b' ')))?;
+    let (value, rest) = line.split_at(start);
+    let name = rest.trim_start_matches(['.', ' ']);
+    if name.is_empty() || name.chars().any(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    Some((name.to_lowercase().replace([' ', '-'], "_"), value.trim().to_string()))
+}
+
+fn parse_config(raw: &[u8]) -> Option<ZebraConfig> {
+    let text = String::from_utf8_lossy(raw).replace([STX as char, ETX as char], "");
+    let mut settings: BTreeMap<String, String> = text.lines().filter_map(config_line).collect();
+    if settings.is_empty() {
+        return None;
+    }
+    Some(ZebraConfig {
+        darkness: settings.remove("darkness"),
+        print_speed: settings.remove("print_speed"),
+        media_type: settings.remove("media_type"),
+        print_method: settings.remove("print_method"),
+        serial: settings.remove("serial_number"),
+        settings,
+    })
+}
+
+/// 在打印端口上发送 `^XA^HH^XZ`，把配置标签的内容传回主机而不实际打印
+pub async fn config_label(source: Source, addr: SocketAddr, timeout_ms: u64) -> Option<ZebraConfig> {
+    parse_config(&status_command(source, addr, timeout_ms, b"^XA^HH^XZ", 1).await?)
+}
+
+/// `--sgd-vars` 中表示读取全部变量的名称
+pub const ALL_VARS: &str = "allcv";
+
+/// 读到连接关闭或静默 `quiet_ms` 为止，`allcv` 的输出有几十 KB，分很多段到达
+async fn read_all(stream: &mut tokio::net::TcpStream, quiet_ms: u64) -> Vec<u8> {
+    let mut raw = Vec::new();
+    let mut buffer = [0u8; 4096];
+    while let Ok(Ok(n)) = timeout(Duration::from_millis(quiet_ms), stream.read(&mut buffer)).await {
+        if n == 0 || raw.len() > 1 << 20 {
+            break;
+        }
+        raw.extend(&buffer[..n]);
+    }
+    raw
+}
+
+/// `allcv` 每行是 `名称 : 值 , Choices: ...`，值可能带引号
+fn parse_allcv(raw: &[u8]) -> BTreeMap<String, String> {
+    String::from_utf8_lossy(raw)
+        .lines()
+        .filter_map(|line| {
+            let (name, rest) = line.split_once(" : ")?;
+            let value = rest.split_once(" , Choices:").map_or(rest, |(v, _)| v);
+            let (name, value) = (name.trim(), value.trim().trim_matches('"'));
+            (!name.is_empty() && !name.contains(' ')).then(|| (name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// 读取 SGD 变量：列表中有 `allcv` 时一次取回全部，否则在同一连接上逐个 getvar。
+/// 不支持的变量回 `"?"`，不放进结果
+pub async fn sgd_vars(source: Source, addr: SocketAddr, timeout_ms: u64, vars: &[String]) -> BTreeMap<String, String> {
+    let Ok(Ok(mut stream)) = timeout(Duration::from_millis(timeout_ms), probes::connect(source, addr)).await else { return BTreeMap::new() };
+    if vars.iter().any(|v| v == ALL_VARS) {
+        if stream.write_all(b"! U1 getvar \"allcv\"\r\n").await.is_err() {
+            return BTreeMap::new();
+        }
+        return parse_allcv(&read_all(&mut stream, 1500).await);
+    }
+    let mut values = BTreeMap::new();
+    for var in vars {
+        if stream.write_all(format!("! U1 getvar \"{}\"\r\n", var).as_bytes()).await.is_err() {
+            break;
+        }
+        let raw = read_all(&mut stream, 500).await;
+        let value = String::from_utf8_lossy(&raw).trim().trim_matches('"').to_string();
+        if !value.is_empty() && value != "?" {
+            values.insert(var.clone(), value);
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDLE: &[u8] = b"\x02030,0,0,0245,000,0,0,0,000,0,0,0\x03\r\n\x02001,0,0,0,1,2,6,0,00000000,1,000\x03\r\n\x021234,0\x03\r\n";
+
+    #[test]
+    fn idle_printer_reports_label_length() {
+        let status = parse_host_status(IDLE).unwrap();
+        assert_eq!(status.label_length_dots, 245);
+        assert_eq!((status.formats_in_buffer, status.labels_remaining), (0, 0));
+        assert_eq!(status.summary(), "idle");
+    }
+
+    #[test]
+    fn flags_come_from_both_frames() {
+        let raw = b"\x02030,1,1,0812,003,0,0,0,000,0,1,0\x03\r\n\x02001,0,1,1,1,2,6,0,00000012,1,000\x03\r\n";
+        let status = parse_host_status(raw).unwrap();
+        assert!(status.paper_out && status.paused && status.under_temperature && !status.over_temperature);
+        assert!(status.head_open && status.ribbon_out);
+        assert_eq!((status.formats_in_buffer, status.labels_remaining), (3, 12));
+        // 打印头打开比缺纸更要紧
+        assert_eq!(status.summary(), "head open");
+    }
+
+    #[test]
+    fn summary_follows_severity() {
+        let mut status = parse_host_status(IDLE).unwrap();
+        status.over_temperature = true;
+        assert_eq!(status.summary(), "head temperature");
+        status.paused = true;
+        assert_eq!(status.summary(), "paused");
+        status.ribbon_out = true;
+        assert_eq!(status.summary(), "ribbon out");
+        status.paper_out = true;
+        assert_eq!(status.summary(), "paper out");
+    }
+
+    #[test]
+    fn short_or_missing_frames_are_rejected() {
+        // 第一个字符串缺最后一个字段
+        assert!(parse_host_status(b"\x02030,0,0,0245,000,0,0,0,000,0,0\x03\x02001,0,0,0,1,2,6,0,00000000\x03").is_none());
+        // 只收到第一个字符串
+        assert!(parse_host_status(b"\x02030,0,0,0245,000,0,0,0,000,0,0,0\x03\r\n").is_none());
+        assert!(parse_host_status(b"\x02030,0,0,abcd,000,0,0,0,000,0,0,0\x03\x02001,0,0,0,1,2,6,0,00000000\x03").is_none());
+        assert!(parse_host_status(b"030,0,0,0245,000,0,0,0,000,0,0,0").is_none());
+    }
+
+    const HQES: &str = "\x02\r\n\r\n  PRINTER STATUS                            \r\n   ERRORS:         1 00000000 00000005     \r\n   WARNINGS:       1 00000000 00000002     \r\n\x03";
+
+    #[test]
+    fn hqes_bits_are_named() {
+        assert_eq!(decode_flags(HQES, "ERRORS", ERROR_FLAGS).unwrap(), ["media out", "head open"]);
+        assert_eq!(decode_flags(HQES, "WARNINGS", WARNING_FLAGS).unwrap(), ["clean head"]);
+    }
+
+    #[test]
+    fn hqes_unknown_bits_are_shown_in_hex() {
+        let text = "ERRORS: 1 00000000 80020001\r\nWARNINGS: 0 00000000 00000000\r\n";
+        assert_eq!(decode_flags(text, "ERRORS", ERROR_FLAGS).unwrap(), ["media out", "paused", "0x80000000"]);
+        assert!(decode_flags(text, "WARNINGS", WARNING_FLAGS).unwrap().is_empty());
+    }
+
+    #[test]
+    fn hqes_needs_third_field() {
+        assert_eq!(decode_flags("ERRORS: 1 00000000\r\n", "ERRORS", ERROR_FLAGS), None);
+        assert_eq!(decode_flags("ERRORS: 1 00000000 zzzzzzzz\r\n", "ERRORS", ERROR_FLAGS), None);
+        assert_eq!(decode_flags(HQES, "ALERTS", ERROR_FLAGS), None);
+    }
+}